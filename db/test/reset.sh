@@ -14,8 +14,14 @@ delete_entries() {
     docker exec -i "$DB_CONTAINER_NAME" psql -U "$DB_USER" -d "$DB_NAME" -c "DELETE FROM $1;"
 }
 
-# Delete entries from different tables and measure time
+# Delete entries from different tables and measure time, referencing tables before the tables they reference.
+# Reference data seeded by the migrations (regions, constellations, star systems and ship classes) is kept, as location and ship writes need it
+delete_entries "players"
 delete_entries "ships"
 delete_entries "empires"
+delete_entries "territory_claims"
+delete_entries "sovereignty_changes"
+delete_entries "stargates"
 delete_entries "locations"
+delete_entries "audit_events"
 delete_entries "users"
@@ -0,0 +1,4 @@
+-- Drop the row versions used for optimistic concurrency control
+ALTER TABLE empires DROP COLUMN version;
+ALTER TABLE locations DROP COLUMN version;
+ALTER TABLE users DROP COLUMN version;
@@ -0,0 +1,4 @@
+-- Add row versions used as entity tags. Every write increments the version of the row
+ALTER TABLE users ADD COLUMN version INT4 NOT NULL DEFAULT 1;
+ALTER TABLE locations ADD COLUMN version INT4 NOT NULL DEFAULT 1;
+ALTER TABLE empires ADD COLUMN version INT4 NOT NULL DEFAULT 1;
@@ -0,0 +1,12 @@
+-- Drop the timestamp columns together with their triggers and indexes
+DROP TRIGGER set_updated_at ON players;
+DROP TRIGGER set_updated_at ON ships;
+DROP TRIGGER set_updated_at ON empires;
+DROP TRIGGER set_updated_at ON locations;
+DROP TRIGGER set_updated_at ON users;
+
+ALTER TABLE players DROP COLUMN created_at, DROP COLUMN updated_at;
+ALTER TABLE ships DROP COLUMN created_at, DROP COLUMN updated_at;
+ALTER TABLE empires DROP COLUMN created_at, DROP COLUMN updated_at;
+ALTER TABLE locations DROP COLUMN created_at, DROP COLUMN updated_at;
+ALTER TABLE users DROP COLUMN created_at, DROP COLUMN updated_at;
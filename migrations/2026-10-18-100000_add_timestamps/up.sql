@@ -0,0 +1,20 @@
+-- Add creation and modification timestamps to every table. Existing rows are stamped with the time of the migration
+-- and 'updated_at' is maintained by the trigger from the initial diesel setup
+ALTER TABLE users ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(), ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE locations ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(), ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE empires ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(), ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE ships ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(), ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE players ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(), ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+SELECT diesel_manage_updated_at('users');
+SELECT diesel_manage_updated_at('locations');
+SELECT diesel_manage_updated_at('empires');
+SELECT diesel_manage_updated_at('ships');
+SELECT diesel_manage_updated_at('players');
+
+-- Incremental sync reads rows ordered by modification time
+CREATE INDEX users_updated_at_idx ON users (updated_at, id);
+CREATE INDEX locations_updated_at_idx ON locations (updated_at, id);
+CREATE INDEX empires_updated_at_idx ON empires (updated_at, id);
+CREATE INDEX ships_updated_at_idx ON ships (updated_at, id);
+CREATE INDEX players_updated_at_idx ON players (updated_at, id);
@@ -0,0 +1,8 @@
+-- Remove soft deleted rows before restoring the plain unique constraint on email
+DELETE FROM users WHERE deleted_at IS NOT NULL;
+DROP INDEX users_email_key;
+ALTER TABLE users ADD CONSTRAINT users_email_key UNIQUE (email);
+
+ALTER TABLE empires DROP COLUMN deleted_at;
+ALTER TABLE locations DROP COLUMN deleted_at;
+ALTER TABLE users DROP COLUMN deleted_at;
@@ -0,0 +1,15 @@
+-- Deleting a row sets 'deleted_at' instead of removing it. Deleted rows are hidden from reads and removed for good once
+-- they are older than the retention period
+ALTER TABLE users ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE locations ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE empires ADD COLUMN deleted_at TIMESTAMPTZ;
+
+-- A deleted account must not block registering its email again, so uniqueness only covers live users.
+-- The index keeps the name of the constraint it replaces, which clients may match in error messages
+ALTER TABLE users DROP CONSTRAINT users_email_key;
+CREATE UNIQUE INDEX users_email_key ON users (email) WHERE deleted_at IS NULL;
+
+-- Purging looks up deleted rows by age
+CREATE INDEX users_deleted_at_idx ON users (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX locations_deleted_at_idx ON locations (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX empires_deleted_at_idx ON empires (deleted_at) WHERE deleted_at IS NOT NULL;
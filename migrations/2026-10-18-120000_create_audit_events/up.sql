@@ -0,0 +1,16 @@
+-- Create the audit_events table recording every mutating operation of the locations, empires and users routers
+CREATE TABLE audit_events (
+                              id BIGSERIAL PRIMARY KEY,
+                              actor VARCHAR(100),
+                              action VARCHAR(20) NOT NULL,
+                              resource_type VARCHAR(20) NOT NULL,
+                              resource_id INT4 NOT NULL,
+                              before JSONB,
+                              after JSONB,
+                              request_id VARCHAR(100),
+                              created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX audit_events_created_at_idx ON audit_events (created_at, id);
+CREATE INDEX audit_events_resource_idx ON audit_events (resource_type, resource_id);
+CREATE INDEX audit_events_actor_idx ON audit_events (actor);
@@ -0,0 +1,15 @@
+-- Create the stargates table. A stargate connects two locations and is jumped in both directions,
+-- so a pair of locations is connected at most once regardless of the order it was given in
+CREATE TABLE stargates (
+                          id SERIAL PRIMARY KEY,
+                          from_location_id INT REFERENCES locations(id) NOT NULL,
+                          to_location_id INT REFERENCES locations(id) NOT NULL,
+                          created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                          updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                          CONSTRAINT stargates_distinct_locations CHECK (from_location_id <> to_location_id)
+);
+
+CREATE UNIQUE INDEX stargates_locations_key ON stargates (LEAST(from_location_id, to_location_id), GREATEST(from_location_id, to_location_id));
+CREATE INDEX stargates_to_location_id_idx ON stargates (to_location_id);
+
+SELECT diesel_manage_updated_at('stargates');
@@ -0,0 +1,2 @@
+-- Delete data from the 'stargates' table
+DELETE FROM stargates WHERE (from_location_id, to_location_id) IN ((1, 2), (1, 3), (2, 4), (3, 4), (4, 5), (2, 5));
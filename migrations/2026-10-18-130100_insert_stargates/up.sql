@@ -0,0 +1,9 @@
+-- Inserting data into the 'stargates' table, connecting the seeded locations
+-- New Eden - Genesis - Domain (Amarr) - Delve, with The Forge (Jita) bridging New Eden and Domain and a back door from Genesis into Delve
+INSERT INTO stargates (from_location_id, to_location_id) VALUES
+                                                             (1, 2),
+                                                             (1, 3),
+                                                             (2, 4),
+                                                             (3, 4),
+                                                             (4, 5),
+                                                             (2, 5);
@@ -0,0 +1,7 @@
+DROP INDEX locations_star_system_area_key;
+DROP INDEX locations_star_system_idx;
+ALTER TABLE locations DROP CONSTRAINT locations_star_system_fkey;
+
+DROP TABLE star_systems;
+DROP TABLE constellations;
+DROP TABLE regions;
@@ -0,0 +1,49 @@
+-- Star systems are grouped into constellations and constellations into regions. Like in New Eden, names are unique across the universe
+CREATE TABLE regions (
+                        id SERIAL PRIMARY KEY,
+                        name VARCHAR(100) NOT NULL UNIQUE,
+                        created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                        updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE constellations (
+                               id SERIAL PRIMARY KEY,
+                               region_id INT REFERENCES regions(id) NOT NULL,
+                               name VARCHAR(100) NOT NULL UNIQUE,
+                               created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                               updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE star_systems (
+                             id SERIAL PRIMARY KEY,
+                             constellation_id INT REFERENCES constellations(id) NOT NULL,
+                             name VARCHAR(100) NOT NULL UNIQUE,
+                             created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                             updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX constellations_region_id_idx ON constellations (region_id);
+CREATE INDEX star_systems_constellation_id_idx ON star_systems (constellation_id);
+
+SELECT diesel_manage_updated_at('regions');
+SELECT diesel_manage_updated_at('constellations');
+SELECT diesel_manage_updated_at('star_systems');
+
+-- The free text holds no regions or constellations, so the star systems of existing locations start out in 'Unassigned'
+INSERT INTO regions (name) VALUES ('Unassigned');
+INSERT INTO constellations (region_id, name) SELECT id, 'Unassigned' FROM regions WHERE name = 'Unassigned';
+INSERT INTO star_systems (constellation_id, name)
+SELECT constellations.id, location_systems.star_system
+FROM (SELECT DISTINCT star_system FROM locations) AS location_systems, constellations
+WHERE constellations.name = 'Unassigned';
+
+-- Locations name their star system, and renaming a star system renames it in its locations
+ALTER TABLE locations ADD CONSTRAINT locations_star_system_fkey FOREIGN KEY (star_system) REFERENCES star_systems (name) ON UPDATE CASCADE;
+CREATE INDEX locations_star_system_idx ON locations (star_system);
+
+-- Of locations sharing a star system and area only the oldest stays visible, the others are marked deleted
+UPDATE locations SET deleted_at = NOW(), version = version + 1
+WHERE deleted_at IS NULL
+  AND id NOT IN (SELECT MIN(id) FROM locations WHERE deleted_at IS NULL GROUP BY star_system, area);
+
+CREATE UNIQUE INDEX locations_star_system_area_key ON locations (star_system, area) WHERE deleted_at IS NULL;
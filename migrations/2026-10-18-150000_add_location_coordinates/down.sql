@@ -0,0 +1,10 @@
+DROP INDEX locations_position_idx;
+DROP FUNCTION location_position;
+
+ALTER TABLE locations
+    DROP CONSTRAINT locations_coordinates_check,
+    DROP COLUMN x,
+    DROP COLUMN y,
+    DROP COLUMN z;
+
+DROP EXTENSION IF EXISTS cube;
@@ -0,0 +1,22 @@
+CREATE EXTENSION IF NOT EXISTS cube;
+
+-- Position in light years. A location has either all three coordinates or none
+ALTER TABLE locations
+    ADD COLUMN x DOUBLE PRECISION,
+    ADD COLUMN y DOUBLE PRECISION,
+    ADD COLUMN z DOUBLE PRECISION,
+    ADD CONSTRAINT locations_coordinates_check CHECK ((x IS NULL) = (y IS NULL) AND (y IS NULL) = (z IS NULL));
+
+-- Point of a position, NULL rather than an error when coordinates are missing so it is safe to index every row
+CREATE FUNCTION location_position(x DOUBLE PRECISION, y DOUBLE PRECISION, z DOUBLE PRECISION) RETURNS cube AS $$
+    SELECT cube(cube(cube(x), y), z)
+$$ LANGUAGE SQL IMMUTABLE STRICT;
+
+-- Radius queries look up the bounding cube of the sphere in this index before measuring exact distances
+CREATE INDEX locations_position_idx ON locations USING gist (location_position(x, y, z)) WHERE deleted_at IS NULL;
+
+UPDATE locations SET x = 0.0, y = 0.0, z = 0.0 WHERE star_system = 'New Eden' AND area = 'Yulai';
+UPDATE locations SET x = 6.2, y = 1.4, z = -3.9 WHERE star_system = 'Genesis' AND area = 'Safizon';
+UPDATE locations SET x = -8.5, y = 2.3, z = 4.1 WHERE star_system = 'The Forge' AND area = 'Jita';
+UPDATE locations SET x = 14.7, y = -1.8, z = 9.6 WHERE star_system = 'Domain' AND area = 'Amarr';
+UPDATE locations SET x = 38.2, y = 4.9, z = -21.3 WHERE star_system = 'Delve' AND area = 'VFK-IV';
@@ -0,0 +1,8 @@
+DROP TRIGGER locations_sovereignty_history ON locations;
+DROP FUNCTION record_sovereignty_change;
+DROP TABLE sovereignty_changes;
+
+DROP INDEX locations_security_status_idx;
+ALTER TABLE locations
+    DROP COLUMN sovereign_empire_id,
+    DROP COLUMN security_status;
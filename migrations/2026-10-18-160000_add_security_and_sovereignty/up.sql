@@ -0,0 +1,40 @@
+-- Security status from -1.0 to 1.0 as in New Eden: high-sec from 0.45, low-sec above 0.0 and null-sec below
+ALTER TABLE locations
+    ADD COLUMN security_status DOUBLE PRECISION NOT NULL DEFAULT 0.0 CHECK (security_status BETWEEN -1.0 AND 1.0),
+    ADD COLUMN sovereign_empire_id INT REFERENCES empires(id) ON DELETE SET NULL;
+
+CREATE INDEX locations_security_status_idx ON locations (security_status);
+
+-- Every change of the sovereign empire of a location, written by a trigger so that no kind of write is missed
+CREATE TABLE sovereignty_changes (
+                                    id SERIAL PRIMARY KEY,
+                                    location_id INT REFERENCES locations(id) ON DELETE CASCADE NOT NULL,
+                                    -- Ids of empires that may since have been purged, NULL when the location was unclaimed
+                                    previous_empire_id INT,
+                                    empire_id INT,
+                                    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX sovereignty_changes_location_id_idx ON sovereignty_changes (location_id, changed_at);
+
+CREATE FUNCTION record_sovereignty_change() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        IF NEW.sovereign_empire_id IS NOT NULL THEN
+            INSERT INTO sovereignty_changes (location_id, previous_empire_id, empire_id) VALUES (NEW.id, NULL, NEW.sovereign_empire_id);
+        END IF;
+    ELSIF NEW.sovereign_empire_id IS DISTINCT FROM OLD.sovereign_empire_id THEN
+        INSERT INTO sovereignty_changes (location_id, previous_empire_id, empire_id) VALUES (NEW.id, OLD.sovereign_empire_id, NEW.sovereign_empire_id);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER locations_sovereignty_history AFTER INSERT OR UPDATE OF sovereign_empire_id ON locations
+    FOR EACH ROW EXECUTE FUNCTION record_sovereignty_change();
+
+UPDATE locations SET security_status = 1.0 WHERE star_system = 'New Eden' AND area = 'Yulai';
+UPDATE locations SET security_status = 0.4, sovereign_empire_id = (SELECT id FROM empires WHERE name = 'Gallente Federation') WHERE star_system = 'Genesis' AND area = 'Safizon';
+UPDATE locations SET security_status = 0.9, sovereign_empire_id = (SELECT id FROM empires WHERE name = 'Caldari State') WHERE star_system = 'The Forge' AND area = 'Jita';
+UPDATE locations SET security_status = 1.0, sovereign_empire_id = (SELECT id FROM empires WHERE name = 'Amarr Empire') WHERE star_system = 'Domain' AND area = 'Amarr';
+UPDATE locations SET security_status = -0.4, sovereign_empire_id = (SELECT id FROM empires WHERE name = 'Pirate Coalition') WHERE star_system = 'Delve' AND area = 'VFK-IV';
@@ -0,0 +1,5 @@
+DROP TRIGGER locations_territory_claims ON locations;
+DROP FUNCTION follow_sovereignty_with_claims;
+DROP TABLE territory_claims;
+
+ALTER TABLE empires DROP COLUMN owner_id;
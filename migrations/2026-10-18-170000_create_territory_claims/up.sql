@@ -0,0 +1,37 @@
+-- The user who created an empire owns it and may manage its territory without being an EDITOR
+ALTER TABLE empires ADD COLUMN owner_id INT REFERENCES users(id) ON DELETE SET NULL;
+
+-- Locations claimed by empires. A claim lasts until it is released, and the live claim of a location is held by its sovereign empire
+CREATE TABLE territory_claims (
+                                  id SERIAL PRIMARY KEY,
+                                  empire_id INT REFERENCES empires(id) ON DELETE CASCADE NOT NULL,
+                                  location_id INT REFERENCES locations(id) ON DELETE CASCADE NOT NULL,
+                                  claimed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                                  released_at TIMESTAMPTZ
+);
+
+-- No two empires hold the same location
+CREATE UNIQUE INDEX territory_claims_location_id_key ON territory_claims (location_id) WHERE released_at IS NULL;
+CREATE INDEX territory_claims_empire_id_idx ON territory_claims (empire_id) WHERE released_at IS NULL;
+CREATE INDEX territory_claims_location_history_idx ON territory_claims (location_id, claimed_at);
+
+-- Claims follow the sovereign empire of the location, whether it was changed by a claim, a release or a write to the location
+CREATE FUNCTION follow_sovereignty_with_claims() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND NEW.sovereign_empire_id IS NOT DISTINCT FROM OLD.sovereign_empire_id THEN
+        RETURN NEW;
+    END IF;
+
+    UPDATE territory_claims SET released_at = NOW() WHERE location_id = NEW.id AND released_at IS NULL;
+    IF NEW.sovereign_empire_id IS NOT NULL THEN
+        INSERT INTO territory_claims (empire_id, location_id) VALUES (NEW.sovereign_empire_id, NEW.id);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER locations_territory_claims AFTER INSERT OR UPDATE OF sovereign_empire_id ON locations
+    FOR EACH ROW EXECUTE FUNCTION follow_sovereignty_with_claims();
+
+INSERT INTO territory_claims (empire_id, location_id)
+SELECT sovereign_empire_id, id FROM locations WHERE sovereign_empire_id IS NOT NULL;
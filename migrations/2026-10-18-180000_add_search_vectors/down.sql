@@ -0,0 +1,7 @@
+DROP INDEX locations_search_vector_idx;
+DROP INDEX ships_search_vector_idx;
+DROP INDEX empires_search_vector_idx;
+
+ALTER TABLE locations DROP COLUMN search_vector;
+ALTER TABLE ships DROP COLUMN search_vector;
+ALTER TABLE empires DROP COLUMN search_vector;
@@ -0,0 +1,21 @@
+-- Documents searched by 'GET /search'. Names weigh most, then slogans and categories, then descriptions
+ALTER TABLE empires ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', name), 'A') ||
+    setweight(to_tsvector('english', slogan), 'B') ||
+    setweight(to_tsvector('english', description), 'C')
+) STORED;
+
+ALTER TABLE ships ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', name), 'A') ||
+    setweight(to_tsvector('english', coalesce(category, '')), 'B') ||
+    setweight(to_tsvector('english', coalesce(description, '')), 'C')
+) STORED;
+
+ALTER TABLE locations ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', star_system), 'A') ||
+    setweight(to_tsvector('english', area), 'A')
+) STORED;
+
+CREATE INDEX empires_search_vector_idx ON empires USING gin (search_vector);
+CREATE INDEX ships_search_vector_idx ON ships USING gin (search_vector);
+CREATE INDEX locations_search_vector_idx ON locations USING gin (search_vector);
@@ -0,0 +1,4 @@
+DROP INDEX ships_category_idx;
+ALTER TABLE ships DROP CONSTRAINT ships_category_fkey;
+
+DROP TABLE ship_classes;
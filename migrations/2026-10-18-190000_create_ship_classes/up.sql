@@ -0,0 +1,36 @@
+-- Classes ships are built to, with the base stats every ship of the class starts out with
+CREATE TABLE ship_classes (
+                              id SERIAL PRIMARY KEY,
+                              name VARCHAR(50) NOT NULL UNIQUE,
+                              hull INT NOT NULL CHECK (hull >= 0),
+                              armor INT NOT NULL CHECK (armor >= 0),
+                              shield INT NOT NULL CHECK (shield >= 0),
+                              speed INT NOT NULL CHECK (speed >= 0),
+                              cargo INT NOT NULL CHECK (cargo >= 0),
+                              slots INT NOT NULL CHECK (slots >= 0),
+                              created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                              updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+SELECT diesel_manage_updated_at('ship_classes');
+
+-- Hit points of each layer, speed in m/s, cargo hold in m3 and the number of fitting slots
+INSERT INTO ship_classes (name, hull, armor, shield, speed, cargo, slots) VALUES
+                                                                              ('Frigate', 350, 400, 450, 350, 150, 8),
+                                                                              ('Interceptor', 400, 450, 500, 500, 100, 8),
+                                                                              ('Covert Ops', 350, 350, 400, 380, 200, 8),
+                                                                              ('Destroyer', 900, 1000, 1100, 250, 400, 11),
+                                                                              ('Cruiser', 1800, 2000, 2200, 200, 450, 14),
+                                                                              ('Battlecruiser', 4000, 4500, 5000, 150, 500, 16),
+                                                                              ('Battleship', 7000, 7500, 8000, 110, 650, 19),
+                                                                              ('Marauder', 9000, 9500, 10000, 120, 1000, 19),
+                                                                              ('Transport Ship', 3000, 3500, 3000, 140, 30000, 10);
+
+-- Any other category already in use becomes a class without stats, to be filled in by an editor
+INSERT INTO ship_classes (name, hull, armor, shield, speed, cargo, slots)
+SELECT DISTINCT category, 0, 0, 0, 0, 0, 0 FROM ships WHERE category IS NOT NULL
+ON CONFLICT (name) DO NOTHING;
+
+-- Ships name their class, and renaming a class renames it in its ships
+ALTER TABLE ships ADD CONSTRAINT ships_category_fkey FOREIGN KEY (category) REFERENCES ship_classes (name) ON UPDATE CASCADE;
+CREATE INDEX ships_category_idx ON ships (category);
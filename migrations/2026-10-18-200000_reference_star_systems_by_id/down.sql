@@ -0,0 +1,19 @@
+DROP INDEX star_systems_search_vector_idx;
+DROP INDEX locations_search_vector_idx;
+ALTER TABLE star_systems DROP COLUMN search_vector;
+ALTER TABLE locations DROP COLUMN search_vector;
+
+ALTER TABLE locations ADD COLUMN star_system VARCHAR(100);
+UPDATE locations SET star_system = star_systems.name FROM star_systems WHERE star_systems.id = locations.star_system_id;
+ALTER TABLE locations ALTER COLUMN star_system SET NOT NULL;
+ALTER TABLE locations DROP COLUMN star_system_id;
+
+ALTER TABLE locations ADD CONSTRAINT locations_star_system_fkey FOREIGN KEY (star_system) REFERENCES star_systems (name) ON UPDATE CASCADE;
+CREATE INDEX locations_star_system_idx ON locations (star_system);
+CREATE UNIQUE INDEX locations_star_system_area_key ON locations (star_system, area) WHERE deleted_at IS NULL;
+
+ALTER TABLE locations ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', star_system), 'A') ||
+    setweight(to_tsvector('english', area), 'A')
+) STORED;
+CREATE INDEX locations_search_vector_idx ON locations USING gin (search_vector);
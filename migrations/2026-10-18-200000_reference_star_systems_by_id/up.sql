@@ -0,0 +1,17 @@
+-- Locations refer to their star system by id instead of repeating its name, the name is read from the star system
+ALTER TABLE locations ADD COLUMN star_system_id INT REFERENCES star_systems(id);
+UPDATE locations SET star_system_id = star_systems.id FROM star_systems WHERE star_systems.name = locations.star_system;
+ALTER TABLE locations ALTER COLUMN star_system_id SET NOT NULL;
+
+-- Dropping the name drops its foreign key and indexes along with the search vector built from it
+ALTER TABLE locations DROP COLUMN search_vector;
+ALTER TABLE locations DROP COLUMN star_system;
+
+CREATE INDEX locations_star_system_id_idx ON locations (star_system_id);
+CREATE UNIQUE INDEX locations_star_system_area_key ON locations (star_system_id, area) WHERE deleted_at IS NULL;
+
+-- A location matches a search by its area or by the name of its star system
+ALTER TABLE locations ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (setweight(to_tsvector('english', area), 'A')) STORED;
+ALTER TABLE star_systems ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (setweight(to_tsvector('english', name), 'A')) STORED;
+CREATE INDEX locations_search_vector_idx ON locations USING gin (search_vector);
+CREATE INDEX star_systems_search_vector_idx ON star_systems USING gin (search_vector);
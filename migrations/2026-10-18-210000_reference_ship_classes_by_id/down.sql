@@ -0,0 +1,20 @@
+DROP FUNCTION ship_class_id_of(TEXT);
+
+DROP INDEX ship_classes_search_vector_idx;
+DROP INDEX ships_search_vector_idx;
+ALTER TABLE ship_classes DROP COLUMN search_vector;
+ALTER TABLE ships DROP COLUMN search_vector;
+
+ALTER TABLE ships ADD COLUMN category VARCHAR(50);
+UPDATE ships SET category = ship_classes.name FROM ship_classes WHERE ship_classes.id = ships.ship_class_id;
+ALTER TABLE ships DROP COLUMN ship_class_id;
+
+ALTER TABLE ships ADD CONSTRAINT ships_category_fkey FOREIGN KEY (category) REFERENCES ship_classes (name) ON UPDATE CASCADE;
+CREATE INDEX ships_category_idx ON ships (category);
+
+ALTER TABLE ships ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', name), 'A') ||
+    setweight(to_tsvector('english', coalesce(category, '')), 'B') ||
+    setweight(to_tsvector('english', coalesce(description, '')), 'C')
+) STORED;
+CREATE INDEX ships_search_vector_idx ON ships USING gin (search_vector);
@@ -0,0 +1,35 @@
+-- Ships refer to their class by id instead of repeating its name, the name is read from the class
+ALTER TABLE ships ADD COLUMN ship_class_id INT REFERENCES ship_classes(id);
+UPDATE ships SET ship_class_id = ship_classes.id FROM ship_classes WHERE ship_classes.name = ships.category;
+
+-- Dropping the name drops its foreign key and index along with the search vector built from it
+ALTER TABLE ships DROP COLUMN search_vector;
+ALTER TABLE ships DROP COLUMN category;
+
+CREATE INDEX ships_ship_class_id_idx ON ships (ship_class_id);
+
+-- A ship matches a search by its name and description or by the name of its class
+ALTER TABLE ships ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', name), 'A') ||
+    setweight(to_tsvector('english', coalesce(description, '')), 'C')
+) STORED;
+ALTER TABLE ship_classes ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (setweight(to_tsvector('english', name), 'B')) STORED;
+CREATE INDEX ships_search_vector_idx ON ships USING gin (search_vector);
+CREATE INDEX ship_classes_search_vector_idx ON ship_classes USING gin (search_vector);
+
+-- Ship writes name the class. The name is resolved within the write, and an unknown name fails like the foreign key would
+CREATE FUNCTION ship_class_id_of(class_name TEXT) RETURNS INT AS $$
+DECLARE
+    class_id INT;
+BEGIN
+    IF class_name IS NULL THEN
+        RETURN NULL;
+    END IF;
+
+    SELECT id INTO class_id FROM ship_classes WHERE name = class_name;
+    IF class_id IS NULL THEN
+        RAISE foreign_key_violation USING MESSAGE = format('Ship class "%s" does not exist', class_name), TABLE = 'ships', CONSTRAINT = 'ships_ship_class_id_fkey';
+    END IF;
+    RETURN class_id;
+END;
+$$ LANGUAGE plpgsql STABLE;
@@ -0,0 +1,79 @@
+use std::fmt;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{IntoParams, ToSchema};
+use crate::schema::audit_events;
+
+#[derive(Serialize, Debug, Clone, Queryable, ToSchema)]
+#[diesel(table_name = audit_events)]
+pub struct AuditEvent {
+    pub id: i64,
+    // Email from the JWT claims of the caller, absent for unauthenticated requests such as registrations
+    pub actor: Option<String>,
+    pub action: String,
+    pub resource_type: String,
+    pub resource_id: i32,
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<Value>,
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<Value>,
+    pub request_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = audit_events)]
+pub struct NewAuditEvent {
+    pub actor: Option<String>,
+    pub action: String,
+    pub resource_type: String,
+    pub resource_id: i32,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub request_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    Create,
+    Update,
+    Patch,
+    Delete,
+    Restore,
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuditAction::Create => write!(f, "create"),
+            AuditAction::Update => write!(f, "update"),
+            AuditAction::Patch => write!(f, "patch"),
+            AuditAction::Delete => write!(f, "delete"),
+            AuditAction::Restore => write!(f, "restore"),
+        }
+    }
+}
+
+// Filters of 'GET /admin/audit'. All given filters must match
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditQuery {
+    /// Email of the caller
+    pub actor: Option<String>,
+    /// One of create, update, patch, delete or restore
+    pub action: Option<String>,
+    /// One of location, empire, ship, stargate, user or territory_claim
+    pub resource_type: Option<String>,
+    pub resource_id: Option<i32>,
+    pub request_id: Option<String>,
+    /// Only events recorded at or after this RFC 3339 timestamp
+    pub since: Option<DateTime<Utc>>,
+    /// Only events recorded before this RFC 3339 timestamp
+    pub until: Option<DateTime<Utc>>,
+    /// Maximum number of events, between 1 and 500. Defaults to 50
+    pub limit: Option<i64>,
+    /// Number of events to skip. Defaults to 0
+    pub offset: Option<i64>,
+}
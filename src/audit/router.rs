@@ -0,0 +1,80 @@
+pub mod router {
+    use serde_json::{json, Value};
+    use tracing::error;
+    use axum::{
+        Router, http::StatusCode, Json, response::IntoResponse, extract::{rejection::QueryRejection, Query, State},
+    };
+    use http::HeaderMap;
+    use crate::{
+        audit::{
+            model::AuditQuery,
+            service::service::AuditTable
+        },
+        users::model::UserRole,
+        common::{
+            db::ConnectionPool,
+            pagination::{invalid_query, Page, Paginated},
+            security::{enforce_role_policy, decode_claims}
+        }
+    };
+
+    // - - - - - - - - - - - [ROUTES] - - - - - - - - - - -
+
+    pub fn audit_route(shared_connection_pool: ConnectionPool) -> Router {
+        Router::new()
+            .route("/admin/audit", axum::routing::get(list_audit_events_handler))
+            .with_state(shared_connection_pool)
+    }
+
+    // - - - - - - - - - - - [HANDLERS] - - - - - - - - - - -
+
+    /// List audit events
+    ///
+    /// Requires role ADMIN. Newest events first.
+    #[utoipa::path(
+        get,
+        path = "/admin/audit",
+        tag = "audit",
+        params(AuditQuery),
+        responses(
+            (status = 200, description = "Page of audit events", body = AuditEventPage),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing audit events", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_audit_events_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<AuditQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        let page = Page::new(query.limit, query.offset)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'ADMIN'
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::ADMIN).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                match AuditTable::new(connection).list(&query, page) {
+                    Ok(events) => Ok((StatusCode::OK, Json(Paginated::new(events, page)))),
+                    Err(err) => {
+                        error!("Error listing audit events: {:?}", err);
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list audit events"}))))
+                    }
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+}
@@ -0,0 +1,177 @@
+pub mod service {
+    use axum::http::{header, HeaderMap};
+    use diesel::{
+        prelude::*,
+        PgConnection,
+        r2d2::{ConnectionManager, PooledConnection},
+    };
+    use serde::Serialize;
+    use serde_json::Value;
+    use tracing::error;
+    use crate::{
+        audit::model::{AuditAction, AuditEvent, AuditQuery, NewAuditEvent},
+        common::{db::ConnectionPool, pagination::Page, security::decode_claims},
+        schema
+    };
+
+    type PooledPg = PooledConnection<ConnectionManager<PgConnection>>;
+
+    pub struct AuditTable {
+        connection: PooledPg,
+    }
+
+    impl AuditTable {
+        pub fn new(connection: PooledPg) -> AuditTable {
+            AuditTable { connection }
+        }
+
+        pub fn record(&mut self, new_event: NewAuditEvent) -> Result<AuditEvent, diesel::result::Error> {
+            use schema::audit_events;
+
+            diesel::insert_into(audit_events::table)
+                .values(&new_event)
+                .get_result(&mut self.connection)
+        }
+
+        // Newest events first
+        pub fn list(&mut self, filter: &AuditQuery, page: Page) -> Result<Vec<AuditEvent>, diesel::result::Error> {
+            use schema::audit_events;
+
+            let mut query = audit_events::table.into_boxed();
+
+            if let Some(actor) = &filter.actor {
+                query = query.filter(audit_events::actor.eq(actor));
+            }
+            if let Some(action) = &filter.action {
+                query = query.filter(audit_events::action.eq(action));
+            }
+            if let Some(resource_type) = &filter.resource_type {
+                query = query.filter(audit_events::resource_type.eq(resource_type));
+            }
+            if let Some(resource_id) = filter.resource_id {
+                query = query.filter(audit_events::resource_id.eq(resource_id));
+            }
+            if let Some(request_id) = &filter.request_id {
+                query = query.filter(audit_events::request_id.eq(request_id));
+            }
+            if let Some(since) = filter.since {
+                query = query.filter(audit_events::created_at.ge(since));
+            }
+            if let Some(until) = filter.until {
+                query = query.filter(audit_events::created_at.lt(until));
+            }
+
+            query
+                .order((audit_events::created_at.desc(), audit_events::id.desc()))
+                .limit(page.limit)
+                .offset(page.offset)
+                .load(&mut self.connection)
+        }
+    }
+
+    // Records a mutation performed by a handler. The actor and request id are taken from the request headers.
+    // The event is written after the mutation has been committed, so a failure is logged rather than failing the request
+    pub fn record_event<T: Serialize>(
+        shared_state: &ConnectionPool,
+        headers: &HeaderMap,
+        action: AuditAction,
+        resource_type: &str,
+        resource_id: i32,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) {
+        let new_event = NewAuditEvent {
+            actor: actor(headers),
+            action: action.to_string(),
+            resource_type: resource_type.to_string(),
+            resource_id,
+            before: before.map(snapshot),
+            after: after.map(snapshot),
+            request_id: headers.get("x-request-id").and_then(|request_id| request_id.to_str().ok()).map(str::to_string),
+        };
+
+        let result = shared_state.pool.get()
+            .map_err(|err| err.to_string())
+            .and_then(|connection| AuditTable::new(connection).record(new_event.clone()).map_err(|err| err.to_string()));
+
+        if let Err(err) = result {
+            error!("Failed to record audit event {:?}: {}", new_event, err);
+        }
+    }
+
+    // Email of the caller according to its bearer token. Requests without a token have no actor
+    fn actor(headers: &HeaderMap) -> Option<String> {
+        if !headers.contains_key(header::AUTHORIZATION) {
+            return None;
+        }
+
+        decode_claims(headers).ok().flatten().map(|token| token.claims.sub)
+    }
+
+    // Password hashes are never written to the audit log
+    fn snapshot<T: Serialize>(resource: &T) -> Value {
+        let mut snapshot = serde_json::to_value(resource).unwrap_or(Value::Null);
+
+        if let Value::Object(members) = &mut snapshot {
+            members.remove("password");
+        }
+
+        snapshot
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::{
+            audit::{
+                model::{AuditQuery, NewAuditEvent},
+                service::service::AuditTable
+            },
+            common::{
+                db::create_shared_connection_pool,
+                pagination::Page,
+                util::load_environment_variable
+            },
+            schema::audit_events
+        };
+        use diesel::prelude::*;
+
+        // No empire has a negative id, so the events of this test are the only ones on it
+        const RESOURCE_ID: i32 = -4242;
+
+        #[test]
+        fn list_filters_by_resource() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 1);
+            let mut connection = connection_pool.pool.get().expect("Failed to get connection");
+
+            // Events left behind by an earlier run would be listed next to the one recorded here
+            diesel::delete(audit_events::table.filter(audit_events::resource_type.eq("empire")).filter(audit_events::resource_id.eq(RESOURCE_ID)))
+                .execute(&mut connection)
+                .expect("Delete audit events failed");
+            let mut audit_db = AuditTable::new(connection);
+
+            let new_event = NewAuditEvent {
+                actor: Some("auditor@revisjon.no".to_string()),
+                action: "delete".to_string(),
+                resource_type: "empire".to_string(),
+                resource_id: RESOURCE_ID,
+                before: Some(serde_json::json!({"name": "Pirate Coalition"})),
+                after: None,
+                request_id: Some("test-request".to_string()),
+            };
+
+            let recorded_event = audit_db.record(new_event).expect("Record audit event failed");
+
+            let filter = AuditQuery {
+                resource_type: Some("empire".to_string()),
+                resource_id: Some(RESOURCE_ID),
+                ..AuditQuery::default()
+            };
+            let events = audit_db.list(&filter, Page::new(None, None).unwrap()).expect("List audit events failed");
+
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].id, recorded_event.id);
+            assert_eq!(events[0].before.as_ref().unwrap()["name"], "Pirate Coalition");
+        }
+    }
+}
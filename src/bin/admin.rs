@@ -0,0 +1,445 @@
+use std::{fs, path::PathBuf, process};
+use chrono::{Duration, Utc};
+use clap::{Parser, Subcommand};
+use diesel::{
+    Connection,
+    PgConnection,
+    RunQueryDsl,
+    r2d2::{ConnectionManager, PooledConnection},
+};
+use serde_derive::Deserialize;
+use axum_api_with_auth::{
+    common::{
+        db::{create_shared_connection_pool, ConnectionPool},
+        security::hash_password,
+        util::load_environment_variable,
+    },
+    empires::{model::UpsertEmpire, service::service::EmpiresTable},
+    locations::{model::UpsertLocation, service::service::LocationsTable},
+    users::{
+        model::{string_to_user_role, User, UpsertUser, UserRole},
+        service::service::UsersTable,
+    },
+};
+
+type PooledPg = PooledConnection<ConnectionManager<PgConnection>>;
+
+/// Maintenance tasks for the API database: bootstrapping users, seeding fixtures, purging deleted rows and test data
+#[derive(Parser)]
+#[command(name = "admin", version)]
+struct Cli {
+    /// Name of the environment variable holding the database URL, DEV_DB when omitted. Purge requires it to be given explicitly
+    #[arg(long, global = true)]
+    database_env: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a new user with the given role
+    CreateUser {
+        #[arg(long)]
+        email: String,
+        #[arg(long)]
+        fullname: String,
+        #[arg(long, default_value = "ADMIN")]
+        role: String,
+        #[arg(long, env = "ADMIN_PASSWORD", hide_env_values = true)]
+        password: String,
+    },
+    /// Change the role of an existing user
+    Promote {
+        #[arg(long)]
+        email: String,
+        #[arg(long)]
+        role: String,
+    },
+    /// Replace the password of an existing user
+    ResetPassword {
+        #[arg(long)]
+        email: String,
+        #[arg(long, env = "ADMIN_PASSWORD", hide_env_values = true)]
+        password: String,
+    },
+    /// Load locations, empires and users from JSON or YAML fixture files
+    Seed {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Permanently remove soft deleted empires, locations and users older than the retention period
+    PurgeDeleted {
+        /// Days a deleted row is kept so that it can still be restored
+        #[arg(long, env = "SOFT_DELETE_RETENTION_DAYS", default_value_t = 30)]
+        retention_days: i64,
+    },
+    /// Delete every row written through the API or by seeding, keeping the reference data of the migrations
+    Purge {
+        /// Confirm that all data in the target database should be deleted
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+// Fixture files are applied in the order locations, empires, users so that empires may reference seeded locations
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Fixtures {
+    locations: Vec<UpsertLocation>,
+    empires: Vec<FixtureEmpire>,
+    users: Vec<UpsertUser>,
+}
+
+// Ids differ from one database to the next, so a fixture names the location of an empire by its star system and area
+#[derive(Debug, Deserialize)]
+struct FixtureEmpire {
+    name: String,
+    slogan: String,
+    location: LocationKey,
+    description: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocationKey {
+    star_system: String,
+    area: String,
+}
+
+// Reason the seed transaction is rolled back
+#[derive(Debug)]
+struct SeedError(String);
+
+impl From<diesel::result::Error> for SeedError {
+    fn from(err: diesel::result::Error) -> SeedError {
+        SeedError(format!("Failed to seed: {}", err))
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let database_env = match database_env(&cli) {
+        Ok(database_env) => database_env,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            process::exit(1);
+        }
+    };
+    let database_url = load_environment_variable(&database_env);
+    let connection_pool = create_shared_connection_pool(database_url, 1);
+
+    let result = match cli.command {
+        Command::CreateUser { email, fullname, role, password } => create_user(&connection_pool, email, fullname, role, password),
+        Command::Promote { email, role } => promote_user(&connection_pool, email, role),
+        Command::ResetPassword { email, password } => reset_password(&connection_pool, email, password),
+        Command::Seed { files } => seed(&connection_pool, files),
+        Command::PurgeDeleted { retention_days } => purge_deleted(&connection_pool, retention_days),
+        Command::Purge { yes } => purge(&connection_pool, yes),
+    };
+
+    if let Err(message) = result {
+        eprintln!("error: {}", message);
+        process::exit(1);
+    }
+}
+
+// Emptying every table is never done against a database picked by default
+fn database_env(cli: &Cli) -> Result<String, String> {
+    match (&cli.command, &cli.database_env) {
+        (Command::Purge { .. }, None) => Err("Refusing to purge without --database-env naming the database to empty".to_string()),
+        (_, Some(database_env)) => Ok(database_env.clone()),
+        (_, None) => Ok("DEV_DB".to_string()),
+    }
+}
+
+fn create_user(connection_pool: &ConnectionPool, email: String, fullname: String, role: String, password: String) -> Result<(), String> {
+    let mut new_user = UpsertUser { email, password, fullname, role: parse_role(&role)?.to_string() };
+    let mut connection = acquire_connection(connection_pool)?;
+    insert_user(&mut connection, &mut new_user)?;
+    println!("Created user '{}' with role {}", new_user.email, new_user.role);
+    Ok(())
+}
+
+fn promote_user(connection_pool: &ConnectionPool, email: String, role: String) -> Result<(), String> {
+    let role = parse_role(&role)?;
+    let mut users = UsersTable::new(acquire_connection(connection_pool)?);
+    let user = find_user(&mut users, &email)?;
+
+    // The stored password is already hashed and is written back untouched
+    let update_user = UpsertUser { email: user.email, password: user.password, fullname: user.fullname, role: role.to_string() };
+
+    users.update(user.id, update_user, Some(user.version)).map_err(|err| format!("Failed to update user: {}", err))?;
+    println!("User '{}' now has role {}", email, role);
+    Ok(())
+}
+
+fn reset_password(connection_pool: &ConnectionPool, email: String, password: String) -> Result<(), String> {
+    let mut users = UsersTable::new(acquire_connection(connection_pool)?);
+    let user = find_user(&mut users, &email)?;
+
+    let mut update_user = UpsertUser { email: user.email, password, fullname: user.fullname, role: user.role };
+    hash_password(&mut update_user).map_err(|_| "Failed to hash password".to_string())?;
+
+    users.update(user.id, update_user, Some(user.version)).map_err(|err| format!("Failed to update user: {}", err))?;
+    println!("Password of user '{}' has been reset", email);
+    Ok(())
+}
+
+// Every file is read before anything is written, and all of them are applied in one transaction so that a failing
+// fixture leaves the database untouched
+fn seed(connection_pool: &ConnectionPool, files: Vec<PathBuf>) -> Result<(), String> {
+    let fixtures = files.iter()
+        .map(|file| read_fixtures(file).map(|fixtures| (file, fixtures)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    acquire_connection(connection_pool)?
+        .transaction(|connection| fixtures.iter().try_for_each(|(_, fixture)| seed_fixtures(connection, fixture)))
+        .map_err(|SeedError(message)| message)?;
+
+    for (file, fixture) in &fixtures {
+        println!("Seeded {} locations, {} empires and {} users from {}",
+                 fixture.locations.len(), fixture.empires.len(), fixture.users.len(), file.display());
+    }
+
+    Ok(())
+}
+
+fn seed_fixtures(connection: &mut PgConnection, fixtures: &Fixtures) -> Result<(), SeedError> {
+    for location in &fixtures.locations {
+        LocationsTable::insert(connection, location).map_err(|err| SeedError(format!("Failed to create location: {}", err)))?;
+    }
+
+    for empire in &fixtures.empires {
+        let LocationKey { star_system, area } = &empire.location;
+        let location_id = LocationsTable::find_id(connection, star_system, area)?
+            .ok_or_else(|| SeedError(format!("Location '{} - {}' of empire '{}' does not exist", star_system, area, empire.name)))?;
+
+        let upsert_empire = UpsertEmpire {
+            name: empire.name.clone(),
+            slogan: empire.slogan.clone(),
+            location_id,
+            description: empire.description.clone(),
+        };
+        EmpiresTable::insert(connection, &upsert_empire, None).map_err(|err| SeedError(format!("Failed to create empire: {}", err)))?;
+    }
+
+    for user in &fixtures.users {
+        let mut user = user.clone();
+        parse_role(&user.role).and_then(|_| insert_user(connection, &mut user)).map_err(SeedError)?;
+    }
+
+    Ok(())
+}
+
+fn purge_deleted(connection_pool: &ConnectionPool, retention_days: i64) -> Result<(), String> {
+    if retention_days < 0 {
+        return Err("Retention period must not be negative".to_string());
+    }
+
+    let deleted_before = Duration::try_days(retention_days)
+        .and_then(|retention| Utc::now().checked_sub_signed(retention))
+        .ok_or_else(|| format!("Retention period of {} days is too large", retention_days))?;
+
+    // Empires go first as they reference locations. Rows that are still referenced are kept and retried on the next run
+    let empires = EmpiresTable::new(acquire_connection(connection_pool)?).purge(deleted_before)
+        .map_err(|err| format!("Failed to purge empires: {}", err))?;
+    let locations = LocationsTable::new(acquire_connection(connection_pool)?).purge(deleted_before)
+        .map_err(|err| format!("Failed to purge locations: {}", err))?;
+    let users = UsersTable::new(acquire_connection(connection_pool)?).purge(deleted_before)
+        .map_err(|err| format!("Failed to purge users: {}", err))?;
+
+    println!("Purged {} empires, {} locations and {} users deleted before {}", empires, locations, users, deleted_before.to_rfc3339());
+    Ok(())
+}
+
+fn purge(connection_pool: &ConnectionPool, confirmed: bool) -> Result<(), String> {
+    if !confirmed {
+        return Err("Refusing to purge without --yes".to_string());
+    }
+
+    let mut connection = acquire_connection(connection_pool)?;
+
+    // Every table written through the API or by seeding is emptied, referencing tables before the tables they reference.
+    // This includes the audit log, whose events hold snapshots of users and the ids of the users acting. The reference
+    // data seeded by the migrations (regions, constellations, star systems and ship classes) is kept, as locations and
+    // ships cannot be written without it
+    connection.transaction::<_, diesel::result::Error, _>(|connection| {
+        for table in ["players", "ships", "empires", "territory_claims", "sovereignty_changes", "stargates", "locations", "audit_events", "users"] {
+            let deleted = diesel::sql_query(format!("DELETE FROM {}", table)).execute(connection)?;
+            println!("Deleted {} rows from {}", deleted, table);
+        }
+        Ok(())
+    }).map_err(|err| format!("Failed to purge data: {}", err))
+}
+
+fn read_fixtures(file: &PathBuf) -> Result<Fixtures, String> {
+    let contents = fs::read_to_string(file)
+        .map_err(|err| format!("Failed to read {}: {}", file.display(), err))?;
+
+    match file.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => serde_json::from_str(&contents).map_err(|err| format!("Invalid JSON in {}: {}", file.display(), err)),
+        Some("yaml") | Some("yml") => serde_yaml::from_str(&contents).map_err(|err| format!("Invalid YAML in {}: {}", file.display(), err)),
+        _ => Err(format!("Unsupported fixture format for {}, expected .json, .yaml or .yml", file.display())),
+    }
+}
+
+fn insert_user(connection: &mut PgConnection, user: &mut UpsertUser) -> Result<(), String> {
+    if !user.is_valid_email() {
+        return Err(format!("Invalid email '{}'", user.email));
+    }
+
+    hash_password(user).map_err(|_| "Failed to hash password".to_string())?;
+
+    UsersTable::insert(connection, user)
+        .map(|_| ())
+        .map_err(|err| format!("Failed to create user: {}", err))
+}
+
+fn find_user(users: &mut UsersTable, email: &str) -> Result<User, String> {
+    match users.get_by_email(email.to_string()) {
+        Ok(Some(user)) => Ok(user),
+        Ok(None) => Err(format!("User '{}' not found", email)),
+        Err(err) => Err(format!("Failed to read user: {}", err)),
+    }
+}
+
+fn parse_role(role: &str) -> Result<UserRole, String> {
+    match string_to_user_role(role.to_uppercase()) {
+        UserRole::INVALID => Err(format!("Invalid role '{}', expected one of READER, WRITER, EDITOR or ADMIN", role)),
+        user_role => Ok(user_role),
+    }
+}
+
+fn acquire_connection(connection_pool: &ConnectionPool) -> Result<PooledPg, String> {
+    connection_pool.pool.get().map_err(|err| format!("Failed to acquire connection from pool: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use clap::Parser;
+    use axum_api_with_auth::common::{db::{create_shared_connection_pool, ConnectionPool}, util::load_environment_variable};
+    use axum_api_with_auth::{locations::service::service::LocationsTable, users::service::service::UsersTable};
+    use super::{create_user, database_env, find_user, promote_user, purge, purge_deleted, reset_password, seed, Cli};
+
+    fn test_pool() -> ConnectionPool {
+        create_shared_connection_pool(load_environment_variable("TEST_DB"), 1)
+    }
+
+    // Soft deletes the user left behind by an earlier run, which frees the email again
+    fn forget_user(connection_pool: &ConnectionPool, email: &str) {
+        let mut users = UsersTable::new(connection_pool.pool.get().unwrap());
+        if let Ok(Some(user)) = users.get_by_email(email.to_string()) {
+            users.delete(user.id, None).unwrap();
+        }
+    }
+
+    #[test]
+    fn create_user_stores_the_user_with_the_given_role() {
+        let connection_pool = test_pool();
+        forget_user(&connection_pool, "havnefogd@admin.no");
+
+        create_user(&connection_pool, "havnefogd@admin.no".to_string(), "Havne Fogd".to_string(), "editor".to_string(), "Fortoy99".to_string()).unwrap();
+
+        let user = find_user(&mut UsersTable::new(connection_pool.pool.get().unwrap()), "havnefogd@admin.no").unwrap();
+        assert_eq!(user.role, "EDITOR");
+        assert!(bcrypt::verify("Fortoy99", &user.password).unwrap());
+        assert!(create_user(&connection_pool, "losjef@admin.no".to_string(), "Los Jef".to_string(), "CAPTAIN".to_string(), "Fortoy99".to_string()).is_err());
+    }
+
+    #[test]
+    fn promote_changes_the_role_of_the_user() {
+        let connection_pool = test_pool();
+        forget_user(&connection_pool, "matros@admin.no");
+        create_user(&connection_pool, "matros@admin.no".to_string(), "Ma Tros".to_string(), "READER".to_string(), "Dekk123".to_string()).unwrap();
+
+        promote_user(&connection_pool, "matros@admin.no".to_string(), "WRITER".to_string()).unwrap();
+
+        let user = find_user(&mut UsersTable::new(connection_pool.pool.get().unwrap()), "matros@admin.no").unwrap();
+        assert_eq!(user.role, "WRITER");
+        assert!(bcrypt::verify("Dekk123", &user.password).unwrap());
+        assert!(promote_user(&connection_pool, "ukjent@admin.no".to_string(), "WRITER".to_string()).is_err());
+    }
+
+    #[test]
+    fn reset_password_replaces_the_password_of_the_user() {
+        let connection_pool = test_pool();
+        forget_user(&connection_pool, "glemsk@admin.no");
+        create_user(&connection_pool, "glemsk@admin.no".to_string(), "Glem Sk".to_string(), "READER".to_string(), "Gammel1".to_string()).unwrap();
+
+        reset_password(&connection_pool, "glemsk@admin.no".to_string(), "Ny12345".to_string()).unwrap();
+
+        let user = find_user(&mut UsersTable::new(connection_pool.pool.get().unwrap()), "glemsk@admin.no").unwrap();
+        assert!(bcrypt::verify("Ny12345", &user.password).unwrap());
+        assert!(!bcrypt::verify("Gammel1", &user.password).unwrap());
+    }
+
+    #[test]
+    fn seed_writes_nothing_when_a_fixture_fails() {
+        let connection_pool = test_pool();
+        forget_user(&connection_pool, "nybygger@admin.no");
+
+        let file = std::env::temp_dir().join(format!("admin-seed-{}.json", std::process::id()));
+        fs::write(&file, r#"{"users": [
+            {"email": "nybygger@admin.no", "password": "Hytte42", "fullname": "Ny Bygger", "role": "READER"},
+            {"email": "ikke-en-epost", "password": "Hytte42", "fullname": "Feil Adresse", "role": "READER"}
+        ]}"#).unwrap();
+
+        let result = seed(&connection_pool, vec![file.clone()]);
+        fs::remove_file(&file).unwrap();
+
+        assert!(result.is_err());
+        assert!(find_user(&mut UsersTable::new(connection_pool.pool.get().unwrap()), "nybygger@admin.no").is_err());
+    }
+
+    #[test]
+    fn seed_resolves_the_location_of_an_empire_by_star_system_and_area() {
+        let connection_pool = test_pool();
+        let area = format!("Ammold {}", std::process::id());
+        let fixture = |empire_area: &str, email: &str| format!(r#"{{
+            "locations": [{{"star_system": "Heimatar", "area": "{}"}}],
+            "empires": [{{"name": "Thukker Tribe {}", "slogan": "The caravan goes on", "location": {{"star_system": "Heimatar", "area": "{}"}},
+                          "description": "Nomads of the Great Wildlands"}}],
+            "users": [{{"email": "{}", "password": "Karavane7", "fullname": "Kara Vane", "role": "READER"}}]
+        }}"#, area, std::process::id(), empire_area, email);
+
+        let file = std::env::temp_dir().join(format!("admin-seed-empire-{}.json", std::process::id()));
+        let seed_fixture = |empire_area: &str, email: &str| {
+            fs::write(&file, fixture(empire_area, email)).unwrap();
+            let result = seed(&connection_pool, vec![file.clone()]);
+            fs::remove_file(&file).unwrap();
+            result
+        };
+
+        // The empire finds the location seeded before it, and the invalid user after it rolls both back
+        assert_eq!(seed_fixture(&area, "ikke-en-epost"), Err("Invalid email 'ikke-en-epost'".to_string()));
+        assert_eq!(seed_fixture("Ammold", "karavane@admin.no"),
+                   Err(format!("Location 'Heimatar - Ammold' of empire 'Thukker Tribe {}' does not exist", std::process::id())));
+
+        let mut connection = connection_pool.pool.get().unwrap();
+        assert_eq!(LocationsTable::find_id(&mut connection, "Heimatar", &area).unwrap(), None);
+    }
+
+    #[test]
+    fn purge_deleted_rejects_retention_periods_out_of_range() {
+        let connection_pool = test_pool();
+
+        assert!(purge_deleted(&connection_pool, -1).is_err());
+        assert!(purge_deleted(&connection_pool, i64::MAX).is_err());
+        assert!(purge_deleted(&connection_pool, 36500).is_ok());
+    }
+
+    #[test]
+    fn purge_requires_the_database_to_be_named_and_confirmed() {
+        let cli = Cli::try_parse_from(["admin", "purge", "--yes"]).unwrap();
+        assert!(database_env(&cli).is_err());
+
+        let cli = Cli::try_parse_from(["admin", "--database-env", "TEST_DB", "purge", "--yes"]).unwrap();
+        assert_eq!(database_env(&cli).unwrap(), "TEST_DB");
+
+        let cli = Cli::try_parse_from(["admin", "promote", "--email", "matros@admin.no", "--role", "EDITOR"]).unwrap();
+        assert_eq!(database_env(&cli).unwrap(), "DEV_DB");
+
+        assert!(purge(&test_pool(), false).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use axum::{http::StatusCode, Json};
+use diesel::{
+    connection::{AnsiTransactionManager, TransactionManager},
+    PgConnection,
+    QueryResult,
+};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::error;
+use utoipa::{IntoParams, ToSchema};
+use crate::{empires::model::UpsertEmpire, locations::model::UpsertLocation, ships::model::UpsertShip};
+
+pub const MAX_BULK_ITEMS: usize = 1000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BulkMode {
+    // Every item is applied or none is
+    #[default]
+    Transactional,
+    // Every item is applied on its own, failures do not affect the other items
+    Partial,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BulkQuery {
+    /// 'transactional' (default) applies all items or none, 'partial' applies each item on its own and answers 207 Multi-Status
+    #[param(inline)]
+    pub mode: Option<BulkMode>,
+}
+
+impl BulkQuery {
+    pub fn mode(&self) -> BulkMode {
+        self.mode.unwrap_or_default()
+    }
+}
+
+// Item of a bulk update, the id of the row to replace next to its new representation
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[aliases(LocationUpdate = BulkUpdate<UpsertLocation>, EmpireUpdate = BulkUpdate<UpsertEmpire>, ShipUpdate = BulkUpdate<UpsertShip>)]
+pub struct BulkUpdate<T> {
+    pub id: i32,
+    #[serde(flatten)]
+    pub body: T,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BulkItemResult {
+    /// Position of the item in the request body
+    pub index: usize,
+    /// Status the item would have been answered with as a single request
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BulkResponse {
+    pub results: Vec<BulkItemResult>,
+}
+
+#[derive(Debug)]
+pub enum BulkError {
+    // An item failed in transactional mode and every item has been rolled back
+    Item { index: usize, error: diesel::result::Error },
+    // Beginning, committing or rolling back the transaction failed
+    Transaction(diesel::result::Error),
+}
+
+// Tables whose connection may be shared by the items of a bulk request
+pub trait Transactional {
+    fn connection(&mut self) -> &mut PgConnection;
+}
+
+// Applies 'apply' to every item in order. In transactional mode the items share one transaction that is rolled back
+// on the first failing item, in partial mode the outcome of each item is returned
+pub fn apply_all<S, T, R>(table: &mut S, items: Vec<T>, mode: BulkMode, mut apply: impl FnMut(&mut S, T) -> QueryResult<R>) -> Result<Vec<QueryResult<R>>, BulkError>
+where
+    S: Transactional,
+{
+    match mode {
+        BulkMode::Partial => Ok(items.into_iter().map(|item| apply(table, item)).collect()),
+        BulkMode::Transactional => {
+            AnsiTransactionManager::begin_transaction(table.connection()).map_err(BulkError::Transaction)?;
+
+            let mut outcomes = Vec::with_capacity(items.len());
+            for (index, item) in items.into_iter().enumerate() {
+                match apply(table, item) {
+                    Ok(outcome) => outcomes.push(Ok(outcome)),
+                    Err(error) => {
+                        AnsiTransactionManager::rollback_transaction(table.connection()).map_err(BulkError::Transaction)?;
+                        return Err(BulkError::Item { index, error });
+                    }
+                }
+            }
+
+            AnsiTransactionManager::commit_transaction(table.connection()).map_err(BulkError::Transaction)?;
+            Ok(outcomes)
+        }
+    }
+}
+
+pub fn check_bulk_size(items: usize) -> Result<(), (StatusCode, Json<Value>)> {
+    if items > MAX_BULK_ITEMS {
+        return Err((StatusCode::PAYLOAD_TOO_LARGE, Json(json!({"error": format!("A bulk request holds at most {} items", MAX_BULK_ITEMS)}))));
+    }
+    Ok(())
+}
+
+// Index and validation error of each item of a bulk request that is not applied
+pub type Rejected = Vec<(usize, String)>;
+
+// Checks every item before any is applied. In transactional mode the first invalid item rejects the whole request,
+// in partial mode the invalid items are set aside and the valid ones are returned to be applied
+pub fn validate_all<T>(items: Vec<T>, mode: BulkMode, validate: impl Fn(&T) -> Result<(), String>) -> Result<(Vec<T>, Rejected), (StatusCode, Json<Value>)> {
+    let mut valid = Vec::with_capacity(items.len());
+    let mut rejected = Vec::new();
+
+    for (index, item) in items.into_iter().enumerate() {
+        match validate(&item) {
+            Ok(()) => valid.push(item),
+            Err(message) if mode == BulkMode::Transactional => {
+                return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": message, "index": index}))));
+            }
+            Err(message) => rejected.push((index, message)),
+        }
+    }
+    Ok((valid, rejected))
+}
+
+// Puts a 422 for every rejected item back at its index among the outcomes of the applied items
+pub fn with_rejected(mut outcomes: Vec<Result<i32, (StatusCode, String)>>, rejected: Rejected) -> Vec<Result<i32, (StatusCode, String)>> {
+    for (index, message) in rejected {
+        outcomes.insert(index, Err((StatusCode::UNPROCESSABLE_ENTITY, message)));
+    }
+    outcomes
+}
+
+// Error response of a transactional bulk request, naming the item that caused the rollback
+pub fn bulk_failure(err: BulkError, describe: impl Fn(&diesel::result::Error) -> (StatusCode, String)) -> (StatusCode, Json<Value>) {
+    match err {
+        BulkError::Item { index, error } => {
+            let (status, message) = describe(&error);
+            (status, Json(json!({"error": message, "index": index})))
+        }
+        BulkError::Transaction(err) => {
+            error!("Error running bulk transaction: {:?}", err);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to apply bulk request"})))
+        }
+    }
+}
+
+// Per item results, answered with 200 when every item succeeded in one transaction and with 207 Multi-Status in partial mode
+pub fn bulk_response(mode: BulkMode, item_status: StatusCode, outcomes: Vec<Result<i32, (StatusCode, String)>>) -> (StatusCode, Json<BulkResponse>) {
+    let results = outcomes.into_iter().enumerate()
+        .map(|(index, outcome)| match outcome {
+            Ok(id) => BulkItemResult { index, status: item_status.as_u16(), id: Some(id), error: None },
+            Err((status, message)) => BulkItemResult { index, status: status.as_u16(), id: None, error: Some(message) },
+        })
+        .collect();
+
+    let status = match mode {
+        BulkMode::Transactional => StatusCode::OK,
+        BulkMode::Partial => StatusCode::MULTI_STATUS,
+    };
+
+    (status, Json(BulkResponse { results }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use crate::common::bulk::{bulk_response, check_bulk_size, validate_all, with_rejected, BulkMode, MAX_BULK_ITEMS};
+
+    #[test]
+    fn partial_response_reports_each_item() {
+        let outcomes = vec![Ok(7), Err((StatusCode::NOT_FOUND, "Ship not found".to_string()))];
+        let (status, response) = bulk_response(BulkMode::Partial, StatusCode::NO_CONTENT, outcomes);
+
+        assert_eq!(status, StatusCode::MULTI_STATUS);
+        assert_eq!(serde_json::to_value(&response.0).unwrap(), serde_json::json!({"results": [
+            {"index": 0, "status": 204, "id": 7},
+            {"index": 1, "status": 404, "error": "Ship not found"}
+        ]}));
+        assert_eq!(check_bulk_size(MAX_BULK_ITEMS + 1).unwrap_err().0, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn invalid_items_are_rejected_before_any_item_is_applied() {
+        let non_empty = |name: &&str| if name.is_empty() { Err("'name' must not be empty".to_string()) } else { Ok(()) };
+
+        let (status, body) = validate_all(vec!["Rifter", "", ""], BulkMode::Transactional, non_empty).unwrap_err();
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body.0, serde_json::json!({"error": "'name' must not be empty", "index": 1}));
+
+        let (valid, rejected) = validate_all(vec!["", "Rifter", "", "Slasher"], BulkMode::Partial, non_empty).unwrap();
+        assert_eq!(valid, vec!["Rifter", "Slasher"]);
+
+        let outcomes = with_rejected(vec![Ok(17), Ok(18)], rejected);
+        let statuses: Vec<StatusCode> = outcomes.iter().map(|outcome| outcome.as_ref().map_or_else(|(status, _)| *status, |_| StatusCode::CREATED)).collect();
+        assert_eq!(statuses, vec![StatusCode::UNPROCESSABLE_ENTITY, StatusCode::CREATED, StatusCode::UNPROCESSABLE_ENTITY, StatusCode::CREATED]);
+    }
+}
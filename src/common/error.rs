@@ -1,4 +1,6 @@
 use std::fmt;
+use serde_derive::Serialize;
+use utoipa::ToSchema;
 
 #[derive(Debug, PartialEq)]
 pub enum ErrorType {
@@ -42,4 +44,11 @@ impl fmt::Display for CustomError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.message)
     }
-}
\ No newline at end of file
+}
+
+// Shape of the JSON error bodies produced by the routers. 'instance' holds the request id
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    pub error: String,
+    pub instance: Option<String>,
+}
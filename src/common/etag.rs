@@ -0,0 +1,103 @@
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Value};
+
+// Entity tag of a row version, quoted as the ETag header requires
+pub fn etag(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+// Whether a comma separated list of entity tags names the version. '*' matches any current representation.
+// Weak comparison ignores the 'W/' prefix and is what If-None-Match uses, If-Match requires strong comparison
+fn list_matches(value: &HeaderValue, version: i32, weak: bool) -> bool {
+    let current = etag(version);
+
+    value.to_str()
+        .map(|list| list.split(',')
+            .map(str::trim)
+            .map(|tag| if weak { tag.trim_start_matches("W/") } else { tag })
+            .any(|tag| tag == "*" || tag == current))
+        .unwrap_or(false)
+}
+
+// Evaluates If-Match against the current version of the resource, reading it only when the header is present.
+// Returns the version the write has to be conditioned on, or None for unconditional requests
+pub fn check_if_match<F>(headers: &HeaderMap, current_version: F) -> Result<Option<i32>, (StatusCode, Json<Value>)>
+where
+    F: FnOnce() -> Result<Option<i32>, diesel::result::Error>,
+{
+    let if_match = match headers.get(header::IF_MATCH) {
+        Some(if_match) => if_match,
+        None => return Ok(None),
+    };
+
+    let current_version = current_version().map_err(|err| {
+        tracing::error!("Error reading current version: {:?}", err);
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to read current version"})))
+    })?;
+
+    // A missing resource has no representation for If-Match to match, not even '*'
+    match current_version {
+        Some(version) if list_matches(if_match, version, false) => Ok(Some(version)),
+        _ => Err(precondition_failed()),
+    }
+}
+
+// Whether If-None-Match names the version, in which case a read is answered with 304
+pub fn is_not_modified(headers: &HeaderMap, version: i32) -> bool {
+    headers.get(header::IF_NONE_MATCH)
+        .map(|if_none_match| list_matches(if_none_match, version, true))
+        .unwrap_or(false)
+}
+
+pub fn not_modified(version: i32) -> Response {
+    (StatusCode::NOT_MODIFIED, [(header::ETAG, etag(version))]).into_response()
+}
+
+pub fn precondition_failed() -> (StatusCode, Json<Value>) {
+    (StatusCode::PRECONDITION_FAILED, Json(json!({"error": "Resource has been modified since it was read"})))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+    use crate::common::etag::{check_if_match, is_not_modified};
+
+    fn headers_with(name: header::HeaderName, value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn if_match_is_unconditional_without_header() {
+        let expected_version = check_if_match(&HeaderMap::new(), || panic!("Version read without If-Match"));
+
+        assert_eq!(expected_version.unwrap(), None);
+    }
+
+    #[test]
+    fn if_match_accepts_current_version_and_wildcard() {
+        assert_eq!(check_if_match(&headers_with(header::IF_MATCH, "\"1\", \"3\""), || Ok(Some(3))).unwrap(), Some(3));
+        assert_eq!(check_if_match(&headers_with(header::IF_MATCH, "*"), || Ok(Some(7))).unwrap(), Some(7));
+    }
+
+    #[test]
+    fn if_match_fails_on_stale_weak_or_missing_resource() {
+        let status = |value, current| check_if_match(&headers_with(header::IF_MATCH, value), || Ok(current)).unwrap_err().0;
+
+        assert_eq!(status("\"2\"", Some(3)), StatusCode::PRECONDITION_FAILED);
+        assert_eq!(status("W/\"3\"", Some(3)), StatusCode::PRECONDITION_FAILED);
+        assert_eq!(status("*", None), StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[test]
+    fn if_none_match_uses_weak_comparison() {
+        assert!(is_not_modified(&headers_with(header::IF_NONE_MATCH, "W/\"4\""), 4));
+        assert!(!is_not_modified(&headers_with(header::IF_NONE_MATCH, "\"3\""), 4));
+        assert!(!is_not_modified(&HeaderMap::new(), 4));
+    }
+}
@@ -0,0 +1,93 @@
+use axum::{http::StatusCode, Json};
+use diesel::{dsl::sql, expression::{SqlLiteral, TypedExpressionType}};
+use serde::Serialize;
+use serde_derive::Deserialize;
+use serde_json::{json, Value};
+use utoipa::IntoParams;
+
+// Query parameter of reads returning a sparse fieldset
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FieldsQuery {
+    /// Comma separated names of the fields to return, every field when left out
+    pub fields: Option<String>,
+}
+
+impl FieldsQuery {
+    // Unknown names are rejected rather than silently returning less than asked for
+    pub fn parse(&self, known: &[&'static str]) -> Result<FieldSet, (StatusCode, Json<Value>)> {
+        let requested: Vec<&str> = self.fields.as_deref().unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect();
+
+        if requested.is_empty() {
+            return Ok(FieldSet::all());
+        }
+        if let Some(unknown) = requested.iter().find(|name| !known.contains(name)) {
+            return Err((StatusCode::BAD_REQUEST, Json(json!({"error": format!("Unknown field '{}', expected any of: {}", unknown, known.join(", "))}))));
+        }
+
+        Ok(FieldSet { selected: Some(known.iter().copied().filter(|name| requested.contains(name)).collect()) })
+    }
+}
+
+// Fields of a resource to return, every field unless a sparse fieldset was asked for
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FieldSet {
+    selected: Option<Vec<&'static str>>,
+}
+
+impl FieldSet {
+    pub fn all() -> FieldSet {
+        FieldSet { selected: None }
+    }
+
+    pub fn contains(&self, field: &str) -> bool {
+        self.selected.as_ref().is_none_or(|selected| selected.contains(&field))
+    }
+
+    // The fieldset extended by fields that must be read although they are not returned, such as the keys of included relations
+    pub fn with(&self, required: &[&'static str]) -> FieldSet {
+        match &self.selected {
+            Some(selected) => FieldSet { selected: Some(selected.iter().chain(required).copied().collect()) },
+            None => FieldSet::all(),
+        }
+    }
+
+    // The column when its field is returned, and otherwise a constant of the same type, so that rows still load
+    // into the model while the columns that were not asked for are never read
+    pub fn column<T: TypedExpressionType>(&self, field: &str, column: &str, placeholder: &str) -> SqlLiteral<T> {
+        sql::<T>(if self.contains(field) { column } else { placeholder })
+    }
+
+    // The serialized item without the fields that were not asked for. Members named in 'keep', such as included relations, stay
+    pub fn project<T: Serialize>(&self, item: &T, keep: &[&str]) -> Value {
+        let mut value = serde_json::to_value(item).unwrap_or(Value::Null);
+
+        if let (Some(selected), Value::Object(members)) = (&self.selected, &mut value) {
+            members.retain(|name, _| selected.contains(&name.as_str()) || keep.contains(&name.as_str()));
+        }
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use serde_json::json;
+    use crate::common::fields::{FieldSet, FieldsQuery};
+
+    #[test]
+    fn fields_project_known_names_and_reject_unknown() {
+        let fields = |value: &str| FieldsQuery { fields: Some(value.to_string()) };
+        let known = ["id", "name", "description"];
+        let empire = json!({"id": 1, "name": "Caldari State", "description": "Corporate", "ships": []});
+
+        assert_eq!(FieldsQuery::default().parse(&known).unwrap(), FieldSet::all());
+        assert_eq!(fields("name, id").parse(&known).unwrap().project(&empire, &["ships"]), json!({"id": 1, "name": "Caldari State", "ships": []}));
+        assert_eq!(FieldSet::all().project(&empire, &[]), empire);
+        assert_eq!(fields("id,slogan").parse(&known).unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,208 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json, Router};
+use diesel::RunQueryDsl;
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use serde_json::{json, Value};
+use crate::common::{db::ConnectionPool, shutdown::Shutdown, util::load_optional_environment_variable};
+
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+#[derive(Clone)]
+pub struct HealthState {
+    pub connection_pool: ConnectionPool,
+    pub shutdown: Shutdown,
+}
+
+// - - - - - - - - - - - [ROUTES] - - - - - - - - - - -
+
+// Probes are served without authentication so that orchestrators can reach them
+pub fn health_route(shared_connection_pool: ConnectionPool, shutdown: Shutdown) -> Router {
+    Router::new()
+        .route("/health/live", axum::routing::get(liveness_handler))
+        .route("/health/ready", axum::routing::get(readiness_handler))
+        .with_state(HealthState { connection_pool: shared_connection_pool, shutdown })
+}
+
+// - - - - - - - - - - - [HANDLERS] - - - - - - - - - - -
+
+pub async fn liveness_handler() -> impl IntoResponse {
+    (StatusCode::OK, Json(json!({"status": "ok"})))
+}
+
+pub async fn readiness_handler(
+    State(shared_state): State<HealthState>,
+) -> impl IntoResponse {
+    let checks = [
+        ("shutdown", check_shutdown(&shared_state.shutdown)),
+        ("database", check_database(&shared_state.connection_pool)),
+        ("migrations", check_migrations(&shared_state.connection_pool)),
+        ("jwt_key", check_jwt_key()),
+    ];
+
+    let ready = checks.iter().all(|(_, result)| result.is_ok());
+
+    let details: serde_json::Map<String, Value> = checks.into_iter()
+        .map(|(name, result)| {
+            let detail = match result {
+                Ok(_) => json!({"status": "up"}),
+                Err(error) => {
+                    tracing::warn!("Readiness check '{}' failed: {}", name, error);
+                    json!({"status": "down", "error": error})
+                }
+            };
+            (name.to_string(), detail)
+        })
+        .collect();
+
+    if ready {
+        (StatusCode::OK, Json(json!({"status": "ready", "checks": details})))
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, Json(json!({"status": "unavailable", "checks": details})))
+    }
+}
+
+// - - - - - - - - - - - [CHECKS] - - - - - - - - - - -
+
+fn check_shutdown(shutdown: &Shutdown) -> Result<(), String> {
+    if shutdown.is_draining() {
+        Err("Server is draining connections".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn check_database(shared_state: &ConnectionPool) -> Result<(), String> {
+    let mut connection = shared_state.pool.get()
+        .map_err(|err| format!("Failed to acquire connection from pool: {}", err))?;
+
+    diesel::sql_query("SELECT 1")
+        .execute(&mut connection)
+        .map(|_| ())
+        .map_err(|err| format!("SELECT 1 failed: {}", err))
+}
+
+fn check_migrations(shared_state: &ConnectionPool) -> Result<(), String> {
+    let mut connection = shared_state.pool.get()
+        .map_err(|err| format!("Failed to acquire connection from pool: {}", err))?;
+
+    match connection.has_pending_migration(MIGRATIONS) {
+        Ok(false) => Ok(()),
+        Ok(true) => Err("Database has pending migrations".to_string()),
+        Err(err) => Err(format!("Failed to read applied migrations: {}", err)),
+    }
+}
+
+fn check_jwt_key() -> Result<(), String> {
+    match load_optional_environment_variable("ENCRYPTION_KEY") {
+        Some(key) if !key.trim().is_empty() => Ok(()),
+        Some(_) => Err("ENCRYPTION_KEY is empty".to_string()),
+        None => Err("ENCRYPTION_KEY is not set".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use serde_json::json;
+    use tower::ServiceExt;
+    use crate::common::{
+        db::create_shared_connection_pool,
+        health::health_route,
+        shutdown::Shutdown,
+        util::load_environment_variable,
+    };
+
+    #[tokio::test]
+    async fn get_live_returns_200() {
+        let database_url = load_environment_variable("TEST_DB");
+        let connection_pool = create_shared_connection_pool(database_url, 1);
+        let service = health_route(connection_pool, Shutdown::new());
+
+        let request = Request::builder()
+            .uri("/health/live")
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = service
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        // Assert that the response status is 200
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn get_ready_returns_200_with_check_details_on_migrated_database() {
+        let database_url = load_environment_variable("TEST_DB");
+        let connection_pool = create_shared_connection_pool(database_url, 1);
+        let service = health_route(connection_pool, Shutdown::new());
+
+        let request = Request::builder()
+            .uri("/health/ready")
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = service
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        // Assert that the response status is 200
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Extract body from response
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        // Construct JSON consisting of expected payload
+        let expected_response = json!({
+            "status": "ready",
+            "checks": {
+                "shutdown": {"status": "up"},
+                "database": {"status": "up"},
+                "migrations": {"status": "up"},
+                "jwt_key": {"status": "up"}
+            }
+        });
+
+        // Assert equality
+        assert_eq!(response_json, expected_response);
+    }
+
+    #[tokio::test]
+    async fn get_ready_returns_503_while_draining() {
+        let database_url = load_environment_variable("TEST_DB");
+        let connection_pool = create_shared_connection_pool(database_url, 1);
+        let shutdown = Shutdown::new();
+        let service = health_route(connection_pool, shutdown.clone());
+
+        // Flip readiness as the signal handler would
+        shutdown.start_draining();
+
+        let request = Request::builder()
+            .uri("/health/ready")
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = service
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        // Assert that the response status is 503
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // Extract body from response
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(response_json["status"], "unavailable");
+        assert_eq!(response_json["checks"]["shutdown"]["status"], "down");
+    }
+}
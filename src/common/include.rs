@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+use axum::{http::StatusCode, Json};
+use serde_derive::Deserialize;
+use serde_json::{json, Value};
+use utoipa::IntoParams;
+
+// Query parameter of reads that embed related resources
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct IncludeQuery {
+    /// Comma separated names of related resources to embed, as listed in the description of the operation
+    pub include: Option<String>,
+}
+
+impl IncludeQuery {
+    // The requested names out of those the resource knows, in the order they are known. Unknown names are rejected
+    pub fn parse(&self, known: &[&'static str]) -> Result<Vec<&'static str>, (StatusCode, Json<Value>)> {
+        let requested: Vec<&str> = self.include.as_deref().unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect();
+
+        if let Some(unknown) = requested.iter().find(|name| !known.contains(name)) {
+            return Err((StatusCode::BAD_REQUEST, Json(json!({"error": format!("Unknown include '{}', expected any of: {}", unknown, known.join(", "))}))));
+        }
+
+        Ok(known.iter().copied().filter(|name| requested.contains(name)).collect())
+    }
+}
+
+// Groups rows loaded for many parents at once by the id of their parent
+pub fn group_by<T>(rows: Vec<T>, parent_id: impl Fn(&T) -> i32) -> HashMap<i32, Vec<T>> {
+    let mut groups: HashMap<i32, Vec<T>> = HashMap::new();
+    for row in rows {
+        groups.entry(parent_id(&row)).or_default().push(row);
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use crate::common::include::IncludeQuery;
+
+    #[test]
+    fn include_keeps_known_names_and_rejects_unknown() {
+        let include = |value: &str| IncludeQuery { include: Some(value.to_string()) };
+        let known = ["location", "ships"];
+
+        assert_eq!(IncludeQuery::default().parse(&known).unwrap(), Vec::<&str>::new());
+        assert_eq!(include("ships, location,ships").parse(&known).unwrap(), vec!["location", "ships"]);
+        assert_eq!(include("ships,crew").parse(&known).unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+}
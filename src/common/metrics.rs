@@ -0,0 +1,210 @@
+use std::{sync::OnceLock, time::Instant};
+use axum::{
+    extract::{MatchedPath, State},
+    http::{header, HeaderMap, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Router,
+};
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder};
+use crate::common::db::ConnectionPool;
+
+pub struct Metrics {
+    registry: Registry,
+    http_requests_total: IntCounterVec,
+    http_request_duration_seconds: HistogramVec,
+    db_pool_connections: IntGauge,
+    db_pool_idle_connections: IntGauge,
+    auth_outcomes_total: IntCounterVec,
+    login_attempts_total: IntCounterVec,
+}
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+// Process wide collectors, registered on first use
+pub fn metrics() -> &'static Metrics {
+    METRICS.get_or_init(|| {
+        let registry = Registry::new();
+
+        let http_requests_total = IntCounterVec::new(
+            Opts::new("http_requests_total", "Number of HTTP requests by method, route and status"),
+            &["method", "route", "status"],
+        ).unwrap();
+
+        let http_request_duration_seconds = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "HTTP request latencies in seconds by method and route"),
+            &["method", "route"],
+        ).unwrap();
+
+        let db_pool_connections = IntGauge::new("db_pool_connections", "Number of connections currently held by the r2d2 pool").unwrap();
+        let db_pool_idle_connections = IntGauge::new("db_pool_idle_connections", "Number of idle connections in the r2d2 pool").unwrap();
+
+        let auth_outcomes_total = IntCounterVec::new(
+            Opts::new("auth_outcomes_total", "Outcomes of token decoding and role policy enforcement"),
+            &["outcome", "reason"],
+        ).unwrap();
+
+        let login_attempts_total = IntCounterVec::new(
+            Opts::new("login_attempts_total", "Number of login attempts by outcome"),
+            &["outcome"],
+        ).unwrap();
+
+        registry.register(Box::new(http_requests_total.clone())).unwrap();
+        registry.register(Box::new(http_request_duration_seconds.clone())).unwrap();
+        registry.register(Box::new(db_pool_connections.clone())).unwrap();
+        registry.register(Box::new(db_pool_idle_connections.clone())).unwrap();
+        registry.register(Box::new(auth_outcomes_total.clone())).unwrap();
+        registry.register(Box::new(login_attempts_total.clone())).unwrap();
+
+        Metrics {
+            registry,
+            http_requests_total,
+            http_request_duration_seconds,
+            db_pool_connections,
+            db_pool_idle_connections,
+            auth_outcomes_total,
+            login_attempts_total,
+        }
+    })
+}
+
+pub fn record_auth_success() {
+    metrics().auth_outcomes_total.with_label_values(&["success", "authorized"]).inc();
+}
+
+pub fn record_auth_failure(reason: &str) {
+    metrics().auth_outcomes_total.with_label_values(&["failure", reason]).inc();
+}
+
+pub fn record_login_attempt(outcome: &str) {
+    metrics().login_attempts_total.with_label_values(&[outcome]).inc();
+}
+
+// Counts and times every routed request. Requests without a matched route share one label to bound cardinality
+pub async fn track_metrics<B>(request: Request<B>, next: Next<B>) -> Response {
+    let start = Instant::now();
+    let method = request.method().to_string();
+    let route = request.extensions().get::<MatchedPath>()
+        .map(|matched_path| matched_path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16().to_string();
+    metrics().http_requests_total.with_label_values(&[&method, &route, &status]).inc();
+    metrics().http_request_duration_seconds.with_label_values(&[&method, &route]).observe(start.elapsed().as_secs_f64());
+
+    response
+}
+
+#[derive(Clone)]
+pub struct MetricsState {
+    pub connection_pool: ConnectionPool,
+    pub token: Option<String>,
+}
+
+// - - - - - - - - - - - [ROUTES] - - - - - - - - - - -
+
+// When a token is given the endpoint requires 'Authorization: Bearer <token>'
+pub fn metrics_route(shared_connection_pool: ConnectionPool, token: Option<String>) -> Router {
+    Router::new()
+        .route("/metrics", axum::routing::get(metrics_handler))
+        .with_state(MetricsState { connection_pool: shared_connection_pool, token })
+}
+
+// - - - - - - - - - - - [HANDLERS] - - - - - - - - - - -
+
+pub async fn metrics_handler(
+    headers: HeaderMap,
+    State(shared_state): State<MetricsState>,
+) -> Response {
+    if let Some(token) = &shared_state.token {
+        let provided = headers.get(header::AUTHORIZATION).and_then(|value| value.to_str().ok());
+
+        if provided != Some(format!("Bearer {}", token).as_str()) {
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+    }
+
+    // Pool state is sampled on scrape rather than tracked continuously
+    let pool_state = shared_state.connection_pool.pool.state();
+    metrics().db_pool_connections.set(pool_state.connections as i64);
+    metrics().db_pool_idle_connections.set(pool_state.idle_connections as i64);
+
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+
+    match encoder.encode(&metrics().registry.gather(), &mut buffer) {
+        Ok(_) => ([(header::CONTENT_TYPE, encoder.format_type().to_string())], buffer).into_response(),
+        Err(err) => {
+            tracing::error!("Error encoding metrics: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+    use crate::common::{
+        db::create_shared_connection_pool,
+        metrics::{metrics_route, record_login_attempt},
+        util::load_environment_variable,
+    };
+
+    #[tokio::test]
+    async fn get_metrics_returns_200_with_prometheus_text() {
+        let database_url = load_environment_variable("TEST_DB");
+        let connection_pool = create_shared_connection_pool(database_url, 1);
+        let service = metrics_route(connection_pool, None);
+
+        record_login_attempt("success");
+
+        let request = Request::builder()
+            .uri("/metrics")
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = service
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        // Assert that the response status is 200
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        // Assert that pool gauges and the recorded counter are exposed
+        assert!(body.contains("db_pool_connections"));
+        assert!(body.contains("db_pool_idle_connections"));
+        assert!(body.contains("login_attempts_total{outcome=\"success\"}"));
+    }
+
+    #[tokio::test]
+    async fn get_metrics_returns_401_without_token_when_protected() {
+        let database_url = load_environment_variable("TEST_DB");
+        let connection_pool = create_shared_connection_pool(database_url, 1);
+        let service = metrics_route(connection_pool, Some("scrape-secret".to_string()));
+
+        let request = Request::builder()
+            .uri("/metrics")
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = service
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        // Assert that the response status is 401
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -2,3 +2,17 @@ pub mod db;
 pub mod security;
 pub mod util;
 pub mod error;
+pub mod telemetry;
+pub mod request_id;
+pub mod metrics;
+pub mod health;
+pub mod shutdown;
+pub mod openapi;
+pub mod versioning;
+pub mod patch;
+pub mod etag;
+pub mod pagination;
+pub mod bulk;
+pub mod transfer;
+pub mod include;
+pub mod fields;
@@ -0,0 +1,269 @@
+use axum::{http::header, response::{Html, IntoResponse}, Json, Router};
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify,
+    OpenApi,
+};
+use crate::{
+    audit::{model::AuditEvent, router::router as audit},
+    common::{
+        bulk::{BulkItemResult, BulkResponse, EmpireUpdate, LocationUpdate, ShipUpdate},
+        error::ErrorBody,
+        pagination::{AuditEventPage, EmpireDocumentPage, EmpirePage, LocationDocumentPage, LocationPage, NearbyLocationPage, PlayerDocumentPage, SearchHitPage, ShipPage, SovereigntyChangePage, StargatePage, TerritoryClaimPage, UserPage},
+        transfer::{ImportReport, RowError},
+    },
+    empires::{model::{Empire, EmpireDocument, TerritoryClaim, UpsertEmpire}, router::router as empires},
+    locations::{model::{Distance, Location, LocationDocument, NearbyLocation, SecurityClass, SovereigntyChange, UpsertLocation}, router::router as locations},
+    players::model::{Player, PlayerDocument},
+    regions::{
+        model::{Constellation, ConstellationNode, NewConstellation, NewRegion, Region, RegionNode, StarSystem, StarSystemNode, UpsertStarSystem},
+        router::router as regions,
+    },
+    search::{model::{SearchHit, SearchKind}, router::router as search},
+    ships::{model::{Ship, ShipClass, UpsertShip, UpsertShipClass}, router::router as ships},
+    stargates::{model::{Route, Stargate, UpsertStargate}, router::router as stargates},
+    users::{model::{LoginUser, UpsertUser, User}, router::router as users},
+};
+
+// Path of the committed specification compared against the generated one by the drift test
+pub const SPECIFICATION_PATH: &str = "openapi.json";
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Axum API", description = "REST API for users, locations, empires and ships. Operations requiring a bearer token state the minimum role in their description."),
+    servers((url = "/v1", description = "Current version")),
+    paths(
+        users::create_user_handler,
+        users::list_users_handler,
+        users::get_user_handler,
+        users::update_user_handler,
+        users::patch_user_handler,
+        users::delete_user_handler,
+        users::restore_user_handler,
+        users::login_user_handler,
+        locations::create_location_handler,
+        locations::list_locations_handler,
+        locations::read_location_handler,
+        locations::update_location_handler,
+        locations::patch_location_handler,
+        locations::delete_location_handler,
+        locations::restore_location_handler,
+        locations::bulk_create_locations_handler,
+        locations::bulk_update_locations_handler,
+        locations::bulk_delete_locations_handler,
+        locations::export_locations_handler,
+        locations::import_locations_handler,
+        locations::nearby_locations_handler,
+        locations::location_distance_handler,
+        locations::sovereignty_history_handler,
+        locations::list_claims_handler,
+        locations::list_location_empires_handler,
+        locations::list_location_players_handler,
+        empires::create_empire_handler,
+        empires::list_empires_handler,
+        empires::read_empire_handler,
+        empires::update_empire_handler,
+        empires::patch_empire_handler,
+        empires::delete_empire_handler,
+        empires::restore_empire_handler,
+        empires::list_empire_ships_handler,
+        empires::list_empire_players_handler,
+        empires::list_territory_handler,
+        empires::claim_location_handler,
+        empires::release_location_handler,
+        empires::bulk_create_empires_handler,
+        empires::bulk_update_empires_handler,
+        empires::bulk_delete_empires_handler,
+        empires::export_empires_handler,
+        empires::import_empires_handler,
+        ships::list_ships_handler,
+        ships::read_ship_handler,
+        ships::bulk_create_ships_handler,
+        ships::bulk_update_ships_handler,
+        ships::bulk_delete_ships_handler,
+        ships::export_ships_handler,
+        ships::import_ships_handler,
+        ships::create_ship_class_handler,
+        ships::list_ship_classes_handler,
+        ships::read_ship_class_handler,
+        ships::update_ship_class_handler,
+        ships::delete_ship_class_handler,
+        stargates::create_stargate_handler,
+        stargates::list_stargates_handler,
+        stargates::read_stargate_handler,
+        stargates::update_stargate_handler,
+        stargates::delete_stargate_handler,
+        stargates::plan_route_handler,
+        regions::create_region_handler,
+        regions::list_regions_handler,
+        regions::read_region_handler,
+        regions::list_region_constellations_handler,
+        regions::list_region_star_systems_handler,
+        regions::create_constellation_handler,
+        regions::list_constellation_star_systems_handler,
+        regions::create_star_system_handler,
+        regions::read_star_system_handler,
+        regions::update_star_system_handler,
+        search::search_handler,
+        audit::list_audit_events_handler,
+    ),
+    components(schemas(User, UpsertUser, LoginUser, Location, UpsertLocation, Empire, UpsertEmpire, ErrorBody, UserPage, LocationPage, EmpirePage, AuditEvent, AuditEventPage,
+        Ship, UpsertShip, LocationUpdate, EmpireUpdate, ShipUpdate, BulkItemResult, BulkResponse, ImportReport, RowError,
+        Stargate, UpsertStargate, StargatePage, Route,
+        NearbyLocation, NearbyLocationPage, Distance, SecurityClass, SovereigntyChange, SovereigntyChangePage, TerritoryClaim, TerritoryClaimPage,
+        ShipPage, Player, PlayerDocument, PlayerDocumentPage, EmpireDocument, EmpireDocumentPage, LocationDocument, LocationDocumentPage,
+        Region, NewRegion, RegionNode, Constellation, NewConstellation, ConstellationNode, StarSystem, UpsertStarSystem, StarSystemNode,
+        SearchKind, SearchHit, SearchHitPage, ShipClass, UpsertShipClass)),
+    modifiers(&BearerSecurity),
+    tags(
+        (name = "users", description = "Registration and login"),
+        (name = "locations", description = "Star systems and areas"),
+        (name = "empires", description = "Empires, their home locations and territory"),
+        (name = "ships", description = "Ships of the empires and the classes they are built to"),
+        (name = "stargates", description = "Connections between locations and route planning"),
+        (name = "regions", description = "Regions, constellations and the star systems of locations"),
+        (name = "search", description = "Full-text search across empires, ships and locations"),
+        (name = "audit", description = "Record of mutating operations"),
+    )
+)]
+pub struct ApiDoc;
+
+// Registers the JWT issued by '/users/login' as the 'bearer_auth' security scheme
+struct BearerSecurity;
+
+impl Modify for BearerSecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build()),
+        );
+    }
+}
+
+// - - - - - - - - - - - [ROUTES] - - - - - - - - - - -
+
+pub fn openapi_route() -> Router {
+    Router::new()
+        .route("/openapi.json", axum::routing::get(openapi_handler))
+        .route("/docs", axum::routing::get(docs_handler))
+        .route("/docs/swagger-ui.css", axum::routing::get(swagger_ui_css_handler))
+        .route("/docs/swagger-ui-bundle.js", axum::routing::get(swagger_ui_bundle_handler))
+}
+
+// - - - - - - - - - - - [HANDLERS] - - - - - - - - - - -
+
+pub async fn openapi_handler() -> impl IntoResponse {
+    Json(ApiDoc::openapi())
+}
+
+pub async fn docs_handler() -> impl IntoResponse {
+    Html(include_str!("docs.html"))
+}
+
+// Swagger UI 5.17.14 is vendored in 'swagger-ui' and built into the binary, so the documentation loads nothing from outside the API
+pub async fn swagger_ui_css_handler() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "text/css")], include_str!("swagger-ui/swagger-ui.css"))
+}
+
+pub async fn swagger_ui_bundle_handler() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "text/javascript")], include_str!("swagger-ui/swagger-ui-bundle.js"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+    use utoipa::OpenApi;
+    use crate::common::openapi::{openapi_route, ApiDoc, SPECIFICATION_PATH};
+
+    // Fails when handlers or models change without the committed specification being regenerated.
+    // Run with UPDATE_OPENAPI=1 to rewrite openapi.json
+    #[test]
+    fn committed_specification_matches_generated() {
+        let generated = ApiDoc::openapi().to_pretty_json().expect("Failed to serialize specification") + "\n";
+
+        if env::var("UPDATE_OPENAPI").is_ok() {
+            fs::write(SPECIFICATION_PATH, &generated).expect("Failed to write specification");
+        }
+
+        let committed = fs::read_to_string(SPECIFICATION_PATH).expect("Failed to read committed specification");
+
+        assert!(committed == generated, "openapi.json is out of date, regenerate it with: UPDATE_OPENAPI=1 cargo test committed_specification_matches_generated");
+    }
+
+    #[test]
+    fn secured_operations_state_required_role() {
+        let specification = serde_json::to_value(ApiDoc::openapi()).unwrap();
+
+        for (path, operations) in specification["paths"].as_object().unwrap() {
+            for (method, operation) in operations.as_object().unwrap() {
+                if operation.get("security").is_some() {
+                    let description = operation["description"].as_str().unwrap_or_default();
+                    assert!(description.contains("Requires role"), "{} {} is secured but does not state its required role", method, path);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn get_openapi_returns_200() {
+        let service = openapi_route();
+
+        let request = Request::builder()
+            .uri("/openapi.json")
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = service
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        // Assert that the response status is 200
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        // Assert that the bearer scheme is part of the served document
+        assert_eq!(response_json["components"]["securitySchemes"]["bearer_auth"]["scheme"], "bearer");
+    }
+
+    #[tokio::test]
+    async fn docs_are_served_without_external_assets() {
+        let service = openapi_route();
+
+        let request = Request::builder()
+            .uri("/docs")
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+        let response = service.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Every asset referenced by the page is served by the API itself
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let page = String::from_utf8(body.to_vec()).unwrap();
+        assert!(!page.contains("https://"));
+
+        for (uri, content_type) in [("/docs/swagger-ui.css", "text/css"), ("/docs/swagger-ui-bundle.js", "text/javascript")] {
+            assert!(page.contains(uri));
+
+            let request = Request::builder()
+                .uri(uri)
+                .method("GET")
+                .body(Body::empty())
+                .unwrap();
+            let response = service.clone().oneshot(request).await.unwrap();
+
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers().get("content-type").unwrap(), content_type);
+        }
+    }
+}
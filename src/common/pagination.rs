@@ -0,0 +1,105 @@
+use axum::{extract::rejection::QueryRejection, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use utoipa::{IntoParams, ToSchema};
+use crate::{audit::model::AuditEvent, empires::model::{Empire, EmpireDocument, TerritoryClaim}, locations::model::{Location, LocationDocument, NearbyLocation, SovereigntyChange},
+    players::model::PlayerDocument, search::model::SearchHit, ships::model::Ship, stargates::model::Stargate, users::model::User};
+
+pub const DEFAULT_LIMIT: i64 = 50;
+pub const MAX_LIMIT: i64 = 500;
+
+// Query parameters shared by the list endpoints
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListQuery {
+    /// Only rows created or modified at or after this RFC 3339 timestamp, for incremental sync
+    pub updated_since: Option<DateTime<Utc>>,
+    /// Maximum number of items, between 1 and 500. Defaults to 50
+    pub limit: Option<i64>,
+    /// Number of items to skip. Defaults to 0
+    pub offset: Option<i64>,
+}
+
+// Query parameters of lists that are only paged through
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageQuery {
+    /// Maximum number of items, between 1 and 500. Defaults to 50
+    pub limit: Option<i64>,
+    /// Number of items to skip. Defaults to 0
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Page {
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl Page {
+    pub fn new(limit: Option<i64>, offset: Option<i64>) -> Result<Page, (StatusCode, Json<Value>)> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT);
+        let offset = offset.unwrap_or(0);
+
+        if !(1..=MAX_LIMIT).contains(&limit) {
+            return Err((StatusCode::BAD_REQUEST, Json(json!({"error": format!("Query parameter 'limit' must be between 1 and {}", MAX_LIMIT)}))));
+        }
+        if offset < 0 {
+            return Err((StatusCode::BAD_REQUEST, Json(json!({"error": "Query parameter 'offset' must not be negative"}))));
+        }
+
+        Ok(Page { limit, offset })
+    }
+}
+
+impl ListQuery {
+    pub fn page(&self) -> Result<Page, (StatusCode, Json<Value>)> {
+        Page::new(self.limit, self.offset)
+    }
+}
+
+impl PageQuery {
+    pub fn page(&self) -> Result<Page, (StatusCode, Json<Value>)> {
+        Page::new(self.limit, self.offset)
+    }
+}
+
+// Envelope of list responses. Resource lists are ordered by modification time, so a client syncing incrementally
+// pages through with 'offset' and passes the last 'updated_at' it saw as 'updated_since' on its next run
+#[derive(Debug, Serialize, ToSchema)]
+#[aliases(LocationPage = Paginated<Location>, EmpirePage = Paginated<Empire>, UserPage = Paginated<User>, AuditEventPage = Paginated<AuditEvent>, StargatePage = Paginated<Stargate>,
+    NearbyLocationPage = Paginated<NearbyLocation>, SovereigntyChangePage = Paginated<SovereigntyChange>,
+    TerritoryClaimPage = Paginated<TerritoryClaim>, ShipPage = Paginated<Ship>,
+    EmpireDocumentPage = Paginated<EmpireDocument>, LocationDocumentPage = Paginated<LocationDocument>, PlayerDocumentPage = Paginated<PlayerDocument>,
+    SearchHitPage = Paginated<SearchHit>)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl<T> Paginated<T> {
+    pub fn new(items: Vec<T>, page: Page) -> Paginated<T> {
+        Paginated { items, limit: page.limit, offset: page.offset }
+    }
+}
+
+// Malformed query strings are reported as JSON like every other client error
+pub fn invalid_query(rejection: QueryRejection) -> (StatusCode, Json<Value>) {
+    (StatusCode::BAD_REQUEST, Json(json!({"error": rejection.body_text()})))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use crate::common::pagination::{Page, DEFAULT_LIMIT};
+
+    #[test]
+    fn page_defaults_and_bounds() {
+        assert_eq!(Page::new(None, None).unwrap(), Page { limit: DEFAULT_LIMIT, offset: 0 });
+        assert_eq!(Page::new(Some(0), None).unwrap_err().0, StatusCode::BAD_REQUEST);
+        assert_eq!(Page::new(Some(501), None).unwrap_err().0, StatusCode::BAD_REQUEST);
+        assert_eq!(Page::new(None, Some(-1)).unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,58 @@
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+// Applies an RFC 7396 merge patch to the upsert representation of a resource and deserializes the result.
+// Members unknown to the representation are rejected rather than silently ignored
+pub fn apply_merge_patch<T: Serialize + DeserializeOwned>(current: &T, patch: &Value) -> Result<T, String> {
+    let patch_members = match patch {
+        Value::Object(members) => members,
+        _ => return Err("Merge patch must be a JSON object".to_string()),
+    };
+
+    let mut document = serde_json::to_value(current)
+        .map_err(|err| format!("Failed to serialize resource: {}", err))?;
+
+    if let Some(unknown) = patch_members.keys().find(|member| document.get(member.as_str()).is_none()) {
+        return Err(format!("Unknown field '{}'", unknown));
+    }
+
+    json_patch::merge(&mut document, patch);
+
+    serde_json::from_value(document).map_err(|err| format!("Invalid merged document: {}", err))
+}
+
+// Value for a changeset column, None when the merge left it untouched
+pub fn changed<T: PartialEq + Clone>(current: &T, merged: &T) -> Option<T> {
+    (current != merged).then(|| merged.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{common::patch::apply_merge_patch, locations::model::UpsertLocation};
+
+    #[test]
+    fn merge_replaces_only_given_members() {
+        let current = UpsertLocation { star_system: "Fountain".to_string(), area: "The Serpent's Lair".to_string(), ..UpsertLocation::default() };
+
+        let merged = apply_merge_patch(&current, &json!({"area": "Pegasus"})).expect("Merge failed");
+
+        assert_eq!(merged.star_system, current.star_system);
+        assert_eq!(merged.area, "Pegasus");
+    }
+
+    #[test]
+    fn merge_fails_on_removed_required_member() {
+        let current = UpsertLocation { star_system: "Fountain".to_string(), area: "The Serpent's Lair".to_string(), ..UpsertLocation::default() };
+
+        // 'null' removes the member, which is required by the upsert model
+        assert!(apply_merge_patch(&current, &json!({"area": null})).is_err());
+    }
+
+    #[test]
+    fn merge_fails_on_unknown_member() {
+        let current = UpsertLocation { star_system: "Fountain".to_string(), area: "The Serpent's Lair".to_string(), ..UpsertLocation::default() };
+
+        assert!(apply_merge_patch(&current, &json!({"galaxy": "Andromeda"})).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+use axum::{
+    body::{boxed, Full},
+    http::{header, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::Value;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, RequestId, SetRequestIdLayer};
+
+// Accepts an incoming 'X-Request-Id' header as is and generates a UUID v4 for requests without one
+pub fn set_request_id_layer() -> SetRequestIdLayer<MakeRequestUuid> {
+    SetRequestIdLayer::x_request_id(MakeRequestUuid)
+}
+
+// Echoes the request id in the 'X-Request-Id' response header
+pub fn propagate_request_id_layer() -> PropagateRequestIdLayer {
+    PropagateRequestIdLayer::x_request_id()
+}
+
+pub fn request_id<B>(request: &Request<B>) -> Option<String> {
+    request.extensions().get::<RequestId>()
+        .and_then(|request_id| request_id.header_value().to_str().ok())
+        .map(|request_id| request_id.to_string())
+}
+
+// Adds the request id as the 'instance' field of JSON error bodies so that error reports can be matched with log lines
+pub async fn include_request_id_in_errors<B>(request: Request<B>, next: Next<B>) -> Response {
+    let request_id = request_id(&request);
+    let response = next.run(request).await;
+
+    let is_json_error = (response.status().is_client_error() || response.status().is_server_error())
+        && response.headers().get(header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .map(|content_type| content_type.starts_with("application/json"))
+            .unwrap_or(false);
+
+    let request_id = match request_id {
+        Some(request_id) if is_json_error => request_id,
+        _ => return response,
+    };
+
+    let (mut parts, body) = response.into_parts();
+
+    let bytes = match hyper::body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(_) => return parts.status.into_response(),
+    };
+
+    let body = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(Value::Object(mut error_body)) => {
+            error_body.insert("instance".to_string(), Value::String(request_id));
+            serde_json::to_vec(&error_body).unwrap_or_else(|_| bytes.to_vec())
+        }
+        _ => bytes.to_vec(),
+    };
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, boxed(Full::from(body)))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+        middleware,
+        routing::get,
+        Json,
+        Router,
+    };
+    use serde_json::json;
+    use tower::{ServiceBuilder, ServiceExt};
+    use crate::common::request_id::{include_request_id_in_errors, propagate_request_id_layer, set_request_id_layer};
+
+    // Router with a single failing route wrapped in the same request id layers as the application
+    fn failing_route() -> Router {
+        Router::new()
+            .route("/fail", get(|| async { (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to update location"}))) }))
+            .layer(ServiceBuilder::new()
+                .layer(set_request_id_layer())
+                .layer(propagate_request_id_layer())
+                .layer(middleware::from_fn(include_request_id_in_errors)))
+    }
+
+    #[tokio::test]
+    async fn error_body_contains_provided_request_id() {
+        let request = Request::builder()
+            .uri("/fail")
+            .header("x-request-id", "trace-me-123")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = failing_route().oneshot(request).await.unwrap();
+
+        // Assert that the provided id is echoed in the response header
+        assert_eq!(response.headers().get("x-request-id").unwrap(), "trace-me-123");
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(response_json, json!({"error": "Failed to update location", "instance": "trace-me-123"}));
+    }
+
+    #[tokio::test]
+    async fn request_id_is_generated_when_missing() {
+        let request = Request::builder()
+            .uri("/fail")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = failing_route().oneshot(request).await.unwrap();
+        let request_id = response.headers().get("x-request-id").expect("Missing request id").to_str().unwrap().to_string();
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        // Assert that the generated id in the header matches the one in the error body
+        assert_eq!(response_json["instance"], request_id);
+    }
+}
@@ -5,8 +5,13 @@ use bcrypt::hash;
 use http::{HeaderMap, StatusCode};
 use jsonwebtoken::{Algorithm, decode, DecodingKey, TokenData, Validation, errors::ErrorKind as JwtErrorKind, encode, Header, EncodingKey};
 use serde_json::{json, Value};
+use tracing::{debug, warn, Span};
 use crate::{
-    common::{db::ConnectionPool, util::load_environment_variable},
+    common::{
+        db::ConnectionPool,
+        metrics::{record_auth_failure, record_auth_success},
+        util::load_environment_variable,
+    },
     users::{
         model::{Claims, User, UpsertUser, UserRole, string_to_user_role},
         service::service::UsersTable as UsersDB,
@@ -48,6 +53,7 @@ pub fn decode_claims(headers: &HeaderMap) -> Result<Option<TokenData<Claims>>, (
     // Map token if it exists - return error if not
     let token = match token_header {
         None => {
+            record_auth_failure("missing_header");
             return Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(json!({"error": "Missing header"})),
@@ -58,7 +64,8 @@ pub fn decode_claims(headers: &HeaderMap) -> Result<Option<TokenData<Claims>>, (
 
     // Return error if the the token does not start with "Bearer"
     if !token.starts_with("Bearer ") {
-        eprintln!("Token is missing 'Bearer ' prefix");
+        warn!("Token is missing 'Bearer ' prefix");
+        record_auth_failure("missing_bearer_prefix");
         return Err((
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({"error": "Token is missing 'Bearer ' prefix"})),
@@ -75,7 +82,8 @@ pub fn decode_claims(headers: &HeaderMap) -> Result<Option<TokenData<Claims>>, (
             match err.kind() {
                 // Handle the specific ExpiredSignature error
                 JwtErrorKind::ExpiredSignature => {
-                    eprintln!("JWT expired: {:?}", err);
+                    warn!("JWT expired: {:?}", err);
+                    record_auth_failure("expired_token");
                     Err((
                         StatusCode::UNAUTHORIZED,
                         Json(json!({"error": "Token has expired"})),
@@ -83,7 +91,8 @@ pub fn decode_claims(headers: &HeaderMap) -> Result<Option<TokenData<Claims>>, (
                 }
                 _ => {
                     // Handle other decoding errors
-                    eprintln!("Error decoding JWT: {:?}", err);
+                    warn!("Error decoding JWT: {:?}", err);
+                    record_auth_failure("invalid_token");
                     Err((
                         StatusCode::UNAUTHORIZED,
                         Json(json!({"error": "Invalid JWT"})),
@@ -95,6 +104,22 @@ pub fn decode_claims(headers: &HeaderMap) -> Result<Option<TokenData<Claims>>, (
     }
 }
 
+// Whether 'user_role' is a superset of or equal to 'required_role'
+pub fn has_role(user_role: &UserRole, required_role: &UserRole) -> bool {
+    // Accessing this map under UserRole key will return a list of associated subset roles
+    let role_hierarchy: HashMap<UserRole, Vec<UserRole>> = {
+        let mut hierarchy = HashMap::new();
+        hierarchy.insert(UserRole::ADMIN, vec![UserRole::ADMIN, UserRole::EDITOR, UserRole::WRITER, UserRole::READER]);
+        hierarchy.insert(UserRole::EDITOR, vec![UserRole::EDITOR, UserRole::WRITER, UserRole::READER]);
+        hierarchy.insert(UserRole::WRITER, vec![UserRole::WRITER, UserRole::READER]);
+        hierarchy.insert(UserRole::READER, vec![UserRole::READER]);
+        hierarchy
+    };
+
+    // Check if the list of UserRoles associated with HashMap retrieval under key 'user_role' contains the required role
+    role_hierarchy.get(user_role).map(|roles| roles.contains(required_role)).unwrap_or(false)
+}
+
 pub async fn enforce_role_policy(
     shared_state: &ConnectionPool,
     claims: &Option<TokenData<Claims>>,
@@ -104,30 +129,33 @@ pub async fn enforce_role_policy(
     let mut users = UsersDB::new(connection);
 
     match users.get_by_email(claims.clone().unwrap().claims.sub) {
-        Ok(user) => {
-            let user_role = string_to_user_role(user.clone().unwrap().role);
-
-            // Accessing this map under UserRole key will return a list of associated subset roles
-            let role_hierarchy: HashMap<UserRole, Vec<UserRole>> = {
-                let mut hierarchy = HashMap::new();
-                hierarchy.insert(UserRole::ADMIN, vec![UserRole::ADMIN, UserRole::EDITOR, UserRole::WRITER, UserRole::READER]);
-                hierarchy.insert(UserRole::EDITOR, vec![UserRole::EDITOR, UserRole::WRITER, UserRole::READER]);
-                hierarchy.insert(UserRole::WRITER, vec![UserRole::WRITER, UserRole::READER]);
-                hierarchy.insert(UserRole::READER, vec![UserRole::READER]);
-                hierarchy
-            };
-
-            // Check if the list of UserRoles associated with HashMap retrieval under key '&user_role' contains the required role '&required_role'
-            if role_hierarchy.get(&user_role).map(|roles| roles.contains(&required_role)).unwrap_or(false) {
-                eprintln!("Access granted: User role '{}' is a superset of or equal to required role '{}'", user_role, required_role);
-                Ok(user)
+        Ok(Some(user)) => {
+            let user_role = string_to_user_role(user.role.clone());
+
+            if has_role(&user_role, &required_role) {
+                debug!("Access granted: User role '{}' is a superset of or equal to required role '{}'", user_role, required_role);
+
+                // Attach the authorized user to the request span opened by the trace layer
+                Span::current().record("user_id", user.id);
+
+                record_auth_success();
+
+                Ok(Some(user))
             } else {
-                eprintln!("User role: {} does not match required role: {}", user_role, required_role);
+                warn!("User role: {} does not match required role: {}", user_role, required_role);
+                record_auth_failure("insufficient_role");
                 Err((StatusCode::UNAUTHORIZED, Json(json!({"error": format!("Current role of {} does not have access to {}", user_role, required_role)}))))
             }
         }
+        // The token outlived its user, which has been deleted since it was issued
+        Ok(None) => {
+            warn!("User in claims has been deleted");
+            record_auth_failure("unknown_user");
+            Err((StatusCode::UNAUTHORIZED, Json(json!({"error": "User no longer exists"}))))
+        }
         Err(err) => {
-            eprintln!("User in claims not found in DB {:?}", err);
+            warn!("User in claims not found in DB {:?}", err);
+            record_auth_failure("unknown_user");
             Err((StatusCode::UNAUTHORIZED, Json(json!({"error": "User in claims not found in DB"}))))
         }
     }
@@ -0,0 +1,90 @@
+use std::{sync::Arc, time::Duration};
+use tokio::sync::watch;
+use crate::common::util::load_optional_environment_variable;
+
+// Shared drain flag. Readiness reports unhealthy once it is set, and the servers stop accepting connections
+#[derive(Clone)]
+pub struct Shutdown {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Shutdown::new()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Shutdown {
+        let (sender, _) = watch::channel(false);
+        Shutdown { sender: Arc::new(sender) }
+    }
+
+    pub fn is_draining(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    pub fn start_draining(&self) {
+        self.sender.send_replace(true);
+    }
+
+    // Resolves once draining has started
+    pub async fn draining(&self) {
+        let mut receiver = self.sender.subscribe();
+
+        while !*receiver.borrow_and_update() {
+            if receiver.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShutdownConfig {
+    // Time between flipping readiness and closing the listeners, giving load balancers a chance to notice.
+    // Defaults to 5 seconds, roughly one probe interval, set SHUTDOWN_READINESS_DELAY_SECS=0 to close them at once
+    pub readiness_delay: Duration,
+    // Upper bound for in-flight requests to complete once the listeners are closed
+    pub drain_timeout: Duration,
+}
+
+impl ShutdownConfig {
+    pub fn from_environment() -> ShutdownConfig {
+        ShutdownConfig {
+            readiness_delay: seconds_from_environment("SHUTDOWN_READINESS_DELAY_SECS", 5),
+            drain_timeout: seconds_from_environment("SHUTDOWN_DRAIN_TIMEOUT_SECS", 30),
+        }
+    }
+}
+
+fn seconds_from_environment(variable_name: &str, default: u64) -> Duration {
+    let seconds = load_optional_environment_variable(variable_name)
+        .map(|value| value.parse().unwrap_or_else(|_| panic!("{} must be a number of seconds", variable_name)))
+        .unwrap_or(default);
+
+    Duration::from_secs(seconds)
+}
+
+// Completes on SIGINT (Ctrl+C) or SIGTERM
+pub async fn wait_for_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => tracing::info!("Received SIGINT"),
+        _ = terminate => tracing::info!("Received SIGTERM"),
+    }
+}
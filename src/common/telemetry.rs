@@ -0,0 +1,66 @@
+use std::time::Duration;
+use axum::{extract::MatchedPath, http::{Request, Response}};
+use tower_http::{
+    classify::{ServerErrorsAsFailures, SharedClassifier},
+    trace::{DefaultOnRequest, MakeSpan, OnResponse, TraceLayer},
+};
+use tracing::{field, info_span, Span};
+use tracing_subscriber::EnvFilter;
+use crate::common::{request_id::request_id, util::load_optional_environment_variable};
+
+pub type RequestTraceLayer = TraceLayer<SharedClassifier<ServerErrorsAsFailures>, RequestSpan, DefaultOnRequest, ResponseLog>;
+
+// Installs the global subscriber. LOG_FORMAT selects between 'json' and 'pretty' output, RUST_LOG overrides the default filter
+pub fn init_tracing() {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new("info,tower_http=info"));
+
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+
+    match load_optional_environment_variable("LOG_FORMAT").as_deref() {
+        Some("json") => subscriber.json().init(),
+        _ => subscriber.pretty().init(),
+    }
+}
+
+pub fn trace_layer() -> RequestTraceLayer {
+    TraceLayer::new_for_http()
+        .make_span_with(RequestSpan)
+        .on_response(ResponseLog)
+}
+
+// Opens one span per request, so every log line emitted while serving it carries the request id
+// 'status' and 'latency_ms' are filled in by ResponseLog, 'user_id' by enforce_role_policy
+#[derive(Clone, Copy)]
+pub struct RequestSpan;
+
+impl<B> MakeSpan<B> for RequestSpan {
+    fn make_span(&mut self, request: &Request<B>) -> Span {
+        let route = request.extensions().get::<MatchedPath>()
+            .map(|matched_path| matched_path.as_str().to_string())
+            .unwrap_or_else(|| request.uri().path().to_string());
+
+        let request_id = request_id(request).unwrap_or_default();
+
+        info_span!(
+            "request",
+            request_id = %request_id,
+            method = %request.method(),
+            route = %route,
+            status = field::Empty,
+            latency_ms = field::Empty,
+            user_id = field::Empty,
+        )
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ResponseLog;
+
+impl<B> OnResponse<B> for ResponseLog {
+    fn on_response(self, response: &Response<B>, latency: Duration, span: &Span) {
+        span.record("status", response.status().as_u16());
+        span.record("latency_ms", latency.as_millis() as u64);
+        tracing::info!("finished processing request");
+    }
+}
@@ -0,0 +1,269 @@
+use std::io;
+use axum::{
+    body::StreamBody,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use diesel::QueryResult;
+use serde::de::DeserializeOwned;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::error;
+use utoipa::{IntoParams, ToSchema};
+
+// Rows read from the database per chunk of an export
+pub const EXPORT_BATCH_SIZE: i64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Csv,
+    Ndjson,
+}
+
+impl Format {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Format::Csv => "text/csv",
+            Format::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    fn from_media_type(media_type: &str) -> Option<Format> {
+        match media_type {
+            "text/csv" => Some(Format::Csv),
+            "application/x-ndjson" | "application/ndjson" => Some(Format::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    /// Overrides the format negotiated through the Accept header
+    #[param(inline)]
+    pub format: Option<Format>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportQuery {
+    /// Overrides the format given by the Content-Type header
+    #[param(inline)]
+    pub format: Option<Format>,
+    /// Validate every row without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportReport {
+    /// Number of rows in the upload
+    pub rows: usize,
+    pub dry_run: bool,
+    /// Ids of the created rows in upload order, empty in dry-run mode
+    pub ids: Vec<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct RowError {
+    /// Line of the upload holding the row, starting at 1
+    pub row: usize,
+    pub error: String,
+}
+
+// Media types without parameters, in the order they are listed
+fn media_types(value: &str) -> impl Iterator<Item = &str> {
+    value.split(',').map(|media_type| media_type.split(';').next().unwrap_or_default().trim())
+}
+
+// The format parameter wins over the Accept header. Clients accepting anything get NDJSON
+pub fn export_format(format: Option<Format>, headers: &HeaderMap) -> Result<Format, (StatusCode, Json<Value>)> {
+    if let Some(format) = format {
+        return Ok(format);
+    }
+
+    let accept = match headers.get(header::ACCEPT).and_then(|accept| accept.to_str().ok()) {
+        Some(accept) => accept,
+        None => return Ok(Format::Ndjson),
+    };
+
+    media_types(accept)
+        .find_map(|media_type| match media_type {
+            "*/*" | "application/*" => Some(Format::Ndjson),
+            "text/*" => Some(Format::Csv),
+            media_type => Format::from_media_type(media_type),
+        })
+        .ok_or((StatusCode::NOT_ACCEPTABLE, Json(json!({"error": "Exports are available as text/csv or application/x-ndjson"}))))
+}
+
+// The format parameter wins over the Content-Type header
+pub fn import_format(format: Option<Format>, headers: &HeaderMap) -> Result<Format, (StatusCode, Json<Value>)> {
+    format
+        .or_else(|| headers.get(header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .and_then(|content_type| media_types(content_type).next())
+            .and_then(Format::from_media_type))
+        .ok_or((StatusCode::UNSUPPORTED_MEDIA_TYPE, Json(json!({"error": "Imports are accepted as text/csv or application/x-ndjson"}))))
+}
+
+// Streams every row returned by 'fetch', which is called with the key of the last row sent until it returns no rows.
+// Reading happens on a blocking thread one batch ahead of the client, so memory use does not grow with the table
+pub fn export<T, F, K>(format: Format, mut fetch: F, key: K) -> Response
+where
+    T: serde::Serialize + Send + 'static,
+    F: FnMut(i32, i64) -> QueryResult<Vec<T>> + Send + 'static,
+    K: Fn(&T) -> i32 + Send + 'static,
+{
+    let (sender, receiver) = tokio::sync::mpsc::channel::<io::Result<Vec<u8>>>(1);
+
+    tokio::task::spawn_blocking(move || {
+        let mut after = 0;
+        let mut first_batch = true;
+
+        loop {
+            let chunk = fetch(after, EXPORT_BATCH_SIZE)
+                .map_err(|err| err.to_string())
+                .and_then(|rows| {
+                    let chunk = encode(format, &rows, first_batch)?;
+                    Ok((rows.last().map(&key), chunk))
+                });
+
+            match chunk {
+                Ok((Some(last_key), chunk)) => {
+                    // The client went away
+                    if sender.blocking_send(Ok(chunk)).is_err() {
+                        return;
+                    }
+                    after = last_key;
+                    first_batch = false;
+                }
+                Ok((None, _)) => return,
+                Err(err) => {
+                    // Headers are already sent, so the failure can only be signalled by aborting the body
+                    error!("Error exporting rows: {}", err);
+                    let _ = sender.blocking_send(Err(io::Error::other(err)));
+                    return;
+                }
+            }
+        }
+    });
+
+    let chunks = futures_util::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    });
+
+    (StatusCode::OK, [(header::CONTENT_TYPE, format.content_type())], StreamBody::new(chunks)).into_response()
+}
+
+// Serializes rows as CSV records or as one JSON document per line. Only the first CSV chunk carries the header
+pub fn encode<T: serde::Serialize>(format: Format, rows: &[T], with_header: bool) -> Result<Vec<u8>, String> {
+    match format {
+        Format::Csv => {
+            let mut writer = csv::WriterBuilder::new().has_headers(with_header).from_writer(Vec::new());
+            for row in rows {
+                writer.serialize(row).map_err(|err| err.to_string())?;
+            }
+            writer.into_inner().map_err(|err| err.to_string())
+        }
+        Format::Ndjson => {
+            let mut lines = Vec::new();
+            for row in rows {
+                serde_json::to_writer(&mut lines, row).map_err(|err| err.to_string())?;
+                lines.push(b'\n');
+            }
+            Ok(lines)
+        }
+    }
+}
+
+// Deserializes every row of an upload along with its line, reporting all malformed rows rather than only the first.
+// CSV uploads start with a header naming the columns, columns unknown to the model are ignored
+pub fn parse_rows<T: DeserializeOwned>(format: Format, body: &[u8]) -> Result<Vec<(usize, T)>, Vec<RowError>> {
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+
+    match format {
+        Format::Csv => {
+            let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(body);
+            let headers = reader.headers().cloned().map_err(|err| vec![RowError { row: 1, error: err.to_string() }])?;
+
+            for record in reader.records() {
+                match record {
+                    Ok(record) => {
+                        let row = record.position().map(|position| position.line() as usize).unwrap_or_default();
+                        match record.deserialize(Some(&headers)) {
+                            Ok(value) => rows.push((row, value)),
+                            Err(err) => errors.push(RowError { row, error: err.to_string() }),
+                        }
+                    }
+                    Err(err) => {
+                        let row = err.position().map(|position| position.line() as usize).unwrap_or_default();
+                        errors.push(RowError { row, error: err.to_string() });
+                    }
+                }
+            }
+        }
+        Format::Ndjson => {
+            let body = String::from_utf8_lossy(body);
+            for (index, line) in body.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+                match serde_json::from_str(line) {
+                    Ok(value) => rows.push((index + 1, value)),
+                    Err(err) => errors.push(RowError { row: index + 1, error: err.to_string() }),
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() { Ok(rows) } else { Err(errors) }
+}
+
+pub fn invalid_rows(rows: Vec<RowError>) -> (StatusCode, Json<Value>) {
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": "Import contains invalid rows, nothing was written", "rows": rows})))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+    use crate::{
+        common::transfer::{encode, export_format, parse_rows, Format, RowError},
+        ships::model::UpsertShip,
+    };
+
+    #[test]
+    fn csv_round_trips_and_reports_every_malformed_row() {
+        let ships = vec![
+            UpsertShip { name: "Rifter".to_string(), category: Some("Frigate".to_string()), description: None, empire_id: 4 },
+            UpsertShip { name: "Stabber".to_string(), category: None, description: Some("Fast, with projectile turrets".to_string()), empire_id: 4 },
+        ];
+
+        let csv = encode(Format::Csv, &ships, true).unwrap();
+        assert_eq!(String::from_utf8(csv.clone()).unwrap(), "name,category,description,empire_id\n\
+            Rifter,Frigate,,4\n\
+            Stabber,,\"Fast, with projectile turrets\",4\n");
+
+        let rows = parse_rows::<UpsertShip>(Format::Csv, &csv).unwrap();
+        assert_eq!(rows.iter().map(|(row, ship)| (*row, ship.category.clone())).collect::<Vec<_>>(), vec![(2, Some("Frigate".to_string())), (3, None)]);
+
+        let malformed = "name,empire_id\nRifter,four\nStabber,4\nClaw,\n";
+        assert_eq!(parse_rows::<UpsertShip>(Format::Csv, malformed.as_bytes()).unwrap_err().iter().map(|err| err.row).collect::<Vec<_>>(), vec![2, 4]);
+
+        let ndjson = "{\"name\": \"Rifter\", \"empire_id\": 4}\n\n{\"name\": \"Claw\"}\n";
+        assert!(matches!(parse_rows::<UpsertShip>(Format::Ndjson, ndjson.as_bytes()).unwrap_err().as_slice(), [RowError { row: 3, .. }]));
+    }
+
+    #[test]
+    fn export_format_prefers_query_over_accept() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(export_format(None, &headers).unwrap(), Format::Ndjson);
+
+        headers.insert(header::ACCEPT, HeaderValue::from_static("application/xml, text/csv;q=0.9"));
+        assert_eq!(export_format(None, &headers).unwrap(), Format::Csv);
+        assert_eq!(export_format(Some(Format::Ndjson), &headers).unwrap(), Format::Ndjson);
+
+        headers.insert(header::ACCEPT, HeaderValue::from_static("application/xml"));
+        assert_eq!(export_format(None, &headers).unwrap_err().0, StatusCode::NOT_ACCEPTABLE);
+    }
+}
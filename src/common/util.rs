@@ -7,3 +7,15 @@ pub fn load_environment_variable(variable_name: &str) -> String {
         .expect(&format!("{} must be set", variable_name))
 }
 
+pub fn load_optional_environment_variable(variable_name: &str) -> Option<String> {
+    dotenv().ok();
+    env::var(variable_name).ok()
+}
+
+// Rejects values longer than the VARCHAR column they are stored in
+pub fn check_length(field: &str, value: &str, max_length: usize) -> Result<(), String> {
+    if value.chars().count() > max_length {
+        return Err(format!("'{}' must be at most {} characters", field, max_length));
+    }
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use axum::{http::{header::HeaderName, HeaderValue}, Router};
+use tower::ServiceBuilder;
+use tower_http::set_header::SetResponseHeaderLayer;
+use crate::{
+    audit::router::router::audit_route,
+    common::{db::ConnectionPool, util::load_optional_environment_variable},
+    empires::router::router::{create_empire_handler, delete_empire_handler, empires_route, read_empire_handler, update_empire_handler},
+    locations::router::router::{create_location_handler, delete_location_handler, locations_route, read_location_handler, update_location_handler},
+    regions::router::router::regions_route,
+    search::router::router::search_route,
+    ships::router::router::ships_route,
+    stargates::router::router::stargates_route,
+    users::router::router::{create_user_handler, delete_user_handler, get_user_handler, login_user_handler, update_user_handler, users_route},
+};
+
+// Resource routers are version agnostic. A version is the set of routers mounted under its prefix,
+// so a future '/v2' can combine new handlers for changed resources with the v1 routers for the rest
+pub fn v1_route(shared_connection_pool: ConnectionPool) -> Router {
+    users_route(shared_connection_pool.clone())
+        .merge(locations_route(shared_connection_pool.clone()))
+        .merge(empires_route(shared_connection_pool.clone()))
+        .merge(ships_route(shared_connection_pool.clone()))
+        .merge(stargates_route(shared_connection_pool.clone()))
+        .merge(regions_route(shared_connection_pool.clone()))
+        .merge(search_route(shared_connection_pool.clone()))
+        .merge(audit_route(shared_connection_pool))
+}
+
+// The unversioned routes as they were before '/v1'. The set is frozen, routes added since are only served under a version prefix
+pub fn legacy_route(shared_connection_pool: ConnectionPool) -> Router {
+    Router::new()
+        .route("/users", axum::routing::post(create_user_handler))
+        .route("/users/:user_id", axum::routing::get(get_user_handler))
+        .route("/users/:user_id", axum::routing::put(update_user_handler))
+        .route("/users/:user_id", axum::routing::delete(delete_user_handler))
+        .route("/users/login", axum::routing::post(login_user_handler))
+        .route("/locations", axum::routing::post(create_location_handler))
+        .route("/locations/:location_id", axum::routing::get(read_location_handler))
+        .route("/locations/:location_id", axum::routing::put(update_location_handler))
+        .route("/locations/:location_id", axum::routing::delete(delete_location_handler))
+        .route("/empires", axum::routing::post(create_empire_handler))
+        .route("/empires/:empire_id", axum::routing::get(read_empire_handler))
+        .route("/empires/:empire_id", axum::routing::put(update_empire_handler))
+        .route("/empires/:empire_id", axum::routing::delete(delete_empire_handler))
+        .with_state(shared_connection_pool)
+}
+
+// Mounts every supported version. The legacy routes remain as a deprecated alias of their v1 counterparts,
+// with LEGACY_API_SUNSET (an HTTP-date) announcing when they are removed
+pub fn versioned_route(shared_connection_pool: ConnectionPool) -> Router {
+    let legacy_policy = DeprecationPolicy {
+        sunset: load_optional_environment_variable("LEGACY_API_SUNSET"),
+        successor: "/v1",
+    };
+
+    Router::new()
+        .nest("/v1", v1_route(shared_connection_pool.clone()))
+        .merge(deprecated(legacy_route(shared_connection_pool), &legacy_policy))
+}
+
+pub struct DeprecationPolicy {
+    pub sunset: Option<String>,
+    pub successor: &'static str,
+}
+
+// Adds 'Deprecation', 'Sunset' and a 'Link' to the successor version to every response of a route of the router
+pub fn deprecated(router: Router, policy: &DeprecationPolicy) -> Router {
+    let sunset = policy.sunset.as_deref()
+        .map(|sunset| HeaderValue::from_str(sunset).expect("Sunset must be a valid header value"));
+    let link = HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", policy.successor))
+        .expect("Successor must be a valid header value");
+
+    router.route_layer(ServiceBuilder::new()
+        .layer(SetResponseHeaderLayer::overriding(HeaderName::from_static("deprecation"), HeaderValue::from_static("true")))
+        .layer(SetResponseHeaderLayer::overriding(HeaderName::from_static("link"), link))
+        .layer(SetResponseHeaderLayer::overriding(HeaderName::from_static("sunset"), sunset)))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+    use crate::common::{
+        db::create_shared_connection_pool,
+        util::load_environment_variable,
+        versioning::{deprecated, legacy_route, versioned_route, DeprecationPolicy},
+    };
+
+    #[tokio::test]
+    async fn v1_routes_are_not_deprecated() {
+        let database_url = load_environment_variable("TEST_DB");
+        let connection_pool = create_shared_connection_pool(database_url, 1);
+        let service = versioned_route(connection_pool);
+
+        let request = Request::builder()
+            .uri(format!("/v1/users/{}", -666)) // Use a non-existent ID
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = service
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        // Assert that the route exists under the version prefix and carries no deprecation headers
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(response.headers().get("deprecation").is_none());
+    }
+
+    #[tokio::test]
+    async fn deprecated_routes_announce_deprecation_and_sunset() {
+        let database_url = load_environment_variable("TEST_DB");
+        let connection_pool = create_shared_connection_pool(database_url, 1);
+        let policy = DeprecationPolicy {
+            sunset: Some("Sat, 01 Nov 2025 00:00:00 GMT".to_string()),
+            successor: "/v1",
+        };
+        let service = deprecated(legacy_route(connection_pool), &policy);
+
+        let request = Request::builder()
+            .uri(format!("/users/{}", -666)) // Use a non-existent ID
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = service
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        // Assert that the legacy route still answers, with deprecation headers attached
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers().get("deprecation").unwrap(), "true");
+        assert_eq!(response.headers().get("sunset").unwrap(), "Sat, 01 Nov 2025 00:00:00 GMT");
+        assert_eq!(response.headers().get("link").unwrap(), "</v1>; rel=\"successor-version\"");
+    }
+
+    #[tokio::test]
+    async fn routes_added_after_v1_have_no_legacy_alias() {
+        let database_url = load_environment_variable("TEST_DB");
+        let connection_pool = create_shared_connection_pool(database_url, 1);
+        let service = versioned_route(connection_pool);
+
+        let request = Request::builder()
+            .uri("/stargates")
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = service
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        // Assert that the route is unknown outside of the version prefix
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(response.headers().get("deprecation").is_none());
+    }
+}
@@ -1,22 +1,141 @@
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use serde_derive::{Serialize, Deserialize};
-use crate::schema::empires;
+use utoipa::ToSchema;
+use diesel::{dsl::sql, expression::SqlLiteral, sql_types::{Integer, Nullable, Text, Timestamptz}};
+use crate::{common::{fields::FieldSet, patch::changed, util::check_length}, locations::model::Location, players::model::Player, schema::empires, ships::model::Ship};
 
-#[derive(Serialize, Debug, Clone, Queryable)]
+#[derive(Serialize, Debug, Clone, Queryable, ToSchema)]
 #[diesel(table_name = empires)]
 pub struct Empire {
     pub id: i32,
     pub name: String,
     pub slogan: String,
     pub location_id: i32,
-    pub description: String
+    pub description: String,
+    #[serde(skip)]
+    pub version: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// User who created the empire and may manage its territory, null for seeded empires
+    pub owner_id: Option<i32>,
+}
+
+// Selection loading an Empire, see Empire::columns
+pub type EmpireColumns = (
+    SqlLiteral<Integer>, SqlLiteral<Text>, SqlLiteral<Text>, SqlLiteral<Integer>, SqlLiteral<Text>, SqlLiteral<Integer>,
+    SqlLiteral<Timestamptz>, SqlLiteral<Timestamptz>, SqlLiteral<Nullable<Timestamptz>>, SqlLiteral<Nullable<Integer>>,
+);
+
+impl Empire {
+    pub const FIELDS: [&'static str; 8] = ["id", "name", "slogan", "location_id", "description", "created_at", "updated_at", "owner_id"];
+
+    // Columns of the row in model order, reading only those of the fieldset besides the id and the version served as ETag
+    pub fn columns(fields: &FieldSet) -> EmpireColumns {
+        (
+            sql("empires.id"),
+            fields.column("name", "empires.name", "''"),
+            fields.column("slogan", "empires.slogan", "''"),
+            fields.column("location_id", "empires.location_id", "0"),
+            fields.column("description", "empires.description", "''"),
+            sql("empires.version"),
+            fields.column("created_at", "empires.created_at", "'epoch'::timestamptz"),
+            fields.column("updated_at", "empires.updated_at", "'epoch'::timestamptz"),
+            sql("NULL"),
+            fields.column("owner_id", "empires.owner_id", "NULL"),
+        )
+    }
+}
+
+// An empire with the related resources named in '?include='. Relations that were not asked for are left out
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct EmpireDocument {
+    #[serde(flatten)]
+    pub empire: Empire,
+    /// Home location, null when it has been deleted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Location>)]
+    pub location: Option<Option<Location>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ships: Option<Vec<Ship>>,
+    /// Players flying a ship of the empire
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub players: Option<Vec<Player>>,
 }
 
-#[derive(Debug, Clone, Insertable, Deserialize, Serialize)]
+impl EmpireDocument {
+    pub const INCLUDES: [&'static str; 3] = ["location", "ships", "players"];
+}
+
+#[derive(Debug, Clone, Insertable, Deserialize, Serialize, ToSchema)]
 #[diesel(table_name = empires)]
 pub struct UpsertEmpire {
     pub name: String,
     pub slogan: String,
     pub location_id: i32,
     pub description: String
-}
\ No newline at end of file
+}
+
+impl UpsertEmpire {
+    // Column limits of the empires table, checked before any row is written
+    pub fn validate(&self) -> Result<(), String> {
+        check_length("name", &self.name, 100)?;
+        check_length("slogan", &self.slogan, 100)
+    }
+}
+
+impl From<Empire> for UpsertEmpire {
+    fn from(empire: Empire) -> Self {
+        UpsertEmpire { name: empire.name, slogan: empire.slogan, location_id: empire.location_id, description: empire.description }
+    }
+}
+
+// Columns changed by a merge patch, unchanged columns are left as None and skipped in the UPDATE
+#[derive(Debug, Clone, Default, AsChangeset)]
+#[diesel(table_name = empires)]
+pub struct EmpireChangeset {
+    pub name: Option<String>,
+    pub slogan: Option<String>,
+    pub location_id: Option<i32>,
+    pub description: Option<String>,
+}
+
+impl EmpireChangeset {
+    pub fn between(current: &UpsertEmpire, merged: &UpsertEmpire) -> EmpireChangeset {
+        EmpireChangeset {
+            name: changed(&current.name, &merged.name),
+            slogan: changed(&current.slogan, &merged.slogan),
+            location_id: changed(&current.location_id, &merged.location_id),
+            description: changed(&current.description, &merged.description),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.slogan.is_none() && self.location_id.is_none() && self.description.is_none()
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Queryable, ToSchema)]
+pub struct TerritoryClaim {
+    pub id: i32,
+    pub empire_id: i32,
+    pub location_id: i32,
+    pub claimed_at: DateTime<Utc>,
+    /// Null while the claim is held
+    pub released_at: Option<DateTime<Utc>>,
+}
+
+impl TerritoryClaim {
+    pub const FIELDS: [&'static str; 5] = ["id", "empire_id", "location_id", "claimed_at", "released_at"];
+}
+
+// Result of an empire claiming a location
+#[derive(Debug, Clone)]
+pub enum ClaimOutcome {
+    Claimed(TerritoryClaim),
+    AlreadyHeld(TerritoryClaim),
+    // Id of the empire holding the location
+    HeldBy(i32),
+}
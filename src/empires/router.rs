@@ -1,17 +1,32 @@
 pub mod router {
+    use std::collections::HashSet;
     use serde_json::{json, Value};
+    use tracing::error;
     use axum::{
-        Router, http::StatusCode, Json, response::IntoResponse, extract::State, extract,
+        Router, http::{header, StatusCode}, Json, body::Bytes, response::{IntoResponse, Response}, extract::{rejection::QueryRejection, Query, State}, extract,
     };
     use http::HeaderMap;
     use crate::{
+        audit::{model::AuditAction, service::service::record_event},
         common::db::ConnectionPool,
+        locations::{model::Location, service::service::LocationsTable},
+        players::{model::{Player, PlayerDocument}, service::service::{player_documents, PlayersTable}},
+        ships::{model::Ship, service::service::ShipsTable},
         empires::{
-            service::service::EmpiresTable as empiresTable,
-            model::UpsertEmpire
+            service::service::{empire_documents, empire_fields, EmpiresTable as empiresTable},
+            model::{ClaimOutcome, Empire, EmpireChangeset, EmpireDocument, UpsertEmpire}
         },
-        users::model::UserRole,
-        common::security::{enforce_role_policy, decode_claims}
+        users::model::{string_to_user_role, User, UserRole},
+        common::{
+            bulk::{apply_all, bulk_failure, bulk_response, check_bulk_size, validate_all, with_rejected, BulkMode, BulkQuery, BulkUpdate},
+            etag::{check_if_match, etag, is_not_modified, not_modified, precondition_failed},
+            fields::FieldsQuery,
+            include::IncludeQuery,
+            pagination::{invalid_query, ListQuery, PageQuery, Paginated},
+            transfer::{export, export_format, import_format, invalid_rows, parse_rows, ExportQuery, ImportQuery, ImportReport, RowError},
+            patch::apply_merge_patch,
+            security::{enforce_role_policy, decode_claims, has_role}
+        }
     };
 
     // - - - - - - - - - - - [ROUTES] - - - - - - - - - - -
@@ -19,14 +34,43 @@ pub mod router {
     pub fn empires_route(shared_connection_pool: ConnectionPool) -> Router {
         Router::new()
             .route("/empires", axum::routing::post(create_empire_handler))
+            .route("/empires", axum::routing::get(list_empires_handler))
+            .route("/empires/bulk", axum::routing::post(bulk_create_empires_handler))
+            .route("/empires/bulk", axum::routing::put(bulk_update_empires_handler))
+            .route("/empires/bulk", axum::routing::delete(bulk_delete_empires_handler))
+            .route("/empires/export", axum::routing::get(export_empires_handler))
+            .route("/empires/import", axum::routing::post(import_empires_handler))
             .route("/empires/:empire_id", axum::routing::get(read_empire_handler))
             .route("/empires/:empire_id", axum::routing::put(update_empire_handler))
+            .route("/empires/:empire_id", axum::routing::patch(patch_empire_handler))
             .route("/empires/:empire_id", axum::routing::delete(delete_empire_handler))
+            .route("/empires/:empire_id/restore", axum::routing::post(restore_empire_handler))
+            .route("/empires/:empire_id/ships", axum::routing::get(list_empire_ships_handler))
+            .route("/empires/:empire_id/players", axum::routing::get(list_empire_players_handler))
+            .route("/empires/:empire_id/territory", axum::routing::get(list_territory_handler))
+            .route("/empires/:empire_id/territory/:location_id", axum::routing::post(claim_location_handler))
+            .route("/empires/:empire_id/territory/:location_id", axum::routing::delete(release_location_handler))
             .with_state(shared_connection_pool)
     }
 
     // - - - - - - - - - - - [HANDLERS] - - - - - - - - - - -
 
+    /// Create an empire
+    ///
+    /// Requires role WRITER or higher. The creating user becomes the owner of the empire.
+    #[utoipa::path(
+        post,
+        path = "/empires",
+        tag = "empires",
+        request_body = UpsertEmpire,
+        responses(
+            (status = 201, description = "Empire created", body = Empire),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 422, description = "Invalid empire or location does not exist", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while creating empire", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
     pub async fn create_empire_handler(
         headers: HeaderMap,
         State(shared_state): State<ConnectionPool>,
@@ -43,14 +87,25 @@ pub mod router {
         let authorization = enforce_role_policy(&shared_state, &claims, UserRole::WRITER).await;
 
         match authorization {
-            Ok(_authorized_user) => {
+            Ok(authorized_user) => {
+                upsert_empire.validate().map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": err}))))?;
+
                 let connection = shared_state.pool.get()
                     .expect("Failed to acquire connection from pool");
 
-                match empiresTable::new(connection).create(upsert_empire) {
-                    Ok(new_empire) => Ok((StatusCode::CREATED, Json(new_empire))),
+                let owner_id = authorized_user.map(|user| user.id);
+                let result = empiresTable::new(connection).create(upsert_empire, owner_id);
+
+                match result {
+                    Ok(new_empire) => {
+                        record_event(&shared_state, &headers, AuditAction::Create, "empire", new_empire.id, None, Some(&new_empire));
+                        Ok((StatusCode::CREATED, Json(new_empire)))
+                    },
+                    Err(diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::ForeignKeyViolation, _)) => {
+                        Err((StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": "Location does not exist"}))))
+                    },
                     Err(err) => {
-                        eprintln!("Error creating empire: {:?}", err);
+                        error!("Error creating empire: {:?}", err);
                         Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to create empire"}))))
                     }
                 }
@@ -60,12 +115,91 @@ pub mod router {
     }
 
 
+    /// List empires
+    ///
+    /// Requires role READER or higher. Ordered by modification time, oldest first. '?include=' embeds any of 'location', 'ships' and 'players',
+    /// '?fields=' limits the fields of each empire.
+    #[utoipa::path(
+        get,
+        path = "/empires",
+        tag = "empires",
+        params(ListQuery, IncludeQuery, FieldsQuery),
+        responses(
+            (status = 200, description = "Page of empires", body = EmpireDocumentPage),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing empires", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_empires_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<ListQuery>, QueryRejection>,
+        include: Result<Query<IncludeQuery>, QueryRejection>,
+        fields: Result<Query<FieldsQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        let page = query.page()?;
+        let included = include.map_err(invalid_query)?.parse(&EmpireDocument::INCLUDES)?;
+        let fields = fields.map_err(invalid_query)?.parse(&Empire::FIELDS)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let listing_failed = |err: diesel::result::Error| {
+                    error!("Error listing empires: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list empires"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let empires = empiresTable::new(connection).list(query.updated_since, page, &empire_fields(&fields, &included)).map_err(listing_failed)?;
+                let documents = empire_documents(&shared_state, empires, &included).map_err(listing_failed)?;
+                let items = documents.iter().map(|document| fields.project(document, &included)).collect();
+                Ok((StatusCode::OK, Json(Paginated::new(items, page))))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Read an empire by id
+    ///
+    /// Requires role READER or higher. '?include=' embeds any of 'location', 'ships' and 'players', '?fields=' limits the fields of the empire.
+    /// The ETag is the version of the empire alone, so If-None-Match is only honoured when nothing is included.
+    #[utoipa::path(
+        get,
+        path = "/empires/{empire_id}",
+        tag = "empires",
+        params(("empire_id" = i32, Path, description = "Id of the empire"), IncludeQuery, FieldsQuery),
+        responses(
+            (status = 200, description = "Empire found", body = EmpireDocument, headers(("ETag" = String, description = "Version of the empire"))),
+            (status = 304, description = "Empire matches If-None-Match"),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Empire not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while reading empire", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
     pub async fn read_empire_handler(
         headers: HeaderMap,
         State(shared_state): State<ConnectionPool>,
         path: extract::Path<(i32, )>,
+        include: Result<Query<IncludeQuery>, QueryRejection>,
+        fields: Result<Query<FieldsQuery>, QueryRejection>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
         let (empire_id, ) = path.0;
+        let included = include.map_err(invalid_query)?.parse(&EmpireDocument::INCLUDES)?;
+        let fields = fields.map_err(invalid_query)?.parse(&Empire::FIELDS)?;
 
         // Decode claims from bearer token header
         let claims = match decode_claims(&headers) {
@@ -78,20 +212,98 @@ pub mod router {
 
         match authorization {
             Ok(_authorized_user) => {
+                let reading_failed = |err: diesel::result::Error| {
+                    error!("Error reading empire: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to read empire"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let empire = empiresTable::new(connection).read(empire_id, &empire_fields(&fields, &included)).map_err(reading_failed)?
+                    .ok_or_else(|| (StatusCode::NOT_FOUND, Json(json!({"error": "empire not found"}))))?;
+
+                let version = empire.version;
+                if included.is_empty() && is_not_modified(&headers, version) {
+                    return Ok(not_modified(version));
+                }
+
+                let document = empire_documents(&shared_state, vec![empire], &included).map_err(reading_failed)?.remove(0);
+                Ok((StatusCode::OK, [(header::ETAG, etag(version))], Json(fields.project(&document, &included))).into_response())
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Replace an empire
+    ///
+    /// Requires role EDITOR or higher.
+    #[utoipa::path(
+        put,
+        path = "/empires/{empire_id}",
+        tag = "empires",
+        params(("empire_id" = i32, Path, description = "Id of the empire")),
+        request_body = UpsertEmpire,
+        responses(
+            (status = 200, description = "Empire updated", body = Empire, headers(("ETag" = String, description = "Version of the empire"))),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Empire not found", body = ErrorBody),
+            (status = 412, description = "If-Match does not match the current version", body = ErrorBody),
+            (status = 422, description = "Invalid empire", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while updating empire", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn update_empire_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+        Json(upsert_empire): Json<UpsertEmpire>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (empire_id, ) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'EDITOR' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::EDITOR).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                upsert_empire.validate().map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": err}))))?;
+
                 let connection = shared_state.pool.get()
                     .expect("Failed to acquire connection from pool");
 
-                match empiresTable::new(connection).get(empire_id) {
-                    Ok(empire) => {
-                        if let Some(empire) = empire {
-                            Ok((StatusCode::OK, Json(empire)))
-                        } else {
-                            Err((StatusCode::NOT_FOUND, Json(json!({"error": "empire not found"}))))
-                        }
+                let mut empires = empiresTable::new(connection);
+
+                let before = empires.get(empire_id).map_err(|err| {
+                    error!("Error reading empire: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to update empire"})))
+                })?;
+
+                let expected_version = check_if_match(&headers, || Ok(before.as_ref().map(|empire| empire.version)))?;
+
+                let result = empires.update(empire_id, upsert_empire, expected_version);
+                // Hand the connection back to the pool before the audit event is recorded
+                drop(empires);
+
+                match result {
+                    Ok(updated_empire) => {
+                        record_event(&shared_state, &headers, AuditAction::Update, "empire", empire_id, before.as_ref(), Some(&updated_empire));
+                        Ok((StatusCode::OK, [(header::ETAG, etag(updated_empire.version))], Json(updated_empire)))
+                    },
+                    // A conditional write matching no row lost the race against a concurrent write
+                    Err(diesel::result::Error::NotFound) if expected_version.is_some() => Err(precondition_failed()),
+                    Err(diesel::result::Error::NotFound) => {
+                        Err((StatusCode::NOT_FOUND, Json(json!({"error": "empire not found"}))))
                     },
                     Err(err) => {
-                        eprintln!("Error reading empire: {:?}", err);
-                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to read empire"}))))
+                        error!("Error updating empire: {:?}", err);
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to update empire"}))))
                     }
                 }
             }
@@ -99,11 +311,30 @@ pub mod router {
         }
     }
 
-    pub async fn update_empire_handler(
+    /// Partially update an empire with a JSON merge patch (RFC 7396)
+    ///
+    /// Requires role EDITOR or higher. Only the columns changed by the patch are written.
+    #[utoipa::path(
+        patch,
+        path = "/empires/{empire_id}",
+        tag = "empires",
+        params(("empire_id" = i32, Path, description = "Id of the empire")),
+        request_body(content = Object, content_type = "application/merge-patch+json", description = "Merge patch applied to the UpsertEmpire representation"),
+        responses(
+            (status = 200, description = "Empire updated", body = Empire, headers(("ETag" = String, description = "Version of the empire"))),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Empire not found", body = ErrorBody),
+            (status = 412, description = "If-Match does not match the current version", body = ErrorBody),
+            (status = 422, description = "Patch is not an object, names unknown fields or yields an invalid empire", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while updating empire", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn patch_empire_handler(
         headers: HeaderMap,
         State(shared_state): State<ConnectionPool>,
         path: extract::Path<(i32, )>,
-        Json(upsert_empire): Json<UpsertEmpire>,
+        Json(patch): Json<Value>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
         let (empire_id, ) = path.0;
 
@@ -121,13 +352,42 @@ pub mod router {
                 let connection = shared_state.pool.get()
                     .expect("Failed to acquire connection from pool");
 
-                match empiresTable::new(connection).update(empire_id, upsert_empire) {
-                    Ok(updated_empire) => Ok((StatusCode::OK, Json(updated_empire))),
+                let mut empires = empiresTable::new(connection);
+
+                let existing = match empires.get(empire_id) {
+                    Ok(Some(empire)) => empire,
+                    Ok(None) => return Err((StatusCode::NOT_FOUND, Json(json!({"error": "empire not found"})))),
+                    Err(err) => {
+                        error!("Error reading empire: {:?}", err);
+                        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to update empire"}))));
+                    }
+                };
+
+                let expected_version = check_if_match(&headers, || Ok(Some(existing.version)))?;
+                let current = UpsertEmpire::from(existing.clone());
+
+                // Merge the patch into the current representation, rejecting documents that no longer form a valid empire
+                let merged = apply_merge_patch(&current, &patch)
+                    .and_then(|merged| merged.validate().map(|_| merged))
+                    .map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": err}))))?;
+
+                let result = empires.patch(empire_id, EmpireChangeset::between(&current, &merged), expected_version);
+                drop(empires);
+
+                match result {
+                    Ok(updated_empire) => {
+                        record_event(&shared_state, &headers, AuditAction::Patch, "empire", empire_id, Some(&existing), Some(&updated_empire));
+                        Ok((StatusCode::OK, [(header::ETAG, etag(updated_empire.version))], Json(updated_empire)))
+                    },
+                    Err(diesel::result::Error::NotFound) if expected_version.is_some() => Err(precondition_failed()),
                     Err(diesel::result::Error::NotFound) => {
                         Err((StatusCode::NOT_FOUND, Json(json!({"error": "empire not found"}))))
                     },
+                    Err(diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::ForeignKeyViolation, _)) => {
+                        Err((StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": "Location does not exist"}))))
+                    },
                     Err(err) => {
-                        eprintln!("Error updating empire: {:?}", err);
+                        error!("Error updating empire: {:?}", err);
                         Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to update empire"}))))
                     }
                 }
@@ -136,6 +396,23 @@ pub mod router {
         }
     }
 
+    /// Delete an empire
+    ///
+    /// Requires role ADMIN. The empire is hidden from reads until it is restored, or removed for good by the
+    /// 'purge-deleted' admin command once it is older than the retention period.
+    #[utoipa::path(
+        delete,
+        path = "/empires/{empire_id}",
+        tag = "empires",
+        params(("empire_id" = i32, Path, description = "Id of the empire")),
+        responses(
+            (status = 204, description = "Empire deleted"),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 412, description = "If-Match does not match the current version", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while deleting empire", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
     pub async fn delete_empire_handler(
         headers: HeaderMap,
         State(shared_state): State<ConnectionPool>,
@@ -157,10 +434,26 @@ pub mod router {
                 let connection = shared_state.pool.get()
                     .expect("Failed to acquire connection from pool");
 
-                match empiresTable::new(connection).delete(empire_id) {
-                    Ok(_) => Ok((StatusCode::NO_CONTENT, ())),
+                let mut empires = empiresTable::new(connection);
+
+                let before = empires.get(empire_id).map_err(|err| {
+                    error!("Error reading empire: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to delete empire"})))
+                })?;
+
+                let expected_version = check_if_match(&headers, || Ok(before.as_ref().map(|empire| empire.version)))?;
+
+                let result = empires.delete(empire_id, expected_version);
+                drop(empires);
+
+                match result {
+                    Ok(_) => {
+                        record_event(&shared_state, &headers, AuditAction::Delete, "empire", empire_id, before.as_ref(), None);
+                        Ok((StatusCode::NO_CONTENT, ()))
+                    },
+                    Err(diesel::result::Error::NotFound) if expected_version.is_some() => Err(precondition_failed()),
                     Err(err) => {
-                        eprintln!("Error deleting empire: {:?}", err);
+                        error!("Error deleting empire: {:?}", err);
                         Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to delete empire"}))))
                     }
                 }
@@ -168,4 +461,1089 @@ pub mod router {
             Err(err) => Err(err)
         }
     }
+
+    /// Restore a deleted empire
+    ///
+    /// Requires role ADMIN.
+    #[utoipa::path(
+        post,
+        path = "/empires/{empire_id}/restore",
+        tag = "empires",
+        params(("empire_id" = i32, Path, description = "Id of the deleted empire")),
+        responses(
+            (status = 200, description = "Empire restored", body = Empire, headers(("ETag" = String, description = "Version of the empire"))),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "No deleted empire with this id", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while restoring empire", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn restore_empire_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (empire_id, ) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'ADMIN'
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::ADMIN).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let result = empiresTable::new(connection).restore(empire_id);
+
+                match result {
+                    Ok(restored_empire) => {
+                        record_event(&shared_state, &headers, AuditAction::Restore, "empire", empire_id, None, Some(&restored_empire));
+                        Ok((StatusCode::OK, [(header::ETAG, etag(restored_empire.version))], Json(restored_empire)))
+                    },
+                    Err(diesel::result::Error::NotFound) => {
+                        Err((StatusCode::NOT_FOUND, Json(json!({"error": "No deleted empire with this id"}))))
+                    },
+                    Err(err) => {
+                        error!("Error restoring empire: {:?}", err);
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to restore empire"}))))
+                    }
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// List the territory of an empire
+    ///
+    /// Requires role READER or higher. Locations held by the empire in the order they were claimed. '?fields=' limits the fields of each location.
+    #[utoipa::path(
+        get,
+        path = "/empires/{empire_id}/territory",
+        tag = "empires",
+        params(("empire_id" = i32, Path, description = "Id of the empire"), PageQuery, FieldsQuery),
+        responses(
+            (status = 200, description = "Page of locations held by the empire", body = LocationPage),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Empire not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing territory", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_territory_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+        query: Result<Query<PageQuery>, QueryRejection>,
+        fields: Result<Query<FieldsQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (empire_id, ) = path.0;
+        let Query(query) = query.map_err(invalid_query)?;
+        let page = query.page()?;
+        let fields = fields.map_err(invalid_query)?.parse(&Location::FIELDS)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let listing_failed = |err: diesel::result::Error| {
+                    error!("Error listing territory: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list territory"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut empires = empiresTable::new(connection);
+
+                if empires.get(empire_id).map_err(listing_failed)?.is_none() {
+                    return Err(empire_not_found());
+                }
+
+                let territory = empires.territory(empire_id, page, &fields).map_err(listing_failed)?;
+                let items = territory.iter().map(|location| fields.project(location, &[])).collect();
+                Ok((StatusCode::OK, Json(Paginated::new(items, page))))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// List the ships of an empire
+    ///
+    /// Requires role READER or higher. Ordered by id. '?fields=' limits the fields of each ship.
+    #[utoipa::path(
+        get,
+        path = "/empires/{empire_id}/ships",
+        tag = "empires",
+        params(("empire_id" = i32, Path, description = "Id of the empire"), PageQuery, FieldsQuery),
+        responses(
+            (status = 200, description = "Page of ships", body = ShipPage),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Empire not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing ships", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_empire_ships_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+        query: Result<Query<PageQuery>, QueryRejection>,
+        fields: Result<Query<FieldsQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (empire_id, ) = path.0;
+        let Query(query) = query.map_err(invalid_query)?;
+        let page = query.page()?;
+        let fields = fields.map_err(invalid_query)?.parse(&Ship::FIELDS)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let listing_failed = |err: diesel::result::Error| {
+                    error!("Error listing empire ships: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list ships"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                if empiresTable::new(connection).get(empire_id).map_err(listing_failed)?.is_none() {
+                    return Err(empire_not_found());
+                }
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let ships = ShipsTable::new(connection).list_by_empire(empire_id, page, &fields).map_err(listing_failed)?;
+                let items = ships.iter().map(|ship| fields.project(ship, &[])).collect();
+                Ok((StatusCode::OK, Json(Paginated::new(items, page))))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// List the players of an empire
+    ///
+    /// Requires role READER or higher. Players flying a ship of the empire, ordered by id. '?include=' embeds any of 'ship' and 'location',
+    /// '?fields=' limits the fields of each player.
+    #[utoipa::path(
+        get,
+        path = "/empires/{empire_id}/players",
+        tag = "empires",
+        params(("empire_id" = i32, Path, description = "Id of the empire"), PageQuery, IncludeQuery, FieldsQuery),
+        responses(
+            (status = 200, description = "Page of players", body = PlayerDocumentPage),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Empire not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing players", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_empire_players_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+        query: Result<Query<PageQuery>, QueryRejection>,
+        include: Result<Query<IncludeQuery>, QueryRejection>,
+        fields: Result<Query<FieldsQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (empire_id, ) = path.0;
+        let Query(query) = query.map_err(invalid_query)?;
+        let page = query.page()?;
+        let included = include.map_err(invalid_query)?.parse(&PlayerDocument::INCLUDES)?;
+        let fields = fields.map_err(invalid_query)?.parse(&Player::FIELDS)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let listing_failed = |err: diesel::result::Error| {
+                    error!("Error listing empire players: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list players"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                if empiresTable::new(connection).get(empire_id).map_err(listing_failed)?.is_none() {
+                    return Err(empire_not_found());
+                }
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let items = PlayersTable::new(connection).list_by_empire(empire_id, page).map_err(listing_failed)?;
+                let documents = player_documents(&shared_state, items, &included).map_err(listing_failed)?;
+                let items = documents.iter().map(|document| fields.project(document, &included)).collect();
+                Ok((StatusCode::OK, Json(Paginated::new(items, page))))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Claim a location for an empire
+    ///
+    /// Requires role EDITOR or higher, or ownership of the empire. The empire becomes the sovereign of the location,
+    /// which fails with 409 while another empire holds it. Claiming a location the empire already holds returns its claim.
+    #[utoipa::path(
+        post,
+        path = "/empires/{empire_id}/territory/{location_id}",
+        tag = "empires",
+        params(
+            ("empire_id" = i32, Path, description = "Id of the claiming empire"),
+            ("location_id" = i32, Path, description = "Id of the claimed location")
+        ),
+        responses(
+            (status = 200, description = "The empire already holds the location", body = TerritoryClaim),
+            (status = 201, description = "Location claimed", body = TerritoryClaim),
+            (status = 401, description = "Missing or insufficient role and not the owner of the empire", body = ErrorBody),
+            (status = 404, description = "Empire or location not found", body = ErrorBody),
+            (status = 409, description = "Another empire holds the location", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while claiming location", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn claim_location_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, i32)>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (empire_id, location_id) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists, whether it may manage the territory depends on the empire
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(authorized_user) => {
+                let claim_failed = |err: diesel::result::Error| {
+                    error!("Error claiming location: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to claim location"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut empires = empiresTable::new(connection);
+
+                let empire = empires.get(empire_id).map_err(claim_failed)?.ok_or_else(empire_not_found)?;
+                authorize_territory_management(authorized_user.as_ref(), &empire)?;
+
+                let outcome = empires.claim(empire_id, location_id);
+                drop(empires);
+
+                match outcome {
+                    Ok(ClaimOutcome::Claimed(claim)) => {
+                        record_event(&shared_state, &headers, AuditAction::Create, "territory_claim", claim.id, None, Some(&claim));
+                        Ok((StatusCode::CREATED, Json(claim)))
+                    },
+                    Ok(ClaimOutcome::AlreadyHeld(claim)) => Ok((StatusCode::OK, Json(claim))),
+                    Ok(ClaimOutcome::HeldBy(holder_id)) => {
+                        Err((StatusCode::CONFLICT, Json(json!({"error": format!("Location is held by empire {}", holder_id)}))))
+                    },
+                    Err(diesel::result::Error::NotFound) => Err((StatusCode::NOT_FOUND, Json(json!({"error": "Location not found"})))),
+                    // A concurrent claim took the location between the lock and the insert of the claim
+                    Err(diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::UniqueViolation, _)) => {
+                        Err((StatusCode::CONFLICT, Json(json!({"error": "Location is held by another empire"}))))
+                    },
+                    Err(err) => Err(claim_failed(err)),
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Release a location held by an empire
+    ///
+    /// Requires role EDITOR or higher, or ownership of the empire. The location is left without a sovereign.
+    #[utoipa::path(
+        delete,
+        path = "/empires/{empire_id}/territory/{location_id}",
+        tag = "empires",
+        params(
+            ("empire_id" = i32, Path, description = "Id of the releasing empire"),
+            ("location_id" = i32, Path, description = "Id of the released location")
+        ),
+        responses(
+            (status = 204, description = "Location released"),
+            (status = 401, description = "Missing or insufficient role and not the owner of the empire", body = ErrorBody),
+            (status = 404, description = "Empire not found or not holding the location", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while releasing location", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn release_location_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, i32)>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (empire_id, location_id) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists, whether it may manage the territory depends on the empire
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(authorized_user) => {
+                let release_failed = |err: diesel::result::Error| {
+                    error!("Error releasing location: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to release location"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut empires = empiresTable::new(connection);
+
+                let empire = empires.get(empire_id).map_err(release_failed)?.ok_or_else(empire_not_found)?;
+                authorize_territory_management(authorized_user.as_ref(), &empire)?;
+
+                let outcome = empires.release(empire_id, location_id);
+                drop(empires);
+
+                match outcome {
+                    Ok(released_claim) => {
+                        record_event(&shared_state, &headers, AuditAction::Delete, "territory_claim", released_claim.id, Some(&released_claim), None);
+                        Ok((StatusCode::NO_CONTENT, ()))
+                    },
+                    Err(diesel::result::Error::NotFound) => Err((StatusCode::NOT_FOUND, Json(json!({"error": "Empire does not hold the location"})))),
+                    Err(err) => Err(release_failed(err)),
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Create empires in bulk
+    ///
+    /// Requires role WRITER or higher. In transactional mode the first failing item rolls back every item and is answered with its status and index.
+    #[utoipa::path(
+        post,
+        path = "/empires/bulk",
+        tag = "empires",
+        params(BulkQuery),
+        request_body = Vec<UpsertEmpire>,
+        responses(
+            (status = 200, description = "Every empire created", body = BulkResponse),
+            (status = 207, description = "Outcome of each empire in partial mode", body = BulkResponse),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 413, description = "Too many items", body = ErrorBody),
+            (status = 422, description = "An empire is invalid or its location does not exist, all items rolled back", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while creating empires", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn bulk_create_empires_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<BulkQuery>, QueryRejection>,
+        Json(upsert_empires): Json<Vec<UpsertEmpire>>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        check_bulk_size(upsert_empires.len())?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'WRITER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::WRITER).await;
+
+        match authorization {
+            Ok(authorized_user) => {
+                let (upsert_empires, rejected) = validate_all(upsert_empires, query.mode(), UpsertEmpire::validate)?;
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let owner_id = authorized_user.map(|user| user.id);
+                let mut empires = empiresTable::new(connection);
+                let result = apply_all(&mut empires, upsert_empires, query.mode(), |empires, upsert_empire| empires.create(upsert_empire, owner_id));
+                drop(empires);
+
+                let outcomes = result.map_err(|err| bulk_failure(err, bulk_error))?;
+
+                for new_empire in outcomes.iter().flatten() {
+                    record_event(&shared_state, &headers, AuditAction::Create, "empire", new_empire.id, None, Some(new_empire));
+                }
+
+                let outcomes = outcomes.into_iter()
+                    .map(|outcome| outcome.map(|new_empire| new_empire.id).map_err(|err| bulk_error(&err)))
+                    .collect();
+
+                Ok(bulk_response(query.mode(), StatusCode::CREATED, with_rejected(outcomes, rejected)))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Replace empires in bulk
+    ///
+    /// Requires role EDITOR or higher. Each item holds the id of the empire next to its new representation.
+    #[utoipa::path(
+        put,
+        path = "/empires/bulk",
+        tag = "empires",
+        params(BulkQuery),
+        request_body = Vec<EmpireUpdate>,
+        responses(
+            (status = 200, description = "Every empire updated", body = BulkResponse),
+            (status = 207, description = "Outcome of each empire in partial mode", body = BulkResponse),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Empire not found, all items rolled back", body = ErrorBody),
+            (status = 413, description = "Too many items", body = ErrorBody),
+            (status = 422, description = "An empire is invalid or its location does not exist, all items rolled back", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while updating empires", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn bulk_update_empires_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<BulkQuery>, QueryRejection>,
+        Json(empire_updates): Json<Vec<BulkUpdate<UpsertEmpire>>>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        check_bulk_size(empire_updates.len())?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'EDITOR' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::EDITOR).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let (empire_updates, rejected) = validate_all(empire_updates, query.mode(), |update: &BulkUpdate<UpsertEmpire>| update.body.validate())?;
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut empires = empiresTable::new(connection);
+                let result = apply_all(&mut empires, empire_updates, query.mode(), |empires, BulkUpdate { id, body }| {
+                    let before = empires.get(id)?.ok_or(diesel::result::Error::NotFound)?;
+                    empires.update(id, body, None).map(|after| (before, after))
+                });
+                drop(empires);
+
+                let outcomes = result.map_err(|err| bulk_failure(err, bulk_error))?;
+
+                for (before, after) in outcomes.iter().flatten() {
+                    record_event(&shared_state, &headers, AuditAction::Update, "empire", after.id, Some(before), Some(after));
+                }
+
+                let outcomes = outcomes.into_iter()
+                    .map(|outcome| outcome.map(|(_, updated_empire)| updated_empire.id).map_err(|err| bulk_error(&err)))
+                    .collect();
+
+                Ok(bulk_response(query.mode(), StatusCode::OK, with_rejected(outcomes, rejected)))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Delete empires in bulk
+    ///
+    /// Requires role ADMIN. The body is the array of ids to delete.
+    #[utoipa::path(
+        delete,
+        path = "/empires/bulk",
+        tag = "empires",
+        params(BulkQuery),
+        request_body = Vec<i32>,
+        responses(
+            (status = 200, description = "Every empire deleted", body = BulkResponse),
+            (status = 207, description = "Outcome of each empire in partial mode", body = BulkResponse),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Empire not found, all items rolled back", body = ErrorBody),
+            (status = 413, description = "Too many items", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while deleting empires", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn bulk_delete_empires_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<BulkQuery>, QueryRejection>,
+        Json(empire_ids): Json<Vec<i32>>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        check_bulk_size(empire_ids.len())?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'ADMIN'
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::ADMIN).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut empires = empiresTable::new(connection);
+                let result = apply_all(&mut empires, empire_ids, query.mode(), |empires, empire_id| {
+                    let before = empires.get(empire_id)?.ok_or(diesel::result::Error::NotFound)?;
+                    empires.delete(empire_id, None).map(|_| before)
+                });
+                drop(empires);
+
+                let outcomes = result.map_err(|err| bulk_failure(err, bulk_error))?;
+
+                for deleted_empire in outcomes.iter().flatten() {
+                    record_event(&shared_state, &headers, AuditAction::Delete, "empire", deleted_empire.id, Some(deleted_empire), None);
+                }
+
+                let outcomes = outcomes.into_iter()
+                    .map(|outcome| outcome.map(|deleted_empire| deleted_empire.id).map_err(|err| bulk_error(&err)))
+                    .collect();
+
+                Ok(bulk_response(query.mode(), StatusCode::NO_CONTENT, outcomes))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Export empires
+    ///
+    /// Requires role READER or higher. Streams every empire in id order as CSV or NDJSON, chosen with '?format=' or else the Accept header.
+    #[utoipa::path(
+        get,
+        path = "/empires/export",
+        tag = "empires",
+        params(ExportQuery),
+        responses(
+            (status = 200, description = "Every empire", content(("text/csv" = String), ("application/x-ndjson" = String))),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 406, description = "Neither CSV nor NDJSON is acceptable", body = ErrorBody),
+            (status = 500, description = "Missing header", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn export_empires_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<ExportQuery>, QueryRejection>,
+    ) -> Result<Response, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        let format = export_format(query.format, &headers)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut empires = empiresTable::new(connection);
+                Ok(export(format, move |after_id, limit| empires.list_after(after_id, limit), |empire: &Empire| empire.id))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Import empires
+    ///
+    /// Requires role WRITER or higher. Creates a empire from every CSV record or NDJSON line, chosen with '?format=' or else the Content-Type header.
+    /// Every row is checked against the empire model and the location it refers to before anything is written, and '?dry_run=true' stops after the check.
+    #[utoipa::path(
+        post,
+        path = "/empires/import",
+        tag = "empires",
+        params(ImportQuery),
+        request_body(content = String, description = "CSV with a header row naming the columns, or one JSON object per line", content_type = "text/csv"),
+        responses(
+            (status = 200, description = "Every row is valid, nothing written in dry-run mode", body = ImportReport),
+            (status = 201, description = "Every row imported", body = ImportReport),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 415, description = "Neither CSV nor NDJSON", body = ErrorBody),
+            (status = 422, description = "Invalid rows, nothing written", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while importing empires", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn import_empires_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<ImportQuery>, QueryRejection>,
+        body: Bytes,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        let format = import_format(query.format, &headers)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'WRITER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::WRITER).await;
+
+        match authorization {
+            Ok(authorized_user) => {
+                let rows = parse_rows::<UpsertEmpire>(format, &body).map_err(invalid_rows)?;
+
+                // Empires may only refer to locations that exist and have not been deleted
+                let location_ids: Vec<i32> = rows.iter().map(|(_, empire)| empire.location_id).collect();
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+                let known_location_ids: HashSet<i32> = match LocationsTable::new(connection).existing_ids(&location_ids) {
+                    Ok(ids) => ids.into_iter().collect(),
+                    Err(err) => {
+                        error!("Error validating empires import: {:?}", err);
+                        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to import empires"}))));
+                    }
+                };
+
+                let invalid: Vec<RowError> = rows.iter()
+                    .filter_map(|(row, empire)| {
+                        empire.validate().err()
+                            .or_else(|| (!known_location_ids.contains(&empire.location_id)).then(|| format!("Location {} does not exist", empire.location_id)))
+                            .map(|error| RowError { row: *row, error })
+                    })
+                    .collect();
+
+                if !invalid.is_empty() {
+                    return Err(invalid_rows(invalid));
+                }
+                if query.dry_run {
+                    return Ok((StatusCode::OK, Json(ImportReport { rows: rows.len(), dry_run: true, ids: Vec::new() })));
+                }
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut empires = empiresTable::new(connection);
+                let upsert_empires = rows.into_iter().map(|(_, empire)| empire).collect();
+                let owner_id = authorized_user.map(|user| user.id);
+                let result = apply_all(&mut empires, upsert_empires, BulkMode::Transactional, |empires, upsert_empire| empires.create(upsert_empire, owner_id));
+                drop(empires);
+
+                let created_empires: Vec<Empire> = result.map_err(|err| bulk_failure(err, bulk_error))?.into_iter().flatten().collect();
+
+                for new_empire in &created_empires {
+                    record_event(&shared_state, &headers, AuditAction::Create, "empire", new_empire.id, None, Some(new_empire));
+                }
+
+                let ids = created_empires.iter().map(|new_empire| new_empire.id).collect();
+                Ok((StatusCode::CREATED, Json(ImportReport { rows: created_empires.len(), dry_run: false, ids })))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    fn empire_not_found() -> (StatusCode, Json<Value>) {
+        (StatusCode::NOT_FOUND, Json(json!({"error": "Empire not found"})))
+    }
+
+    // Territory is managed by an EDITOR or by the owner of the empire
+    fn authorize_territory_management(user: Option<&User>, empire: &Empire) -> Result<(), (StatusCode, Json<Value>)> {
+        let authorized = user.is_some_and(|user| {
+            has_role(&string_to_user_role(user.role.clone()), &UserRole::EDITOR) || empire.owner_id == Some(user.id)
+        });
+
+        if authorized {
+            Ok(())
+        } else {
+            Err((StatusCode::UNAUTHORIZED, Json(json!({"error": "Only an EDITOR or the owner of the empire may manage its territory"}))))
+        }
+    }
+
+    // Status and message of an item that failed in a bulk request
+    fn bulk_error(err: &diesel::result::Error) -> (StatusCode, String) {
+        match err {
+            diesel::result::Error::NotFound => (StatusCode::NOT_FOUND, "Empire not found".to_string()),
+            diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::ForeignKeyViolation, _) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "Location does not exist".to_string())
+            },
+            err => {
+                error!("Error applying bulk empire item: {:?}", err);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Failed to apply empire".to_string())
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use axum::{
+            body::Body,
+            http::{Request, StatusCode}
+        };
+        use serde_json::{json, Value};
+        use tower::ServiceExt;
+        use crate::{
+            audit::{model::AuditQuery, service::service::AuditTable},
+            common::{
+                db::{create_shared_connection_pool, ConnectionPool},
+                pagination::Page,
+                security::{generate_token, hash_password},
+                util::load_environment_variable
+            },
+            empires::{model::{Empire, UpsertEmpire}, router::router::empires_route, service::service::EmpiresTable},
+            locations::{
+                model::{Location, UpsertLocation},
+                router::router::locations_route,
+                service::service::LocationsTable
+            },
+            players::{model::UpsertPlayer, service::service::PlayersTable},
+            ships::{model::{Ship, UpsertShip}, service::service::ShipsTable},
+            users::{
+                model::{UpsertUser, UserRole},
+                service::service::UsersTable
+            }
+        };
+
+        fn create_user_and_generate_token(connection_pool: ConnectionPool, email: &str, user_role: UserRole) -> String {
+            let mut new_user = UpsertUser {
+                email: email.to_string(),
+                role: user_role.to_string(),
+                password: "KrevIkkeMerEnnDuKanHolde7".to_string(),
+                fullname: "Keiser Kristiansen".to_string()
+            };
+            hash_password(&mut new_user).expect("Hash failed");
+
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let created_user = UsersTable::new(connection).create(new_user).expect("Create user failed");
+            generate_token(&created_user).expect("Generate token failed")
+        }
+
+        // Home location, empire and frigates of a test of its own, as db/test/reset.sh removes the seeded rows
+        fn create_empire_with_ships(connection_pool: &ConnectionPool, name: &str, ship_names: &[&str]) -> (Location, Empire, Vec<Ship>) {
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let location = LocationsTable::new(connection).create(UpsertLocation {
+                star_system: "Heimatar".to_string(),
+                area: name.to_string(),
+                ..UpsertLocation::default()
+            }).expect("Create location failed");
+
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let empire = EmpiresTable::new(connection).create(UpsertEmpire {
+                name: name.to_string(),
+                slogan: "Strength in the clan".to_string(),
+                location_id: location.id,
+                description: "A tribe of the Minmatar Republic".to_string(),
+            }, None).expect("Create empire failed");
+
+            let mut ships = ShipsTable::new(connection_pool.pool.get().expect("Failed to get connection"));
+            let ships = ship_names.iter().map(|ship_name| ships.create(UpsertShip {
+                name: ship_name.to_string(),
+                category: Some("Frigate".to_string()),
+                description: None,
+                empire_id: empire.id,
+            }).expect("Create ship failed")).collect();
+
+            (location, empire, ships)
+        }
+
+        fn request(method: &str, uri: String, bearer_token: &str, body: Body) -> Request<Body> {
+            Request::builder()
+                .uri(uri)
+                .method(method)
+                .header("content-type", "application/json")
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .body(body)
+                .unwrap()
+        }
+
+        async fn send(connection_pool: ConnectionPool, request: Request<Body>) -> (StatusCode, Value) {
+            let service = empires_route(connection_pool.clone()).merge(locations_route(connection_pool));
+            let response = service.oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+        }
+
+        #[tokio::test]
+        async fn territory_is_claimed_by_owner_or_editor_and_held_by_one_empire_at_a_time() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+
+            let owner_token = create_user_and_generate_token(connection_pool.clone(), "landnam@succulentmail.gb", UserRole::WRITER);
+            let writer_token = create_user_and_generate_token(connection_pool.clone(), "nabo@succulentmail.gb", UserRole::WRITER);
+            let editor_token = create_user_and_generate_token(connection_pool.clone(), "oppmaaler@succulentmail.gb", UserRole::EDITOR);
+
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let location = LocationsTable::new(connection).create(UpsertLocation {
+                star_system: "Curse".to_string(),
+                area: "G-0Q86".to_string(),
+                ..UpsertLocation::default()
+            }).expect("Create location failed");
+
+            let (_, rival, _) = create_empire_with_ships(&connection_pool, "Guristas", &[]);
+
+            let new_empire = json!({"name": "Angel Cartel", "slogan": "Fortune favours the bold", "location_id": location.id, "description": "Pirates of Curse"});
+            let (status, empire) = send(connection_pool.clone(), request("POST", "/empires".to_string(), &owner_token, Body::from(new_empire.to_string()))).await;
+            assert_eq!(status, StatusCode::CREATED);
+            let empire_id = empire["id"].as_i64().unwrap();
+            assert!(empire["owner_id"].is_i64());
+
+            let territory = format!("/empires/{}/territory/{}", empire_id, location.id);
+
+            // Only the owner or an EDITOR manages the territory of the empire
+            let (status, _) = send(connection_pool.clone(), request("POST", territory.clone(), &writer_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+            let (status, claim) = send(connection_pool.clone(), request("POST", territory.clone(), &owner_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::CREATED);
+            assert_eq!(claim["released_at"], Value::Null);
+
+            let (status, _) = send(connection_pool.clone(), request("POST", territory.clone(), &owner_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::OK);
+
+            // A location is held by one empire at a time
+            let rival_territory = format!("/empires/{}/territory/{}", rival.id, location.id);
+            let (status, _) = send(connection_pool.clone(), request("POST", rival_territory.clone(), &editor_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::CONFLICT);
+
+            let (status, page) = send(connection_pool.clone(), request("GET", format!("/empires/{}/territory", empire_id), &writer_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(page["items"].as_array().unwrap().iter().map(|location| location["id"].as_i64().unwrap()).collect::<Vec<_>>(), vec![location.id as i64]);
+
+            let (status, _) = send(connection_pool.clone(), request("DELETE", territory.clone(), &owner_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::NO_CONTENT);
+            let (status, _) = send(connection_pool.clone(), request("DELETE", territory, &owner_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+
+            let (status, _) = send(connection_pool.clone(), request("POST", rival_territory, &editor_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::CREATED);
+
+            // The released claim is kept next to the live one
+            let (status, page) = send(connection_pool.clone(), request("GET", format!("/locations/{}/claims", location.id), &writer_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::OK);
+            let claims: Vec<(i64, bool)> = page["items"].as_array().unwrap().iter()
+                .map(|claim| (claim["empire_id"].as_i64().unwrap(), claim["released_at"].is_null()))
+                .collect();
+            assert_eq!(claims, vec![(rival.id as i64, true), (empire_id, false)]);
+        }
+    
+
+        #[tokio::test]
+        async fn claims_change_the_version_of_the_location_and_are_audited() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+
+            let editor_token = create_user_and_generate_token(connection_pool.clone(), "grensevakt@succulentmail.gb", UserRole::EDITOR);
+
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let location = LocationsTable::new(connection).create(UpsertLocation {
+                star_system: "Curse".to_string(),
+                area: "Jorund".to_string(),
+                ..UpsertLocation::default()
+            }).expect("Create location failed");
+            let (_, empire, _) = create_empire_with_ships(&connection_pool, "Curse Kartellet", &[]);
+
+            let territory = format!("/empires/{}/territory/{}", empire.id, location.id);
+            let (status, claim) = send(connection_pool.clone(), request("POST", territory.clone(), &editor_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::CREATED);
+
+            // A client holding the location as read before the claim is told that its copy is stale
+            let stale_update = Request::builder()
+                .uri(format!("/locations/{}", location.id))
+                .method("PUT")
+                .header("content-type", "application/json")
+                .header("Authorization", format!("Bearer {}", editor_token))
+                .header("If-Match", format!("\"{}\"", location.version))
+                .body(Body::from(json!({"star_system": "Curse", "area": "Jorund"}).to_string()))
+                .unwrap();
+            let (status, _) = send(connection_pool.clone(), stale_update).await;
+            assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+
+            let (status, _) = send(connection_pool.clone(), request("DELETE", territory, &editor_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::NO_CONTENT);
+
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            assert_eq!(LocationsTable::new(connection).get(location.id).unwrap().unwrap().version, location.version + 2);
+
+            let filter = AuditQuery {
+                resource_type: Some("territory_claim".to_string()),
+                resource_id: Some(claim["id"].as_i64().unwrap() as i32),
+                ..AuditQuery::default()
+            };
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let events = AuditTable::new(connection).list(&filter, Page::new(None, None).unwrap()).expect("List audit events failed");
+            let actions: Vec<&str> = events.iter().map(|event| event.action.as_str()).collect();
+            assert_eq!(actions, vec!["delete", "create"]);
+        }
+
+        #[tokio::test]
+        async fn nested_lists_return_ships_players_and_empires_of_their_parent() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+
+            let bearer_token = create_user_and_generate_token(connection_pool.clone(), "folketeller@succulentmail.gb", UserRole::READER);
+
+            let (location, empire, ships) = create_empire_with_ships(&connection_pool, "Brutor Tribe", &["Rifter", "Slasher", "Breacher", "Probe", "Burst", "Vigil"]);
+
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let user = UsersTable::new(connection).get_by_email("folketeller@succulentmail.gb".to_string()).unwrap().unwrap();
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let player = PlayersTable::new(connection).create(UpsertPlayer { user_id: user.id, active_ship_id: ships[0].id, location_id: location.id })
+                .expect("Create player failed");
+
+            let ids = |page: &Value| page["items"].as_array().unwrap().iter().map(|item| item["id"].as_i64().unwrap()).collect::<Vec<_>>();
+
+            let (status, page) = send(connection_pool.clone(), request("GET", format!("/empires/{}/ships?limit=5", empire.id), &bearer_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(ids(&page), ships[..5].iter().map(|ship| ship.id as i64).collect::<Vec<_>>());
+            assert_eq!(page["limit"], 5);
+
+            let (status, page) = send(connection_pool.clone(), request("GET", format!("/empires/{}/players", empire.id), &bearer_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(ids(&page), vec![player.id as i64]);
+
+            let (status, page) = send(connection_pool.clone(), request("GET", format!("/locations/{}/players", location.id), &bearer_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(ids(&page), vec![player.id as i64]);
+
+            let (status, page) = send(connection_pool.clone(), request("GET", format!("/locations/{}/empires", location.id), &bearer_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(ids(&page), vec![empire.id as i64]);
+
+            let (status, _) = send(connection_pool.clone(), request("GET", "/empires/999999/ships".to_string(), &bearer_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+            let (status, _) = send(connection_pool, request("GET", "/locations/999999/players".to_string(), &bearer_token, Body::empty())).await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+        }
+    
+
+        #[tokio::test]
+        async fn include_embeds_related_rows_on_empire_location_and_player_reads() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+
+            let bearer_token = create_user_and_generate_token(connection_pool.clone(), "samlemappe@succulentmail.gb", UserRole::READER);
+            let get = |uri: &str| request("GET", uri.to_string(), &bearer_token, Body::empty());
+            let ids = |items: &Value| items.as_array().unwrap().iter().map(|item| item["id"].as_i64().unwrap()).collect::<Vec<_>>();
+            let (location, created_empire, ships) = create_empire_with_ships(&connection_pool, "Vherokior Tribe", &["Probe", "Breacher", "Vigil"]);
+
+            let (status, empire) = send(connection_pool.clone(), get(&format!("/empires/{}?include=location,ships", created_empire.id))).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(empire["location"]["area"], "Vherokior Tribe");
+            assert_eq!(ids(&empire["ships"]), ships.iter().map(|ship| ship.id as i64).collect::<Vec<_>>());
+            assert!(empire.get("players").is_none());
+
+            // Without includes the representation is unchanged
+            let (_, empire) = send(connection_pool.clone(), get(&format!("/empires/{}", created_empire.id))).await;
+            assert!(empire.get("location").is_none() && empire.get("ships").is_none());
+
+            let (status, page) = send(connection_pool.clone(), get("/empires?include=location&limit=500")).await;
+            assert_eq!(status, StatusCode::OK);
+            assert!(page["items"].as_array().unwrap().iter().all(|empire| empire["location"].is_null() || empire["location"]["id"] == empire["location_id"]));
+
+            let (status, embedding) = send(connection_pool.clone(), get(&format!("/locations/{}?include=empires", location.id))).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(ids(&embedding["empires"]), [created_empire.id as i64]);
+
+            let (status, error) = send(connection_pool.clone(), get(&format!("/empires/{}?include=crew", created_empire.id))).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(error["error"], "Unknown include 'crew', expected any of: location, ships, players");
+
+            // Players embed their ship and location
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let user = UsersTable::new(connection).get_by_email("samlemappe@succulentmail.gb".to_string()).unwrap().unwrap();
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let player = PlayersTable::new(connection).create(UpsertPlayer { user_id: user.id, active_ship_id: ships[0].id, location_id: location.id })
+                .expect("Create player failed");
+
+            let (status, page) = send(connection_pool.clone(), get(&format!("/locations/{}/players?include=ship,location", location.id))).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(page["items"][0]["id"], player.id);
+            assert_eq!(page["items"][0]["ship"]["name"], "Probe");
+            assert_eq!(page["items"][0]["location"]["area"], "Vherokior Tribe");
+        }
+
+        #[tokio::test]
+        async fn fields_limit_reads_and_lists_to_the_selected_fields() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+
+            let bearer_token = create_user_and_generate_token(connection_pool.clone(), "stikkelsbaer@succulentmail.gb", UserRole::READER);
+            let get = |uri: &str| request("GET", uri.to_string(), &bearer_token, Body::empty());
+            let (location, created_empire, _) = create_empire_with_ships(&connection_pool, "Krusual Tribe", &["Slasher", "Rifter"]);
+
+            let (status, empire) = send(connection_pool.clone(), get(&format!("/empires/{}?fields=id,name", created_empire.id))).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(empire, json!({"id": created_empire.id, "name": "Krusual Tribe"}));
+
+            // Included relations are kept, and the home location is found although 'location_id' is not returned
+            let (_, empire) = send(connection_pool.clone(), get(&format!("/empires/{}?fields=name&include=location", created_empire.id))).await;
+            assert_eq!(empire.as_object().unwrap().keys().collect::<Vec<_>>(), ["location", "name"]);
+            assert_eq!(empire["location"]["area"], "Krusual Tribe");
+
+            let (status, page) = send(connection_pool.clone(), get(&format!("/empires/{}/ships?fields=name", created_empire.id))).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(page["items"], json!([{"name": "Slasher"}, {"name": "Rifter"}]));
+
+            let (_, fields) = send(connection_pool.clone(), get(&format!("/locations/{}?fields=area,security_status", location.id))).await;
+            assert_eq!(fields, json!({"area": "Krusual Tribe", "security_status": location.security_status}));
+
+            let (_, page) = send(connection_pool.clone(), get("/locations?fields=id&limit=500")).await;
+            assert!(page["items"].as_array().unwrap().iter().all(|location| location.as_object().unwrap().len() == 1));
+
+            let (status, error) = send(connection_pool.clone(), get(&format!("/empires/{}?fields=id,motto", created_empire.id))).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert!(error["error"].as_str().unwrap().starts_with("Unknown field 'motto'"));
+        }
+    }
 }
@@ -4,8 +4,14 @@ pub mod service {
         PgConnection,
         r2d2::{ConnectionManager, PooledConnection},
     };
+    use std::collections::HashMap;
+    use chrono::{DateTime, Utc};
     use crate::{
-        empires::model::{Empire, UpsertEmpire},
+        common::{bulk::Transactional, db::ConnectionPool, fields::FieldSet, include::group_by, pagination::Page},
+        empires::model::{ClaimOutcome, Empire, EmpireChangeset, EmpireDocument, TerritoryClaim, UpsertEmpire},
+        locations::{model::Location, service::service::LocationsTable},
+        players::service::service::PlayersTable,
+        ships::service::service::ShipsTable,
         schema
     };
 
@@ -15,12 +21,24 @@ pub mod service {
         connection: PooledPg,
     }
 
+    impl Transactional for EmpiresTable {
+        fn connection(&mut self) -> &mut PgConnection {
+            &mut self.connection
+        }
+    }
+
     impl EmpiresTable {
         pub fn new(connection: PooledPg) -> EmpiresTable {
             EmpiresTable { connection }
         }
 
-        pub fn create(&mut self, upsert_empire: UpsertEmpire) -> Result<Empire, diesel::result::Error> {
+        // The owner is the user creating the empire, None when it is seeded
+        pub fn create(&mut self, upsert_empire: UpsertEmpire, owner_id: Option<i32>) -> Result<Empire, diesel::result::Error> {
+            Self::insert(&mut self.connection, &upsert_empire, owner_id)
+        }
+
+        // Takes the connection so that the insert can share a transaction with writes to other tables, as when fixtures are seeded
+        pub fn insert(connection: &mut PgConnection, upsert_empire: &UpsertEmpire, owner_id: Option<i32>) -> Result<Empire, diesel::result::Error> {
             use schema::empires;
 
             let new_empire = diesel::insert_into(empires::table)
@@ -28,71 +46,309 @@ pub mod service {
                     empires::name.eq(&upsert_empire.name),
                     empires::slogan.eq(&upsert_empire.slogan),
                     empires::location_id.eq(&upsert_empire.location_id),
-                    empires::description.eq(&upsert_empire.description)
+                    empires::description.eq(&upsert_empire.description),
+                    empires::owner_id.eq(owner_id)
                 ))
-                .get_result(&mut self.connection)
-                .expect("Create empire failed");
+                .get_result(connection)?;
 
             Ok(new_empire)
         }
 
         pub fn get(&mut self, empire_id: i32) -> Result<Option<Empire>, diesel::result::Error> {
+            self.read(empire_id, &FieldSet::all())
+        }
+
+        // The empire with only the columns of the fieldset read
+        pub fn read(&mut self, empire_id: i32, fields: &FieldSet) -> Result<Option<Empire>, diesel::result::Error> {
             use schema::empires;
 
             let empire = empires::table
                 .find(empire_id)
+                .filter(empires::deleted_at.is_null())
+                .select(Empire::columns(fields))
                 .get_result(&mut self.connection)
                 .optional()?;
 
             Ok(empire)
         }
 
-        pub fn update(&mut self, empire_id: i32, upsert_empire: UpsertEmpire,
-        ) -> Result<Empire, diesel::result::Error> {
+        // Rows with an id greater than 'after_id' in id order, the keyset pagination used by exports
+        pub fn list_after(&mut self, after_id: i32, limit: i64) -> Result<Vec<Empire>, diesel::result::Error> {
             use schema::empires;
 
-            // Check if the empire exists before attempting to update
-            let existing_empire = empires::table
-                .find(empire_id)
-                .get_result::<Empire>(&mut self.connection);
-
-            match existing_empire {
-                Ok(_) => {
-                    let updated_empire = diesel::update(empires::table.find(empire_id))
-                        .set((
-                            empires::name.eq(&upsert_empire.name),
-                            empires::slogan.eq(&upsert_empire.slogan),
-                            empires::location_id.eq(upsert_empire.location_id),
-                            empires::description.eq(&upsert_empire.description)
-                        ))
-                        .get_result(&mut self.connection)
-                        .expect("Update empire failed");
-
-                    Ok(updated_empire)
-                }
-                Err(_) => Err(diesel::result::Error::NotFound),
+            empires::table
+                .filter(empires::id.gt(after_id)).filter(empires::deleted_at.is_null())
+                .order(empires::id.asc())
+                .limit(limit)
+                .load(&mut self.connection)
+        }
+
+        // The subset of 'ids' referring to empires that exist and are not deleted
+        pub fn existing_ids(&mut self, ids: &[i32]) -> Result<Vec<i32>, diesel::result::Error> {
+            use schema::empires;
+
+            empires::table
+                .filter(empires::id.eq_any(ids)).filter(empires::deleted_at.is_null())
+                .select(empires::id)
+                .load(&mut self.connection)
+        }
+
+        // Empires with their home at the location, in id order
+        pub fn list_by_location(&mut self, location_id: i32, page: Page, fields: &FieldSet) -> Result<Vec<Empire>, diesel::result::Error> {
+            use schema::empires;
+
+            empires::table
+                .filter(empires::location_id.eq(location_id)).filter(empires::deleted_at.is_null())
+                .select(Empire::columns(fields))
+                .order(empires::id.asc())
+                .limit(page.limit)
+                .offset(page.offset)
+                .load(&mut self.connection)
+        }
+
+        // Empires with their home at any of the given locations in one query, in id order
+        pub fn list_by_locations(&mut self, location_ids: &[i32]) -> Result<Vec<Empire>, diesel::result::Error> {
+            use schema::empires;
+
+            empires::table
+                .filter(empires::location_id.eq_any(location_ids)).filter(empires::deleted_at.is_null())
+                .order(empires::id.asc())
+                .load(&mut self.connection)
+        }
+
+        // Rows ordered by modification time, optionally limited to those modified at or after 'updated_since'
+        pub fn list(&mut self, updated_since: Option<DateTime<Utc>>, page: Page, fields: &FieldSet) -> Result<Vec<Empire>, diesel::result::Error> {
+            use schema::empires;
+
+            let mut query = empires::table.filter(empires::deleted_at.is_null()).into_boxed();
+
+            if let Some(updated_since) = updated_since {
+                query = query.filter(empires::updated_at.ge(updated_since));
             }
+
+            query
+                .select(Empire::columns(fields))
+                .order((empires::updated_at.asc(), empires::id.asc()))
+                .limit(page.limit)
+                .offset(page.offset)
+                .load(&mut self.connection)
         }
 
-        pub fn delete(&mut self, empire_id: i32) -> Result<(), diesel::result::Error> {
+        pub fn update(&mut self, empire_id: i32, upsert_empire: UpsertEmpire, expected_version: Option<i32>) -> Result<Empire, diesel::result::Error> {
             use schema::empires;
 
-            // Check if the empire exists before attempting to delete
-            let existing_empire = empires::table
-                .find(empire_id)
-                .get_result::<Empire>(&mut self.connection);
+            // The empire is locked from the check until it is written, so a write without an expected version cannot lose a race
+            self.connection.transaction(|connection| {
+                // Check if the empire exists before attempting to update
+                let existing_empire = empires::table
+                    .find(empire_id)
+                    .filter(empires::deleted_at.is_null())
+                    .for_update()
+                    .get_result::<Empire>(connection);
 
-            match existing_empire {
-                Ok(_) => {
-                    diesel::delete(empires::table.find(empire_id))
-                        .execute(&mut self.connection)?;
-                    Ok(())
+                match existing_empire {
+                    Ok(existing) => {
+                        // Without an expected version the write is conditioned on the version just read
+                        let expected_version = expected_version.unwrap_or(existing.version);
+
+                        let updated_empire = diesel::update(empires::table.find(empire_id).filter(empires::version.eq(expected_version)))
+                            .set((
+                                empires::name.eq(&upsert_empire.name),
+                                empires::slogan.eq(&upsert_empire.slogan),
+                                empires::location_id.eq(upsert_empire.location_id),
+                                empires::description.eq(&upsert_empire.description),
+                                empires::version.eq(empires::version + 1),
+                            ))
+                            .get_result(connection)?;
+
+                        Ok(updated_empire)
+                    }
+                    Err(_) => Err(diesel::result::Error::NotFound),
                 }
-                Err(_) => {
-                    Err(diesel::result::Error::NotFound)
+            })
+        }
+
+        // Only the columns present in the changeset are written
+        pub fn patch(&mut self, empire_id: i32, changes: EmpireChangeset, expected_version: Option<i32>) -> Result<Empire, diesel::result::Error> {
+            use schema::empires;
+
+            // The empire is locked from the read until it is written, so a write without an expected version cannot lose a race
+            self.connection.transaction(|connection| {
+                let current = empires::table.find(empire_id).filter(empires::deleted_at.is_null()).for_update().get_result::<Empire>(connection)?;
+                let expected_version = expected_version.unwrap_or(current.version);
+
+                // An empty changeset is not a valid UPDATE, so the current row is returned as is
+                if changes.is_empty() {
+                    return if current.version == expected_version { Ok(current) } else { Err(diesel::result::Error::NotFound) };
                 }
-            }
+
+                diesel::update(empires::table.find(empire_id).filter(empires::version.eq(expected_version)))
+                    .set((&changes, empires::version.eq(empires::version + 1)))
+                    .get_result(connection)
+            })
+        }
+
+        // Makes the empire the sovereign of the location unless another empire holds it. The claim itself is written by a trigger
+        // following the sovereign empire, so locking the location row is enough to keep two claims from racing.
+        // Fails with NotFound when the location does not exist or has been deleted
+        pub fn claim(&mut self, empire_id: i32, location_id: i32) -> Result<ClaimOutcome, diesel::result::Error> {
+            use schema::{locations, territory_claims};
+
+            self.connection.transaction(|connection| {
+                let sovereign_empire_id = locations::table
+                    .find(location_id)
+                    .filter(locations::deleted_at.is_null())
+                    .select(locations::sovereign_empire_id)
+                    .for_update()
+                    .get_result::<Option<i32>>(connection)?;
+
+                let held = match sovereign_empire_id {
+                    Some(holder_id) if holder_id != empire_id => return Ok(ClaimOutcome::HeldBy(holder_id)),
+                    Some(_) => true,
+                    None => {
+                        diesel::update(locations::table.find(location_id))
+                            .set((locations::sovereign_empire_id.eq(empire_id), locations::version.eq(locations::version + 1)))
+                            .execute(connection)?;
+                        false
+                    }
+                };
+
+                let claim = territory_claims::table
+                    .filter(territory_claims::location_id.eq(location_id))
+                    .filter(territory_claims::released_at.is_null())
+                    .get_result::<TerritoryClaim>(connection)?;
+
+                Ok(if held { ClaimOutcome::AlreadyHeld(claim) } else { ClaimOutcome::Claimed(claim) })
+            })
+        }
+
+        // Gives up the sovereignty of the empire over the location and returns the claim as it was before the release.
+        // Fails with NotFound unless the empire holds it
+        pub fn release(&mut self, empire_id: i32, location_id: i32) -> Result<TerritoryClaim, diesel::result::Error> {
+            use schema::{locations, territory_claims};
+
+            self.connection.transaction(|connection| {
+                let claim = territory_claims::table
+                    .filter(territory_claims::location_id.eq(location_id))
+                    .filter(territory_claims::empire_id.eq(empire_id))
+                    .filter(territory_claims::released_at.is_null())
+                    .for_update()
+                    .get_result::<TerritoryClaim>(connection)?;
+
+                let released_rows = diesel::update(locations::table.find(location_id).filter(locations::sovereign_empire_id.eq(empire_id)))
+                    .set((locations::sovereign_empire_id.eq(None::<i32>), locations::version.eq(locations::version + 1)))
+                    .execute(connection)?;
+
+                if released_rows == 0 {
+                    return Err(diesel::result::Error::NotFound);
+                }
+                Ok(claim)
+            })
+        }
+
+        // Locations held by the empire in the order they were claimed, deleted locations left out
+        pub fn territory(&mut self, empire_id: i32, page: Page, fields: &FieldSet) -> Result<Vec<Location>, diesel::result::Error> {
+            use schema::{locations, star_systems, territory_claims};
+
+            territory_claims::table
+                .inner_join(locations::table.inner_join(star_systems::table))
+                .filter(territory_claims::empire_id.eq(empire_id))
+                .filter(territory_claims::released_at.is_null())
+                .filter(locations::deleted_at.is_null())
+                .select(Location::columns(fields))
+                .order((territory_claims::claimed_at.asc(), territory_claims::id.asc()))
+                .limit(page.limit)
+                .offset(page.offset)
+                .load(&mut self.connection)
+        }
+
+        // Marks the row as deleted, hiding it from reads until it is restored or purged
+        pub fn delete(&mut self, empire_id: i32, expected_version: Option<i32>) -> Result<(), diesel::result::Error> {
+            use schema::empires;
+
+            // The empire is locked from the check until it is deleted, so a delete without an expected version cannot lose a race
+            self.connection.transaction(|connection| {
+                // Check if the empire exists before attempting to delete
+                let existing_empire = empires::table
+                    .find(empire_id)
+                    .filter(empires::deleted_at.is_null())
+                    .for_update()
+                    .get_result::<Empire>(connection);
+
+                match existing_empire {
+                    Ok(existing) => {
+                        let deleted_rows = diesel::update(empires::table.find(empire_id).filter(empires::version.eq(expected_version.unwrap_or(existing.version))))
+                            .set((empires::deleted_at.eq(Some(Utc::now())), empires::version.eq(empires::version + 1)))
+                            .execute(connection)?;
+
+                        // No deleted row means the version differs from the expected version
+                        if deleted_rows == 0 {
+                            return Err(diesel::result::Error::NotFound);
+                        }
+                        Ok(())
+                    }
+                    Err(_) => {
+                        Err(diesel::result::Error::NotFound)
+                    }
+                }
+            })
+        }
+
+        // Undoes a soft delete. Fails with NotFound unless the row exists and is deleted
+        pub fn restore(&mut self, empire_id: i32) -> Result<Empire, diesel::result::Error> {
+            use schema::empires;
+
+            diesel::update(empires::table.find(empire_id).filter(empires::deleted_at.is_not_null()))
+                .set((empires::deleted_at.eq(None::<DateTime<Utc>>), empires::version.eq(empires::version + 1)))
+                .get_result(&mut self.connection)
+        }
+
+        // Permanently removes rows deleted before the cutoff. Rows still referenced by other tables are kept until those references are gone
+        pub fn purge(&mut self, deleted_before: DateTime<Utc>) -> Result<usize, diesel::result::Error> {
+            use diesel::dsl::{exists, not};
+            use schema::empires;
+
+            diesel::delete(empires::table)
+                .filter(empires::deleted_at.lt(deleted_before))
+                .filter(not(exists(schema::ships::table.filter(schema::ships::empire_id.eq(empires::id)))))
+                .execute(&mut self.connection)
         }
     }
-}
 
+    // Fields to read for the documents, the returned ones along with the home location when it is included
+    pub fn empire_fields(fields: &FieldSet, included: &[&str]) -> FieldSet {
+        if included.contains(&"location") { fields.with(&["location_id"]) } else { fields.clone() }
+    }
+
+    // Empires along with the relations named in 'included', each loaded in one query whatever the number of empires
+    pub fn empire_documents(shared_state: &ConnectionPool, empires: Vec<Empire>, included: &[&str]) -> Result<Vec<EmpireDocument>, diesel::result::Error> {
+        let acquire = || shared_state.pool.get().expect("Failed to acquire connection from pool");
+        let empire_ids: Vec<i32> = empires.iter().map(|empire| empire.id).collect();
+
+        let locations: Option<HashMap<i32, Location>> = if included.contains(&"location") {
+            let location_ids: Vec<i32> = empires.iter().map(|empire| empire.location_id).collect();
+            Some(LocationsTable::new(acquire()).get_many(&location_ids)?.into_iter().map(|location| (location.id, location)).collect())
+        } else {
+            None
+        };
+        let mut ships = if included.contains(&"ships") {
+            Some(group_by(ShipsTable::new(acquire()).list_by_empires(&empire_ids)?, |ship| ship.empire_id))
+        } else {
+            None
+        };
+        let mut players = if included.contains(&"players") {
+            Some(group_by(PlayersTable::new(acquire()).list_by_empires(&empire_ids)?, |(empire_id, _)| *empire_id))
+        } else {
+            None
+        };
+
+        Ok(empires.into_iter()
+            .map(|empire| EmpireDocument {
+                location: locations.as_ref().map(|locations| locations.get(&empire.location_id).cloned()),
+                ships: ships.as_mut().map(|ships| ships.remove(&empire.id).unwrap_or_default()),
+                players: players.as_mut().map(|players| players.remove(&empire.id).unwrap_or_default().into_iter().map(|(_, player)| player).collect()),
+                empire,
+            })
+            .collect())
+    }
+}
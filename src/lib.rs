@@ -0,0 +1,13 @@
+#![allow(clippy::module_inception, clippy::expect_fun_call, clippy::to_string_in_format_args, clippy::clone_on_copy)]
+
+pub mod locations;
+pub mod users;
+pub mod common;
+pub mod empires;
+pub mod audit;
+pub mod ships;
+pub mod stargates;
+pub mod regions;
+pub mod players;
+pub mod search;
+mod schema;
@@ -1,18 +1,261 @@
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use serde_derive::{Serialize, Deserialize};
-use crate::schema::locations;
+use utoipa::{IntoParams, ToSchema};
+use diesel::{dsl::sql, expression::SqlLiteral, sql_types::{Double, Integer, Nullable, Text, Timestamptz}};
+use crate::{common::{fields::FieldSet, patch::changed, util::check_length}, empires::model::Empire, players::model::Player, schema::locations};
 
-#[derive(Serialize, Debug, Clone, Queryable)]
+#[derive(Serialize, Debug, Clone, Queryable, QueryableByName, ToSchema)]
 #[diesel(table_name = locations)]
 pub struct Location {
     pub id: i32,
+    pub star_system_id: i32,
+    // Name of the star system, read from the star system the location refers to
+    #[diesel(sql_type = Text)]
     pub star_system: String,
     pub area: String,
+    // Row version, incremented on every write and served as the ETag instead of in the body
+    #[serde(skip)]
+    pub version: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    // Position in light years, either all coordinates are set or none
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub z: Option<f64>,
+    pub security_status: f64,
+    // Empire holding sovereignty over the location, changes are kept in the sovereignty history. Only claiming and releasing
+    // territory changes it, location writes leave it as it is
+    pub sovereign_empire_id: Option<i32>,
 }
 
-#[derive(Debug, Clone, Insertable, Deserialize, Serialize)]
-#[diesel(table_name = locations)]
+// Selection loading a Location, see Location::columns
+pub type LocationColumns = (
+    SqlLiteral<Integer>, SqlLiteral<Integer>, SqlLiteral<Text>, SqlLiteral<Text>, SqlLiteral<Integer>, SqlLiteral<Timestamptz>, SqlLiteral<Timestamptz>, SqlLiteral<Nullable<Timestamptz>>,
+    SqlLiteral<Nullable<Double>>, SqlLiteral<Nullable<Double>>, SqlLiteral<Nullable<Double>>, SqlLiteral<Double>, SqlLiteral<Nullable<Integer>>,
+);
+
+impl Location {
+    pub const FIELDS: [&'static str; 11] = ["id", "star_system_id", "star_system", "area", "created_at", "updated_at", "x", "y", "z", "security_status", "sovereign_empire_id"];
+
+    // Columns of the row joined with its star system in model order, reading only those of the fieldset besides the id and the version served as ETag
+    pub fn columns(fields: &FieldSet) -> LocationColumns {
+        (
+            sql("locations.id"),
+            fields.column("star_system_id", "locations.star_system_id", "0"),
+            fields.column("star_system", "star_systems.name", "''"),
+            fields.column("area", "locations.area", "''"),
+            sql("locations.version"),
+            fields.column("created_at", "locations.created_at", "'epoch'::timestamptz"),
+            fields.column("updated_at", "locations.updated_at", "'epoch'::timestamptz"),
+            sql("NULL"),
+            fields.column("x", "locations.x", "NULL"),
+            fields.column("y", "locations.y", "NULL"),
+            fields.column("z", "locations.z", "NULL"),
+            fields.column("security_status", "locations.security_status", "0::float8"),
+            fields.column("sovereign_empire_id", "locations.sovereign_empire_id", "NULL"),
+        )
+    }
+
+    pub fn position(&self) -> Option<[f64; 3]> {
+        Some([self.x?, self.y?, self.z?])
+    }
+
+    // Straight line distance in light years, None unless both locations have a position
+    pub fn distance_to(&self, other: &Location) -> Option<f64> {
+        let (from, to) = (self.position()?, other.position()?);
+        Some(from.iter().zip(to.iter()).map(|(from, to)| (to - from).powi(2)).sum::<f64>().sqrt())
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, ToSchema)]
 pub struct UpsertLocation {
+    /// Name of the star system, which is added to the 'Unassigned' constellation unless it exists
     pub star_system: String,
     pub area: String,
-}
\ No newline at end of file
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub z: Option<f64>,
+    /// From -1.0 to 1.0, defaults to 0.0
+    #[serde(default)]
+    pub security_status: f64,
+}
+
+impl UpsertLocation {
+    // Column limits of the locations table
+    pub fn validate(&self) -> Result<(), String> {
+        check_length("star_system", &self.star_system, 100)?;
+        check_length("area", &self.area, 100)?;
+
+        let coordinates = [self.x, self.y, self.z];
+        if coordinates.iter().any(Option::is_some) && !coordinates.iter().all(|coordinate| coordinate.is_some_and(f64::is_finite)) {
+            return Err("'x', 'y' and 'z' must either all be finite numbers or all be omitted".to_string());
+        }
+        if !(-1.0..=1.0).contains(&self.security_status) {
+            return Err("'security_status' must be between -1.0 and 1.0".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl From<Location> for UpsertLocation {
+    fn from(location: Location) -> Self {
+        UpsertLocation {
+            star_system: location.star_system,
+            area: location.area,
+            x: location.x,
+            y: location.y,
+            z: location.z,
+            security_status: location.security_status,
+        }
+    }
+}
+
+// Columns changed by a merge patch, unchanged columns are left as None and skipped in the UPDATE
+#[derive(Debug, Clone, Default, AsChangeset)]
+#[diesel(table_name = locations)]
+pub struct LocationChangeset {
+    // The name is resolved to the id of its star system before the changeset is written
+    #[diesel(skip_update)]
+    pub star_system: Option<String>,
+    pub star_system_id: Option<i32>,
+    pub area: Option<String>,
+    pub x: Option<Option<f64>>,
+    pub y: Option<Option<f64>>,
+    pub z: Option<Option<f64>>,
+    pub security_status: Option<f64>,
+}
+
+impl LocationChangeset {
+    pub fn between(current: &UpsertLocation, merged: &UpsertLocation) -> LocationChangeset {
+        LocationChangeset {
+            star_system: changed(&current.star_system, &merged.star_system),
+            star_system_id: None,
+            area: changed(&current.area, &merged.area),
+            x: changed(&current.x, &merged.x),
+            y: changed(&current.y, &merged.y),
+            z: changed(&current.z, &merged.z),
+            security_status: changed(&current.security_status, &merged.security_status),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.star_system.is_none() && self.area.is_none() && self.x.is_none() && self.y.is_none() && self.z.is_none()
+            && self.security_status.is_none()
+    }
+}
+
+// A location with the related resources named in '?include='. Relations that were not asked for are left out
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct LocationDocument {
+    #[serde(flatten)]
+    pub location: Location,
+    /// Empires with their home at the location
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub empires: Option<Vec<Empire>>,
+    /// Players at the location
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub players: Option<Vec<Player>>,
+}
+
+impl LocationDocument {
+    pub const INCLUDES: [&'static str; 2] = ["empires", "players"];
+}
+
+// Location within a radius of another location
+#[derive(Serialize, Debug, Clone, QueryableByName, ToSchema)]
+pub struct NearbyLocation {
+    #[diesel(embed)]
+    #[serde(flatten)]
+    pub location: Location,
+    /// Distance in light years
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    pub distance: f64,
+}
+
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct Distance {
+    pub from_location_id: i32,
+    pub to_location_id: i32,
+    /// Straight line distance in light years
+    pub light_years: f64,
+}
+
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NearbyQuery {
+    /// Id of the location at the centre of the search
+    pub of: i32,
+    /// Radius in light years
+    pub within: f64,
+    /// Maximum number of items, between 1 and 500. Defaults to 50
+    pub limit: Option<i64>,
+    /// Number of items to skip. Defaults to 0
+    pub offset: Option<i64>,
+}
+
+impl NearbyQuery {
+    pub fn radius(&self) -> Result<f64, String> {
+        if !self.within.is_finite() || self.within <= 0.0 {
+            return Err("Query parameter 'within' must be a positive number of light years".to_string());
+        }
+        Ok(self.within)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SecurityClass {
+    // Security status of 0.45 and above
+    High,
+    // Security status above 0.0 and below 0.45
+    Low,
+    // Security status of 0.0 and below
+    Null,
+}
+
+impl SecurityClass {
+    pub const HIGH_SEC: f64 = 0.45;
+
+    pub fn of(security_status: f64) -> SecurityClass {
+        if security_status >= SecurityClass::HIGH_SEC {
+            SecurityClass::High
+        } else if security_status > 0.0 {
+            SecurityClass::Low
+        } else {
+            SecurityClass::Null
+        }
+    }
+}
+
+// Query parameters of the location list, the shared list parameters along with filters only locations have
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LocationQuery {
+    /// Only rows created or modified at or after this RFC 3339 timestamp, for incremental sync
+    pub updated_since: Option<DateTime<Utc>>,
+    /// Only locations of this security class: 'high' (0.45 and above), 'low' (above 0.0) or 'null'
+    #[param(inline)]
+    pub security: Option<SecurityClass>,
+    /// Maximum number of items, between 1 and 500. Defaults to 50
+    pub limit: Option<i64>,
+    /// Number of items to skip. Defaults to 0
+    pub offset: Option<i64>,
+}
+
+#[derive(Serialize, Debug, Clone, Queryable, ToSchema)]
+pub struct SovereigntyChange {
+    pub id: i32,
+    pub location_id: i32,
+    /// Empire that held sovereignty before the change, null when the location was unclaimed
+    pub previous_empire_id: Option<i32>,
+    /// Empire holding sovereignty after the change, null when it was given up
+    pub empire_id: Option<i32>,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl SovereigntyChange {
+    pub const FIELDS: [&'static str; 5] = ["id", "location_id", "previous_empire_id", "empire_id", "changed_at"];
+}
@@ -1,17 +1,32 @@
 pub mod router {
+    use std::collections::HashSet;
+    use diesel::result::DatabaseErrorKind;
     use serde_json::{json, Value};
+    use tracing::error;
     use axum::{
-        Router, http::StatusCode, Json, response::IntoResponse, extract::State, extract,
+        Router, http::{header, StatusCode}, Json, body::Bytes, response::{IntoResponse, Response}, extract::{rejection::QueryRejection, Query, State}, extract,
     };
     use http::HeaderMap;
     use crate::{
+        audit::{model::AuditAction, service::service::record_event},
         common::db::ConnectionPool,
+        empires::{model::{Empire, EmpireDocument, TerritoryClaim}, service::service::{empire_documents, empire_fields, EmpiresTable}},
+        players::{model::{Player, PlayerDocument}, service::service::{player_documents, PlayersTable}},
         locations::{
-            service::service::LocationsTable as locationsDB,
-            model::UpsertLocation
+            service::service::{location_documents, LocationsTable as locationsDB},
+            model::{Distance, Location, LocationChangeset, LocationDocument, LocationQuery, NearbyQuery, SovereigntyChange, UpsertLocation}
         },
         users::model::UserRole,
-        common::security::{enforce_role_policy, decode_claims}
+        common::{
+            bulk::{apply_all, bulk_failure, bulk_response, check_bulk_size, validate_all, with_rejected, BulkMode, BulkQuery, BulkUpdate},
+            etag::{check_if_match, etag, is_not_modified, not_modified, precondition_failed},
+            fields::FieldsQuery,
+            include::IncludeQuery,
+            pagination::{invalid_query, Page, PageQuery, Paginated},
+            transfer::{export, export_format, import_format, invalid_rows, parse_rows, ExportQuery, ImportQuery, ImportReport, RowError},
+            patch::apply_merge_patch,
+            security::{enforce_role_policy, decode_claims}
+        }
     };
 
     // - - - - - - - - - - - [ROUTES] - - - - - - - - - - -
@@ -19,14 +34,45 @@ pub mod router {
     pub fn locations_route(shared_connection_pool: ConnectionPool) -> Router {
         Router::new()
             .route("/locations", axum::routing::post(create_location_handler))
+            .route("/locations", axum::routing::get(list_locations_handler))
+            .route("/locations/bulk", axum::routing::post(bulk_create_locations_handler))
+            .route("/locations/bulk", axum::routing::put(bulk_update_locations_handler))
+            .route("/locations/bulk", axum::routing::delete(bulk_delete_locations_handler))
+            .route("/locations/export", axum::routing::get(export_locations_handler))
+            .route("/locations/nearby", axum::routing::get(nearby_locations_handler))
+            .route("/locations/import", axum::routing::post(import_locations_handler))
             .route("/locations/:location_id", axum::routing::get(read_location_handler))
             .route("/locations/:location_id", axum::routing::put(update_location_handler))
+            .route("/locations/:location_id", axum::routing::patch(patch_location_handler))
             .route("/locations/:location_id", axum::routing::delete(delete_location_handler))
+            .route("/locations/:location_id/restore", axum::routing::post(restore_location_handler))
+            .route("/locations/:location_id/distance/:other_id", axum::routing::get(location_distance_handler))
+            .route("/locations/:location_id/sovereignty", axum::routing::get(sovereignty_history_handler))
+            .route("/locations/:location_id/claims", axum::routing::get(list_claims_handler))
+            .route("/locations/:location_id/empires", axum::routing::get(list_location_empires_handler))
+            .route("/locations/:location_id/players", axum::routing::get(list_location_players_handler))
             .with_state(shared_connection_pool)
     }
 
     // - - - - - - - - - - - [HANDLERS] - - - - - - - - - - -
 
+    /// Create a location
+    ///
+    /// Requires role WRITER or higher.
+    #[utoipa::path(
+        post,
+        path = "/locations",
+        tag = "locations",
+        request_body = UpsertLocation,
+        responses(
+            (status = 201, description = "Location created", body = Location),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 409, description = "A location with this star system and area already exists", body = ErrorBody),
+            (status = 422, description = "Invalid field", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while creating location", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
     pub async fn create_location_handler(
         headers: HeaderMap,
         State(shared_state): State<ConnectionPool>,
@@ -44,13 +90,21 @@ pub mod router {
 
         match authorization {
             Ok(_authorized_user) => {
+                upsert_location.validate().map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": err}))))?;
+
                 let connection = shared_state.pool.get()
                     .expect("Failed to acquire connection from pool");
 
-                match locationsDB::new(connection).create(upsert_location) {
-                    Ok(new_location) => Ok((StatusCode::CREATED, Json(new_location))),
+                let result = locationsDB::new(connection).create(upsert_location);
+
+                match result {
+                    Ok(new_location) => {
+                        record_event(&shared_state, &headers, AuditAction::Create, "location", new_location.id, None, Some(&new_location));
+                        Ok((StatusCode::CREATED, Json(new_location)))
+                    },
+                    Err(diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => Err(duplicate_location()),
                     Err(err) => {
-                        eprintln!("Error creating location: {:?}", err);
+                        error!("Error creating location: {:?}", err);
                         Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to create location"}))))
                     }
                 }
@@ -59,12 +113,91 @@ pub mod router {
         }
     }
 
+    /// List locations
+    ///
+    /// Requires role READER or higher. Ordered by modification time, oldest first, optionally limited to one security class.
+    /// '?include=' embeds any of 'empires' and 'players', '?fields=' limits the fields of each location.
+    #[utoipa::path(
+        get,
+        path = "/locations",
+        tag = "locations",
+        params(LocationQuery, IncludeQuery, FieldsQuery),
+        responses(
+            (status = 200, description = "Page of locations", body = LocationDocumentPage),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing locations", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_locations_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<LocationQuery>, QueryRejection>,
+        include: Result<Query<IncludeQuery>, QueryRejection>,
+        fields: Result<Query<FieldsQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        let page = Page::new(query.limit, query.offset)?;
+        let included = include.map_err(invalid_query)?.parse(&LocationDocument::INCLUDES)?;
+        let fields = fields.map_err(invalid_query)?.parse(&Location::FIELDS)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let listing_failed = |err: diesel::result::Error| {
+                    error!("Error listing locations: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list locations"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let locations = locationsDB::new(connection).list(&query, page, &fields).map_err(listing_failed)?;
+                let documents = location_documents(&shared_state, locations, &included).map_err(listing_failed)?;
+                let items = documents.iter().map(|document| fields.project(document, &included)).collect();
+                Ok((StatusCode::OK, Json(Paginated::new(items, page))))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Read a location by id
+    ///
+    /// Requires role READER or higher. '?include=' embeds any of 'empires' and 'players', '?fields=' limits the fields of the location.
+    /// The ETag is the version of the location alone, so If-None-Match is only honoured when nothing is included.
+    #[utoipa::path(
+        get,
+        path = "/locations/{location_id}",
+        tag = "locations",
+        params(("location_id" = i32, Path, description = "Id of the location"), IncludeQuery, FieldsQuery),
+        responses(
+            (status = 200, description = "Location found", body = LocationDocument, headers(("ETag" = String, description = "Version of the location"))),
+            (status = 304, description = "Location matches If-None-Match"),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Location not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while reading location", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
     pub async fn read_location_handler(
         headers: HeaderMap,
         State(shared_state): State<ConnectionPool>,
         path: extract::Path<(i32, )>,
+        include: Result<Query<IncludeQuery>, QueryRejection>,
+        fields: Result<Query<FieldsQuery>, QueryRejection>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
         let (location_id, ) = path.0;
+        let included = include.map_err(invalid_query)?.parse(&LocationDocument::INCLUDES)?;
+        let fields = fields.map_err(invalid_query)?.parse(&Location::FIELDS)?;
 
         // Decode claims from bearer token header
         let claims = match decode_claims(&headers) {
@@ -77,20 +210,100 @@ pub mod router {
 
         match authorization {
             Ok(_authorized_user) => {
+                let reading_failed = |err: diesel::result::Error| {
+                    error!("Error reading location: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to read location"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let location = locationsDB::new(connection).read(location_id, &fields).map_err(reading_failed)?
+                    .ok_or_else(|| (StatusCode::NOT_FOUND, Json(json!({"error": "Location not found"}))))?;
+
+                let version = location.version;
+                if included.is_empty() && is_not_modified(&headers, version) {
+                    return Ok(not_modified(version));
+                }
+
+                let document = location_documents(&shared_state, vec![location], &included).map_err(reading_failed)?.remove(0);
+                Ok((StatusCode::OK, [(header::ETAG, etag(version))], Json(fields.project(&document, &included))).into_response())
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Replace a location
+    ///
+    /// Requires role EDITOR or higher.
+    #[utoipa::path(
+        put,
+        path = "/locations/{location_id}",
+        tag = "locations",
+        params(("location_id" = i32, Path, description = "Id of the location")),
+        request_body = UpsertLocation,
+        responses(
+            (status = 200, description = "Location updated", body = Location, headers(("ETag" = String, description = "Version of the location"))),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Location not found", body = ErrorBody),
+            (status = 409, description = "A location with this star system and area already exists", body = ErrorBody),
+            (status = 412, description = "If-Match does not match the current version", body = ErrorBody),
+            (status = 422, description = "Invalid field", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while updating location", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn update_location_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+        Json(upsert_location): Json<UpsertLocation>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (location_id, ) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'EDITOR' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::EDITOR).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                upsert_location.validate().map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": err}))))?;
+
                 let connection = shared_state.pool.get()
                     .expect("Failed to acquire connection from pool");
 
-                match locationsDB::new(connection).get(location_id) {
-                    Ok(location) => {
-                        if let Some(location) = location {
-                            Ok((StatusCode::OK, Json(location)))
-                        } else {
-                            Err((StatusCode::NOT_FOUND, Json(json!({"error": "Location not found"}))))
-                        }
+                let mut locations = locationsDB::new(connection);
+
+                let before = locations.get(location_id).map_err(|err| {
+                    error!("Error reading location: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to update location"})))
+                })?;
+
+                let expected_version = check_if_match(&headers, || Ok(before.as_ref().map(|location| location.version)))?;
+
+                let result = locations.update(location_id, upsert_location, expected_version);
+                // Hand the connection back to the pool before the audit event is recorded
+                drop(locations);
+
+                match result {
+                    Ok(updated_location) => {
+                        record_event(&shared_state, &headers, AuditAction::Update, "location", location_id, before.as_ref(), Some(&updated_location));
+                        Ok((StatusCode::OK, [(header::ETAG, etag(updated_location.version))], Json(updated_location)))
                     },
+                    // A conditional write matching no row lost the race against a concurrent write
+                    Err(diesel::result::Error::NotFound) if expected_version.is_some() => Err(precondition_failed()),
+                    Err(diesel::result::Error::NotFound) => {
+                        Err((StatusCode::NOT_FOUND, Json(json!({"error": "Location not found"}))))
+                    },
+                    Err(diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => Err(duplicate_location()),
                     Err(err) => {
-                        eprintln!("Error reading location: {:?}", err);
-                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to read location"}))))
+                        error!("Error updating location: {:?}", err);
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to update location"}))))
                     }
                 }
             }
@@ -98,11 +311,31 @@ pub mod router {
         }
     }
 
-    pub async fn update_location_handler(
+    /// Partially update a location with a JSON merge patch (RFC 7396)
+    ///
+    /// Requires role EDITOR or higher. Only the columns changed by the patch are written.
+    #[utoipa::path(
+        patch,
+        path = "/locations/{location_id}",
+        tag = "locations",
+        params(("location_id" = i32, Path, description = "Id of the location")),
+        request_body(content = Object, content_type = "application/merge-patch+json", description = "Merge patch applied to the UpsertLocation representation"),
+        responses(
+            (status = 200, description = "Location updated", body = Location, headers(("ETag" = String, description = "Version of the location"))),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Location not found", body = ErrorBody),
+            (status = 409, description = "A location with this star system and area already exists", body = ErrorBody),
+            (status = 412, description = "If-Match does not match the current version", body = ErrorBody),
+            (status = 422, description = "Patch is not an object, names unknown fields or yields an invalid location", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while updating location", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn patch_location_handler(
         headers: HeaderMap,
         State(shared_state): State<ConnectionPool>,
         path: extract::Path<(i32, )>,
-        Json(upsert_location): Json<UpsertLocation>,
+        Json(patch): Json<Value>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
         let (location_id, ) = path.0;
 
@@ -120,13 +353,40 @@ pub mod router {
                 let connection = shared_state.pool.get()
                     .expect("Failed to acquire connection from pool");
 
-                match locationsDB::new(connection).update(location_id, upsert_location) {
-                    Ok(updated_location) => Ok((StatusCode::OK, Json(updated_location))),
+                let mut locations = locationsDB::new(connection);
+
+                let existing = match locations.get(location_id) {
+                    Ok(Some(location)) => location,
+                    Ok(None) => return Err((StatusCode::NOT_FOUND, Json(json!({"error": "Location not found"})))),
+                    Err(err) => {
+                        error!("Error reading location: {:?}", err);
+                        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to update location"}))));
+                    }
+                };
+
+                let expected_version = check_if_match(&headers, || Ok(Some(existing.version)))?;
+                let current = UpsertLocation::from(existing.clone());
+
+                // Merge the patch into the current representation, rejecting documents that no longer form a valid location
+                let merged = apply_merge_patch(&current, &patch)
+                    .and_then(|merged| merged.validate().map(|_| merged))
+                    .map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": err}))))?;
+
+                let result = locations.patch(location_id, LocationChangeset::between(&current, &merged), expected_version);
+                drop(locations);
+
+                match result {
+                    Ok(updated_location) => {
+                        record_event(&shared_state, &headers, AuditAction::Patch, "location", location_id, Some(&existing), Some(&updated_location));
+                        Ok((StatusCode::OK, [(header::ETAG, etag(updated_location.version))], Json(updated_location)))
+                    },
+                    Err(diesel::result::Error::NotFound) if expected_version.is_some() => Err(precondition_failed()),
                     Err(diesel::result::Error::NotFound) => {
                         Err((StatusCode::NOT_FOUND, Json(json!({"error": "Location not found"}))))
                     },
+                    Err(diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => Err(duplicate_location()),
                     Err(err) => {
-                        eprintln!("Error updating location: {:?}", err);
+                        error!("Error updating location: {:?}", err);
                         Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to update location"}))))
                     }
                 }
@@ -135,6 +395,23 @@ pub mod router {
         }
     }
 
+    /// Delete a location
+    ///
+    /// Requires role ADMIN. The location is hidden from reads until it is restored, or removed for good by the
+    /// 'purge-deleted' admin command once it is older than the retention period.
+    #[utoipa::path(
+        delete,
+        path = "/locations/{location_id}",
+        tag = "locations",
+        params(("location_id" = i32, Path, description = "Id of the location")),
+        responses(
+            (status = 204, description = "Location deleted"),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 412, description = "If-Match does not match the current version", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while deleting location", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
     pub async fn delete_location_handler(
         headers: HeaderMap,
         State(shared_state): State<ConnectionPool>,
@@ -156,10 +433,26 @@ pub mod router {
                 let connection = shared_state.pool.get()
                     .expect("Failed to acquire connection from pool");
 
-                match locationsDB::new(connection).delete(location_id) {
-                    Ok(_) => Ok((StatusCode::NO_CONTENT, ())),
+                let mut locations = locationsDB::new(connection);
+
+                let before = locations.get(location_id).map_err(|err| {
+                    error!("Error reading location: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to delete location"})))
+                })?;
+
+                let expected_version = check_if_match(&headers, || Ok(before.as_ref().map(|location| location.version)))?;
+
+                let result = locations.delete(location_id, expected_version);
+                drop(locations);
+
+                match result {
+                    Ok(_) => {
+                        record_event(&shared_state, &headers, AuditAction::Delete, "location", location_id, before.as_ref(), None);
+                        Ok((StatusCode::NO_CONTENT, ()))
+                    },
+                    Err(diesel::result::Error::NotFound) if expected_version.is_some() => Err(precondition_failed()),
                     Err(err) => {
-                        eprintln!("Error deleting location: {:?}", err);
+                        error!("Error deleting location: {:?}", err);
                         Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to delete location"}))))
                     }
                 }
@@ -168,354 +461,1886 @@ pub mod router {
         }
     }
 
-    #[cfg(test)]
-    mod tests {
-        use axum::{
-            body::Body,
-            http::{Request, StatusCode}
+    /// Restore a deleted location
+    ///
+    /// Requires role ADMIN.
+    #[utoipa::path(
+        post,
+        path = "/locations/{location_id}/restore",
+        tag = "locations",
+        params(("location_id" = i32, Path, description = "Id of the deleted location")),
+        responses(
+            (status = 200, description = "Location restored", body = Location, headers(("ETag" = String, description = "Version of the location"))),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "No deleted location with this id", body = ErrorBody),
+            (status = 409, description = "A location with this star system and area has been created since the delete", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while restoring location", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn restore_location_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (location_id, ) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
         };
-        use serde_json::json;
-        use tower::ServiceExt;
-        use crate::{
-            common::{
-                db::create_shared_connection_pool,
-                util::load_environment_variable,
-                security::hash_password
-            },
-            locations::{
-                model::UpsertLocation,
-                service::service::LocationsTable
-            },
-            users::{
-                model::UpsertUser,
-                service::service::UsersTable
-            },
-            locations_route
+
+        // Ensure that the user derived from claims exists and has the role 'ADMIN'
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::ADMIN).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let result = locationsDB::new(connection).restore(location_id);
+
+                match result {
+                    Ok(restored_location) => {
+                        record_event(&shared_state, &headers, AuditAction::Restore, "location", location_id, None, Some(&restored_location));
+                        Ok((StatusCode::OK, [(header::ETAG, etag(restored_location.version))], Json(restored_location)))
+                    },
+                    Err(diesel::result::Error::NotFound) => {
+                        Err((StatusCode::NOT_FOUND, Json(json!({"error": "No deleted location with this id"}))))
+                    },
+                    Err(diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => Err(duplicate_location()),
+                    Err(err) => {
+                        error!("Error restoring location: {:?}", err);
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to restore location"}))))
+                    }
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Create locations in bulk
+    ///
+    /// Requires role WRITER or higher. In transactional mode the first failing item rolls back every item and is answered with its status and index.
+    #[utoipa::path(
+        post,
+        path = "/locations/bulk",
+        tag = "locations",
+        params(BulkQuery),
+        request_body = Vec<UpsertLocation>,
+        responses(
+            (status = 200, description = "Every location created", body = BulkResponse),
+            (status = 207, description = "Outcome of each location in partial mode", body = BulkResponse),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 409, description = "A location duplicates another, all items rolled back", body = ErrorBody),
+            (status = 413, description = "Too many items", body = ErrorBody),
+            (status = 422, description = "A location is invalid, all items rolled back", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while creating locations", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn bulk_create_locations_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<BulkQuery>, QueryRejection>,
+        Json(upsert_locations): Json<Vec<UpsertLocation>>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        check_bulk_size(upsert_locations.len())?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
         };
-        use crate::common::db::ConnectionPool;
-        use crate::common::security::generate_token;
-        use crate::users::model::UserRole;
 
-        // Helper method utilized to create user with a specific role and return the associated bearer token in one line of code
-        pub fn create_user_and_generate_token(connection_pool: ConnectionPool, email: &str, user_role: UserRole) -> Result<String, jsonwebtoken::errors::Error> {
+        // Ensure that the user derived from claims exists and has the role 'WRITER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::WRITER).await;
 
-            // Only email and role are mutable as password and fullname has no constraints
-            let mut new_user = UpsertUser {
-                email: email.to_string(),
-                role: user_role.to_string(),
-                password: "StålGardinerFunkerFjell53".to_string(),
-                fullname: "Josef Stålhard".to_string()
-            };
+        match authorization {
+            Ok(_authorized_user) => {
+                let (upsert_locations, rejected) = validate_all(upsert_locations, query.mode(), UpsertLocation::validate)?;
 
-            // Hash the password
-            hash_password(&mut new_user).expect("Hash failed");
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
 
-            // Perform the user creation
-            let create_user_result = {
-                let connection = connection_pool.pool.get().expect("Failed to get connection");
-                UsersTable::new(connection).create(new_user.clone())
-            };
+                let mut locations = locationsDB::new(connection);
+                let result = apply_all(&mut locations, upsert_locations, query.mode(), |locations, upsert_location| locations.create(upsert_location));
+                drop(locations);
 
-            // Generate the bearer token
-            generate_token(&create_user_result.unwrap())
+                let outcomes = result.map_err(|err| bulk_failure(err, bulk_error))?;
+
+                for new_location in outcomes.iter().flatten() {
+                    record_event(&shared_state, &headers, AuditAction::Create, "location", new_location.id, None, Some(new_location));
+                }
+
+                let outcomes = outcomes.into_iter()
+                    .map(|outcome| outcome.map(|new_location| new_location.id).map_err(|err| bulk_error(&err)))
+                    .collect();
+
+                Ok(bulk_response(query.mode(), StatusCode::CREATED, with_rejected(outcomes, rejected)))
+            }
+            Err(err) => Err(err)
         }
+    }
 
-        #[tokio::test]
-        async fn post_locations_returns_201_for_authorized_user_with_write_access() {
-            let database_url = load_environment_variable("TEST_DB");
-            let connection_pool = create_shared_connection_pool(database_url, 1);
-            let service = locations_route(connection_pool.clone());
+    /// Replace locations in bulk
+    ///
+    /// Requires role EDITOR or higher. Each item holds the id of the location next to its new representation.
+    #[utoipa::path(
+        put,
+        path = "/locations/bulk",
+        tag = "locations",
+        params(BulkQuery),
+        request_body = Vec<LocationUpdate>,
+        responses(
+            (status = 200, description = "Every location updated", body = BulkResponse),
+            (status = 207, description = "Outcome of each location in partial mode", body = BulkResponse),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Location not found, all items rolled back", body = ErrorBody),
+            (status = 409, description = "A location duplicates another, all items rolled back", body = ErrorBody),
+            (status = 413, description = "Too many items", body = ErrorBody),
+            (status = 422, description = "A location is invalid, all items rolled back", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while updating locations", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn bulk_update_locations_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<BulkQuery>, QueryRejection>,
+        Json(location_updates): Json<Vec<BulkUpdate<UpsertLocation>>>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        check_bulk_size(location_updates.len())?;
 
-            // Create user with role WRITER and generate associated bearer token
-            let bearer_token = create_user_and_generate_token(connection_pool, "stål.hard.russer@ugreit.ru", UserRole::WRITER);
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
 
-            let request_body = UpsertLocation {
-                star_system: "Fountain".to_string(),
-                area: "The Serpent's Lair".to_string(),
-            };
+        // Ensure that the user derived from claims exists and has the role 'EDITOR' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::EDITOR).await;
 
-            // Create a request with the above data as payload
-            let request = Request::builder()
-                .uri("/locations")
-                .method("POST")
-                .header("content-type", "application/json")
+        match authorization {
+            Ok(_authorized_user) => {
+                let (location_updates, rejected) = validate_all(location_updates, query.mode(), |update: &BulkUpdate<UpsertLocation>| update.body.validate())?;
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut locations = locationsDB::new(connection);
+                let result = apply_all(&mut locations, location_updates, query.mode(), |locations, BulkUpdate { id, body }| {
+                    let before = locations.get(id)?.ok_or(diesel::result::Error::NotFound)?;
+                    locations.update(id, body, None).map(|after| (before, after))
+                });
+                drop(locations);
+
+                let outcomes = result.map_err(|err| bulk_failure(err, bulk_error))?;
+
+                for (before, after) in outcomes.iter().flatten() {
+                    record_event(&shared_state, &headers, AuditAction::Update, "location", after.id, Some(before), Some(after));
+                }
+
+                let outcomes = outcomes.into_iter()
+                    .map(|outcome| outcome.map(|(_, updated_location)| updated_location.id).map_err(|err| bulk_error(&err)))
+                    .collect();
+
+                Ok(bulk_response(query.mode(), StatusCode::OK, with_rejected(outcomes, rejected)))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Delete locations in bulk
+    ///
+    /// Requires role ADMIN. The body is the array of ids to delete.
+    #[utoipa::path(
+        delete,
+        path = "/locations/bulk",
+        tag = "locations",
+        params(BulkQuery),
+        request_body = Vec<i32>,
+        responses(
+            (status = 200, description = "Every location deleted", body = BulkResponse),
+            (status = 207, description = "Outcome of each location in partial mode", body = BulkResponse),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Location not found, all items rolled back", body = ErrorBody),
+            (status = 413, description = "Too many items", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while deleting locations", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn bulk_delete_locations_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<BulkQuery>, QueryRejection>,
+        Json(location_ids): Json<Vec<i32>>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        check_bulk_size(location_ids.len())?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'ADMIN'
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::ADMIN).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut locations = locationsDB::new(connection);
+                let result = apply_all(&mut locations, location_ids, query.mode(), |locations, location_id| {
+                    let before = locations.get(location_id)?.ok_or(diesel::result::Error::NotFound)?;
+                    locations.delete(location_id, None).map(|_| before)
+                });
+                drop(locations);
+
+                let outcomes = result.map_err(|err| bulk_failure(err, bulk_error))?;
+
+                for deleted_location in outcomes.iter().flatten() {
+                    record_event(&shared_state, &headers, AuditAction::Delete, "location", deleted_location.id, Some(deleted_location), None);
+                }
+
+                let outcomes = outcomes.into_iter()
+                    .map(|outcome| outcome.map(|deleted_location| deleted_location.id).map_err(|err| bulk_error(&err)))
+                    .collect();
+
+                Ok(bulk_response(query.mode(), StatusCode::NO_CONTENT, outcomes))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Export locations
+    ///
+    /// Requires role READER or higher. Streams every location in id order as CSV or NDJSON, chosen with '?format=' or else the Accept header.
+    #[utoipa::path(
+        get,
+        path = "/locations/export",
+        tag = "locations",
+        params(ExportQuery),
+        responses(
+            (status = 200, description = "Every location", content(("text/csv" = String), ("application/x-ndjson" = String))),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 406, description = "Neither CSV nor NDJSON is acceptable", body = ErrorBody),
+            (status = 500, description = "Missing header", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn export_locations_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<ExportQuery>, QueryRejection>,
+    ) -> Result<Response, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        let format = export_format(query.format, &headers)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut locations = locationsDB::new(connection);
+                Ok(export(format, move |after_id, limit| locations.list_after(after_id, limit), |location: &Location| location.id))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Import locations
+    ///
+    /// Requires role WRITER or higher. Creates a location from every CSV record or NDJSON line, chosen with '?format=' or else the Content-Type header.
+    /// Every row is checked against the location model before anything is written, and '?dry_run=true' stops after the check.
+    #[utoipa::path(
+        post,
+        path = "/locations/import",
+        tag = "locations",
+        params(ImportQuery),
+        request_body(content = String, description = "CSV with a header row naming the columns, or one JSON object per line", content_type = "text/csv"),
+        responses(
+            (status = 200, description = "Every row is valid, nothing written in dry-run mode", body = ImportReport),
+            (status = 201, description = "Every row imported", body = ImportReport),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 409, description = "A row duplicates an existing location, nothing written", body = ErrorBody),
+            (status = 415, description = "Neither CSV nor NDJSON", body = ErrorBody),
+            (status = 422, description = "Invalid rows, nothing written", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while importing locations", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn import_locations_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<ImportQuery>, QueryRejection>,
+        body: Bytes,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        let format = import_format(query.format, &headers)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'WRITER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::WRITER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let rows = parse_rows::<UpsertLocation>(format, &body).map_err(invalid_rows)?;
+
+                // Rows repeating the star system and area of an earlier row would fail the unique index when written
+                let mut seen = HashSet::new();
+                let invalid: Vec<RowError> = rows.iter()
+                    .filter_map(|(row, location)| {
+                        location.validate().err()
+                            .or_else(|| (!seen.insert((&location.star_system, &location.area))).then(|| DUPLICATE_LOCATION.to_string()))
+                            .map(|error| RowError { row: *row, error })
+                    })
+                    .collect();
+
+                if !invalid.is_empty() {
+                    return Err(invalid_rows(invalid));
+                }
+                if query.dry_run {
+                    return Ok((StatusCode::OK, Json(ImportReport { rows: rows.len(), dry_run: true, ids: Vec::new() })));
+                }
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut locations = locationsDB::new(connection);
+                let upsert_locations = rows.into_iter().map(|(_, location)| location).collect();
+                let result = apply_all(&mut locations, upsert_locations, BulkMode::Transactional, |locations, upsert_location| locations.create(upsert_location));
+                drop(locations);
+
+                let created_locations: Vec<Location> = result.map_err(|err| bulk_failure(err, bulk_error))?.into_iter().flatten().collect();
+
+                for new_location in &created_locations {
+                    record_event(&shared_state, &headers, AuditAction::Create, "location", new_location.id, None, Some(new_location));
+                }
+
+                let ids = created_locations.iter().map(|new_location| new_location.id).collect();
+                Ok((StatusCode::CREATED, Json(ImportReport { rows: created_locations.len(), dry_run: false, ids })))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Find locations near another location
+    ///
+    /// Requires role READER or higher. Locations with a position within 'within' light years of the location 'of', nearest first.
+    /// '?fields=' limits the fields of each location, the distance is always returned.
+    #[utoipa::path(
+        get,
+        path = "/locations/nearby",
+        tag = "locations",
+        params(NearbyQuery, FieldsQuery),
+        responses(
+            (status = 200, description = "Page of nearby locations", body = NearbyLocationPage),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Location not found", body = ErrorBody),
+            (status = 422, description = "The location has no coordinates", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while finding locations", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn nearby_locations_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<NearbyQuery>, QueryRejection>,
+        fields: Result<Query<FieldsQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        let page = Page::new(query.limit, query.offset)?;
+        let radius = query.radius().map_err(|err| (StatusCode::BAD_REQUEST, Json(json!({"error": err}))))?;
+        let fields = fields.map_err(invalid_query)?.parse(&Location::FIELDS)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let search_failed = |err: diesel::result::Error| {
+                    error!("Error finding nearby locations: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to find nearby locations"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut locations = locationsDB::new(connection);
+
+                let origin = locations.get(query.of).map_err(search_failed)?
+                    .ok_or((StatusCode::NOT_FOUND, Json(json!({"error": "Location not found"}))))?;
+                if origin.position().is_none() {
+                    return Err(no_coordinates());
+                }
+
+                let nearby = locations.nearby(&origin, radius, page).map_err(search_failed)?;
+                let items = nearby.iter().map(|location| fields.project(location, &["distance"])).collect();
+                Ok((StatusCode::OK, Json(Paginated::new(items, page))))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Measure the distance between two locations
+    ///
+    /// Requires role READER or higher. The straight line distance in light years, regardless of stargates.
+    #[utoipa::path(
+        get,
+        path = "/locations/{location_id}/distance/{other_id}",
+        tag = "locations",
+        params(
+            ("location_id" = i32, Path, description = "Id of the location measured from"),
+            ("other_id" = i32, Path, description = "Id of the location measured to")
+        ),
+        responses(
+            (status = 200, description = "Distance between the locations", body = Distance),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Location not found", body = ErrorBody),
+            (status = 422, description = "A location has no coordinates", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while measuring distance", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn location_distance_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, i32)>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (location_id, other_id) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut locations = locationsDB::new(connection);
+
+                let mut read = |id: i32| match locations.get(id) {
+                    Ok(Some(location)) => Ok(location),
+                    Ok(None) => Err((StatusCode::NOT_FOUND, Json(json!({"error": "Location not found"})))),
+                    Err(err) => {
+                        error!("Error reading location: {:?}", err);
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to measure distance"}))))
+                    }
+                };
+
+                let (from, to) = (read(location_id)?, read(other_id)?);
+                let light_years = from.distance_to(&to).ok_or_else(no_coordinates)?;
+
+                Ok((StatusCode::OK, Json(Distance { from_location_id: location_id, to_location_id: other_id, light_years })))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// List the sovereignty history of a location
+    ///
+    /// Requires role READER or higher. Every change of the empire holding sovereignty over the location, newest first.
+    /// '?fields=' limits the fields of each change.
+    #[utoipa::path(
+        get,
+        path = "/locations/{location_id}/sovereignty",
+        tag = "locations",
+        params(("location_id" = i32, Path, description = "Id of the location"), PageQuery, FieldsQuery),
+        responses(
+            (status = 200, description = "Page of sovereignty changes", body = SovereigntyChangePage),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Location not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing sovereignty changes", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn sovereignty_history_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+        query: Result<Query<PageQuery>, QueryRejection>,
+        fields: Result<Query<FieldsQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (location_id, ) = path.0;
+        let Query(query) = query.map_err(invalid_query)?;
+        let page = query.page()?;
+        let fields = fields.map_err(invalid_query)?.parse(&SovereigntyChange::FIELDS)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let listing_failed = |err: diesel::result::Error| {
+                    error!("Error listing sovereignty changes: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list sovereignty changes"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut locations = locationsDB::new(connection);
+
+                if locations.get(location_id).map_err(listing_failed)?.is_none() {
+                    return Err((StatusCode::NOT_FOUND, Json(json!({"error": "Location not found"}))));
+                }
+
+                let changes = locations.sovereignty_history(location_id, page).map_err(listing_failed)?;
+                let items = changes.iter().map(|change| fields.project(change, &[])).collect();
+                Ok((StatusCode::OK, Json(Paginated::new(items, page))))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// List the empires at home in a location
+    ///
+    /// Requires role READER or higher. Ordered by id. '?include=' embeds any of 'location', 'ships' and 'players', '?fields=' limits the fields of each empire.
+    #[utoipa::path(
+        get,
+        path = "/locations/{location_id}/empires",
+        tag = "locations",
+        params(("location_id" = i32, Path, description = "Id of the location"), PageQuery, IncludeQuery, FieldsQuery),
+        responses(
+            (status = 200, description = "Page of empires", body = EmpireDocumentPage),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Location not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing empires", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_location_empires_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+        query: Result<Query<PageQuery>, QueryRejection>,
+        include: Result<Query<IncludeQuery>, QueryRejection>,
+        fields: Result<Query<FieldsQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (location_id, ) = path.0;
+        let Query(query) = query.map_err(invalid_query)?;
+        let page = query.page()?;
+        let included = include.map_err(invalid_query)?.parse(&EmpireDocument::INCLUDES)?;
+        let fields = fields.map_err(invalid_query)?.parse(&Empire::FIELDS)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let listing_failed = |err: diesel::result::Error| {
+                    error!("Error listing location empires: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list empires"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                if locationsDB::new(connection).get(location_id).map_err(listing_failed)?.is_none() {
+                    return Err((StatusCode::NOT_FOUND, Json(json!({"error": "Location not found"}))));
+                }
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let items = EmpiresTable::new(connection).list_by_location(location_id, page, &empire_fields(&fields, &included)).map_err(listing_failed)?;
+                let documents = empire_documents(&shared_state, items, &included).map_err(listing_failed)?;
+                let items = documents.iter().map(|document| fields.project(document, &included)).collect();
+                Ok((StatusCode::OK, Json(Paginated::new(items, page))))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// List the players at a location
+    ///
+    /// Requires role READER or higher. Ordered by id. '?include=' embeds any of 'ship' and 'location', '?fields=' limits the fields of each player.
+    #[utoipa::path(
+        get,
+        path = "/locations/{location_id}/players",
+        tag = "locations",
+        params(("location_id" = i32, Path, description = "Id of the location"), PageQuery, IncludeQuery, FieldsQuery),
+        responses(
+            (status = 200, description = "Page of players", body = PlayerDocumentPage),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Location not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing players", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_location_players_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+        query: Result<Query<PageQuery>, QueryRejection>,
+        include: Result<Query<IncludeQuery>, QueryRejection>,
+        fields: Result<Query<FieldsQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (location_id, ) = path.0;
+        let Query(query) = query.map_err(invalid_query)?;
+        let page = query.page()?;
+        let included = include.map_err(invalid_query)?.parse(&PlayerDocument::INCLUDES)?;
+        let fields = fields.map_err(invalid_query)?.parse(&Player::FIELDS)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let listing_failed = |err: diesel::result::Error| {
+                    error!("Error listing location players: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list players"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                if locationsDB::new(connection).get(location_id).map_err(listing_failed)?.is_none() {
+                    return Err((StatusCode::NOT_FOUND, Json(json!({"error": "Location not found"}))));
+                }
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let items = PlayersTable::new(connection).list_by_location(location_id, page).map_err(listing_failed)?;
+                let documents = player_documents(&shared_state, items, &included).map_err(listing_failed)?;
+                let items = documents.iter().map(|document| fields.project(document, &included)).collect();
+                Ok((StatusCode::OK, Json(Paginated::new(items, page))))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// List the claims of empires on a location
+    ///
+    /// Requires role READER or higher. Live and released claims, newest first. At most one claim is live, the one of the sovereign empire.
+    /// '?fields=' limits the fields of each claim.
+    #[utoipa::path(
+        get,
+        path = "/locations/{location_id}/claims",
+        tag = "locations",
+        params(("location_id" = i32, Path, description = "Id of the location"), PageQuery, FieldsQuery),
+        responses(
+            (status = 200, description = "Page of territory claims", body = TerritoryClaimPage),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Location not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing claims", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_claims_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+        query: Result<Query<PageQuery>, QueryRejection>,
+        fields: Result<Query<FieldsQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (location_id, ) = path.0;
+        let Query(query) = query.map_err(invalid_query)?;
+        let page = query.page()?;
+        let fields = fields.map_err(invalid_query)?.parse(&TerritoryClaim::FIELDS)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let listing_failed = |err: diesel::result::Error| {
+                    error!("Error listing territory claims: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list claims"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut locations = locationsDB::new(connection);
+
+                if locations.get(location_id).map_err(listing_failed)?.is_none() {
+                    return Err((StatusCode::NOT_FOUND, Json(json!({"error": "Location not found"}))));
+                }
+
+                let territory_claims = locations.claims(location_id, page).map_err(listing_failed)?;
+                let items = territory_claims.iter().map(|claim| fields.project(claim, &[])).collect();
+                Ok((StatusCode::OK, Json(Paginated::new(items, page))))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    const DUPLICATE_LOCATION: &str = "A location with this star system and area already exists";
+
+    // Locations that have not been deleted are unique by star system and area
+    fn duplicate_location() -> (StatusCode, Json<Value>) {
+        (StatusCode::CONFLICT, Json(json!({"error": DUPLICATE_LOCATION})))
+    }
+
+    fn no_coordinates() -> (StatusCode, Json<Value>) {
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": "Location has no coordinates"})))
+    }
+
+    // Status and message of an item that failed in a bulk request
+    fn bulk_error(err: &diesel::result::Error) -> (StatusCode, String) {
+        match err {
+            diesel::result::Error::NotFound => (StatusCode::NOT_FOUND, "Location not found".to_string()),
+            diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => (StatusCode::CONFLICT, DUPLICATE_LOCATION.to_string()),
+            diesel::result::Error::DatabaseError(DatabaseErrorKind::CheckViolation, _) => (StatusCode::UNPROCESSABLE_ENTITY, "Location has incomplete coordinates or a security status out of range".to_string()),
+            err => {
+                error!("Error applying bulk location item: {:?}", err);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Failed to apply location".to_string())
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use axum::{
+            body::Body,
+            http::{Request, StatusCode}
+        };
+        use serde_json::json;
+        use tower::ServiceExt;
+        use crate::{
+            audit::{
+                model::AuditQuery,
+                service::service::AuditTable
+            },
+            common::{
+                db::create_shared_connection_pool,
+                pagination::Page,
+                util::load_environment_variable,
+                security::hash_password
+            },
+            locations::{
+                model::UpsertLocation,
+                service::service::LocationsTable
+            },
+            users::{
+                model::UpsertUser,
+                service::service::UsersTable
+            },
+            locations::router::router::locations_route
+        };
+        use crate::common::db::ConnectionPool;
+        use crate::common::security::generate_token;
+        use crate::empires::{model::UpsertEmpire, service::service::EmpiresTable};
+        use crate::users::model::UserRole;
+
+        // Helper method utilized to create user with a specific role and return the associated bearer token in one line of code
+        pub fn create_user_and_generate_token(connection_pool: ConnectionPool, email: &str, user_role: UserRole) -> Result<String, jsonwebtoken::errors::Error> {
+
+            // Only email and role are mutable as password and fullname has no constraints
+            let mut new_user = UpsertUser {
+                email: email.to_string(),
+                role: user_role.to_string(),
+                password: "StålGardinerFunkerFjell53".to_string(),
+                fullname: "Josef Stålhard".to_string()
+            };
+
+            // Hash the password
+            hash_password(&mut new_user).expect("Hash failed");
+
+            // Perform the user creation
+            let create_user_result = {
+                let connection = connection_pool.pool.get().expect("Failed to get connection");
+                UsersTable::new(connection).create(new_user.clone())
+            };
+
+            // Generate the bearer token
+            generate_token(&create_user_result.unwrap())
+        }
+
+        #[tokio::test]
+        async fn post_locations_returns_201_for_authorized_user_with_write_access() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 1);
+            let service = locations_route(connection_pool.clone());
+
+            // Create user with role WRITER and generate associated bearer token
+            let bearer_token = create_user_and_generate_token(connection_pool, "stål.hard.russer@ugreit.ru", UserRole::WRITER);
+
+            let request_body = UpsertLocation {
+                star_system: "Fountain".to_string(),
+                area: "Y-C3EQ".to_string(),
+                ..UpsertLocation::default()
+            };
+
+            // Create a request with the above data as payload
+            let request = Request::builder()
+                .uri("/locations")
+                .method("POST")
+                .header("content-type", "application/json")
+                .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
+                .body(Body::from(serde_json::to_string(&request_body).unwrap()))
+                .unwrap();
+
+            // Send the request through the service
+            let response = service
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            // Assert that the response status is 201
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+
+        #[tokio::test]
+        async fn location_writes_leave_the_sovereign_empire_to_territory_claims() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let service = locations_route(connection_pool.clone());
+
+            let bearer_token = create_user_and_generate_token(connection_pool.clone(), "grensetvist@ugreit.ru", UserRole::EDITOR).unwrap();
+
+            let request = |method: &str, uri: String, body: serde_json::Value| Request::builder()
+                .uri(uri)
+                .method(method)
+                .header("content-type", "application/json")
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .body(Body::from(body.to_string()))
+                .unwrap();
+
+            let response = service.clone().oneshot(request("POST", "/locations".to_string(), json!({"star_system": "Cloud Ring", "area": "FD-MLJ", "sovereign_empire_id": 999999}))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let created_location: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(created_location["sovereign_empire_id"], json!(null));
+            let location_id = created_location["id"].as_i64().unwrap() as i32;
+
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut empire_db = EmpiresTable::new(connection);
+            let holder = empire_db.create(UpsertEmpire {
+                name: "Grensevakten".to_string(),
+                slogan: "Ingen slipper forbi".to_string(),
+                location_id,
+                description: "Holds the border".to_string(),
+            }, None).expect("Create empire failed");
+            empire_db.claim(holder.id, location_id).expect("Claim failed");
+            drop(empire_db);
+
+            // Replacing the location keeps the holder of the claim
+            let response = service.clone().oneshot(request("PUT", format!("/locations/{}", location_id), json!({"star_system": "Cloud Ring", "area": "FD-MLJ", "sovereign_empire_id": null}))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            assert_eq!(serde_json::from_slice::<serde_json::Value>(&body).unwrap()["sovereign_empire_id"], json!(holder.id));
+
+            let response = service.oneshot(request("PATCH", format!("/locations/{}", location_id), json!({"sovereign_empire_id": null}))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        }
+
+        #[tokio::test]
+        async fn get_locations_returns_401_for_deleted_user() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 1);
+            let service = locations_route(connection_pool.clone());
+
+            // The token is still valid after its user has been deleted
+            let bearer_token = create_user_and_generate_token(connection_pool.clone(), "avskiltet@ugreit.ru", UserRole::READER);
+            {
+                let connection = connection_pool.pool.get().expect("Failed to get connection");
+                let mut user_db = UsersTable::new(connection);
+                let user = user_db.get_by_email("avskiltet@ugreit.ru".to_string()).unwrap().unwrap();
+                user_db.delete(user.id, None).expect("Delete user failed");
+            }
+
+            let request = Request::builder()
+                .uri("/locations/1")
+                .method("GET")
+                .header("Authorization", format!("Bearer {}", bearer_token.unwrap()))
+                .body(Body::empty())
+                .unwrap();
+
+            let response = service
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["error"], "User no longer exists");
+        }
+
+        #[tokio::test]
+        async fn post_locations_returns_401_for_unauthorized_user_without_write_access() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 1);
+            let service = locations_route(connection_pool.clone());
+
+            // Create user with role READER and generate associated bearer token
+            let bearer_token = create_user_and_generate_token(connection_pool, "myk.og.ekkel.russer@put.in", UserRole::READER);
+
+            let request_body = UpsertLocation {
+                star_system: "Fountain".to_string(),
+                area: "PF-346".to_string(),
+                ..UpsertLocation::default()
+            };
+
+            // Create a request with the above data as payload
+            let request = Request::builder()
+                .uri("/locations")
+                .method("POST")
+                .header("content-type", "application/json")
+                .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
+                .body(Body::from(serde_json::to_string(&request_body).unwrap()))
+                .unwrap();
+
+            // Send the request through the service
+            let response = service
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            // Assert that the response status is 401
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        #[tokio::test]
+        async fn put_locations_returns_200_for_authorized_user_with_edit_access() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
+            let service = locations_route(connection_pool.clone());
+
+            // Create user with role WRITER and generate associated bearer token
+            let bearer_token = create_user_and_generate_token(connection_pool, "dagfinnkuk@blåfjelletsvenner.no", UserRole::EDITOR);
+
+            let request_body = UpsertLocation {
+                star_system: "Fountain".to_string(),
+                area: "6SB-BN".to_string(),
+                ..UpsertLocation::default()
+            };
+
+            // Create a new location with the above data
+            let created_location = location_db.create(request_body.clone()).expect("Create location failed");
+
+            // Assert equality
+            assert_eq!(request_body.star_system, created_location.star_system);
+            assert_eq!(request_body.area, created_location.area);
+
+            let updated_request_body = UpsertLocation {
+                star_system: "Kador".to_string(),
+                area: "KVN-36".to_string(),
+                ..UpsertLocation::default()
+            };
+
+            // Create a request with the above data as payload
+            let request = Request::builder()
+                .uri(format!("/locations/{}", created_location.id))
+                .method("PUT")
+                .header("content-type", "application/json")
+                .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
+                .body(Body::from(serde_json::to_string(&updated_request_body).unwrap()))
+                .unwrap();
+
+            // Send the request through the service
+            let response = service
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            // Assert that the response status is 200
+            assert_eq!(response.status(), StatusCode::OK);
+
+            // Extract body from response
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+            // Construct JSON consisting of expected payload
+            let expected_response = json!({
+                "id": created_location.id,
+                "area": updated_request_body.area,
+                "star_system_id": location_db.get(created_location.id).unwrap().unwrap().star_system_id,
+                "star_system": updated_request_body.star_system,
+                "created_at": created_location.created_at,
+                "updated_at": response_json["updated_at"],
+                "x": null,
+                "y": null,
+                "z": null,
+                "security_status": 0.0,
+                "sovereign_empire_id": null
+            });
+
+            // Assert equality
+            assert_eq!(response_json, expected_response);
+
+            // Assert that the trigger advanced the modification time
+            let updated_at: chrono::DateTime<chrono::Utc> = serde_json::from_value(response_json["updated_at"].clone()).unwrap();
+            assert!(updated_at > created_location.updated_at);
+        }
+
+        #[tokio::test]
+        async fn put_locations_returns_401_for_unauthorized_user_without_edit_access() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
+            let service = locations_route(connection_pool.clone());
+
+            // Create user with role WRITER and generate associated bearer token
+            let bearer_token = create_user_and_generate_token(connection_pool, "necromancer@gpf.no", UserRole::WRITER);
+
+            let request_body = UpsertLocation {
+                star_system: "Fountain".to_string(),
+                area: "RH0-EG".to_string(),
+                ..UpsertLocation::default()
+            };
+
+            // Create a new location with the above data
+            let created_location = location_db.create(request_body.clone()).expect("Create location failed");
+
+            // Assert equality
+            assert_eq!(request_body.star_system, created_location.star_system);
+            assert_eq!(request_body.area, created_location.area);
+
+            let updated_request_body = UpsertLocation {
+                star_system: "Kador".to_string(),
+                area: "9-4RP2".to_string(),
+                ..UpsertLocation::default()
+            };
+
+            // Create a request with the above data as payload
+            let request = Request::builder()
+                .uri(format!("/locations/{}", created_location.id))
+                .method("PUT")
+                .header("content-type", "application/json")
+                .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
+                .body(Body::from(serde_json::to_string(&updated_request_body).unwrap()))
+                .unwrap();
+
+            // Send the request through the service
+            let response = service
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            // Assert that the response status is 401
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        #[tokio::test]
+        async fn patch_locations_returns_200_and_changes_only_patched_fields() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
+            let service = locations_route(connection_pool.clone());
+
+            // Create user with role EDITOR and generate associated bearer token
+            let bearer_token = create_user_and_generate_token(connection_pool, "lappeteppe@sydvest.no", UserRole::EDITOR);
+
+            let request_body = UpsertLocation {
+                star_system: "Fountain".to_string(),
+                area: "UEXO-Z".to_string(),
+                ..UpsertLocation::default()
+            };
+
+            // Create a new location with the above data
+            let created_location = location_db.create(request_body.clone()).expect("Create location failed");
+
+            // Create a request with a merge patch touching only the area
+            let request = Request::builder()
+                .uri(format!("/locations/{}", created_location.id))
+                .method("PATCH")
+                .header("content-type", "application/merge-patch+json")
+                .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
+                .body(Body::from(json!({"area": "Pegasus"}).to_string()))
+                .unwrap();
+
+            // Send the request through the service
+            let response = service
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            // Assert that the response status is 200
+            assert_eq!(response.status(), StatusCode::OK);
+
+            // Extract body from response
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+            // Construct JSON consisting of expected payload
+            let expected_response = json!({
+                "id": created_location.id,
+                "area": "Pegasus",
+                "star_system_id": created_location.star_system_id,
+                "star_system": request_body.star_system,
+                "created_at": created_location.created_at,
+                "updated_at": response_json["updated_at"],
+                "x": null,
+                "y": null,
+                "z": null,
+                "security_status": 0.0,
+                "sovereign_empire_id": null
+            });
+
+            // Assert equality
+            assert_eq!(response_json, expected_response);
+        }
+
+        #[tokio::test]
+        async fn patch_locations_returns_422_on_unknown_field() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
+            let service = locations_route(connection_pool.clone());
+
+            // Create user with role EDITOR and generate associated bearer token
+            let bearer_token = create_user_and_generate_token(connection_pool, "flikkesaker@sydvest.no", UserRole::EDITOR);
+
+            let created_location = location_db.create(UpsertLocation {
+                star_system: "Fountain".to_string(),
+                area: "M-OEE8".to_string(),
+                ..UpsertLocation::default()
+            }).expect("Create location failed");
+
+            // Create a request with a merge patch naming a field that locations do not have
+            let request = Request::builder()
+                .uri(format!("/locations/{}", created_location.id))
+                .method("PATCH")
+                .header("content-type", "application/merge-patch+json")
+                .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
+                .body(Body::from(json!({"galaxy": "Andromeda"}).to_string()))
+                .unwrap();
+
+            // Send the request through the service
+            let response = service
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            // Assert that the response status is 422
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        }
+
+        #[tokio::test]
+        async fn get_locations_returns_200_for_authorized_user_with_read_access() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
+            let service = locations_route(connection_pool.clone());
+
+            let bearer_token = create_user_and_generate_token(connection_pool, "duvetdet@gjerrigknark.no", UserRole::READER);
+
+            let request_body = UpsertLocation {
+                star_system: "Fountain".to_string(),
+                area: "E-VKJV".to_string(),
+                ..UpsertLocation::default()
+            };
+
+            // Create a new location with the above data
+            let created_location = location_db.create(request_body.clone()).expect("Create location failed");
+
+            // Create a request with the ID associated with our newly inserted row
+            let request = Request::builder()
+                .uri(format!("/locations/{}", created_location.id))
+                .method("GET")
+                .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
+                .body(Body::empty())
+                .unwrap();
+
+            // Send the request through the service
+            let response = service
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            // Assert that the response status is 200
+            assert_eq!(response.status(), StatusCode::OK);
+
+            // Extract body from response
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+            // Construct JSON consisting of expected payload
+            let expected_response = json!({
+                "id": created_location.id,
+                "area": request_body.area,
+                "star_system_id": created_location.star_system_id,
+                "star_system": request_body.star_system,
+                "created_at": created_location.created_at,
+                "updated_at": created_location.updated_at,
+                "x": null,
+                "y": null,
+                "z": null,
+                "security_status": 0.0,
+                "sovereign_empire_id": null
+            });
+
+            // Assert equality
+            assert_eq!(response_json, expected_response);
+        }
+
+        #[tokio::test]
+        async fn get_locations_returns_etag_and_304_on_matching_if_none_match() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
+            let service = locations_route(connection_pool.clone());
+
+            let bearer_token = create_user_and_generate_token(connection_pool, "lurvete@gjerrigknark.no", UserRole::READER).unwrap();
+
+            let created_location = location_db.create(UpsertLocation {
+                star_system: "Fountain".to_string(),
+                area: "4-07MU".to_string(),
+                ..UpsertLocation::default()
+            }).expect("Create location failed");
+
+            // Read the location once to obtain its entity tag
+            let request = Request::builder()
+                .uri(format!("/locations/{}", created_location.id))
+                .method("GET")
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .body(Body::empty())
+                .unwrap();
+
+            let response = service.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let etag = response.headers().get("etag").expect("Missing ETag").clone();
+
+            // Read it again, conditioned on the entity tag
+            let request = Request::builder()
+                .uri(format!("/locations/{}", created_location.id))
+                .method("GET")
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .header("If-None-Match", etag.clone())
+                .body(Body::empty())
+                .unwrap();
+
+            let response = service.oneshot(request).await.unwrap();
+
+            // Assert that the unchanged location is not sent again
+            assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+            assert_eq!(response.headers().get("etag").unwrap(), &etag);
+        }
+
+        #[tokio::test]
+        async fn put_locations_returns_412_on_stale_if_match() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
+            let service = locations_route(connection_pool.clone());
+
+            let bearer_token = create_user_and_generate_token(connection_pool, "kappløp@blåfjelletsvenner.no", UserRole::EDITOR).unwrap();
+
+            let created_location = location_db.create(UpsertLocation {
+                star_system: "Fountain".to_string(),
+                area: "I-E3TG".to_string(),
+                ..UpsertLocation::default()
+            }).expect("Create location failed");
+
+            let update = |area: &str, if_match: &str| Request::builder()
+                .uri(format!("/locations/{}", created_location.id))
+                .method("PUT")
+                .header("content-type", "application/json")
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .header("If-Match", if_match)
+                .body(Body::from(json!({"star_system": "Fountain", "area": area}).to_string()))
+                .unwrap();
+
+            // The first editor writes against the version both editors read
+            let response = service.clone().oneshot(update("Perrigen Falls", "\"1\"")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers().get("etag").unwrap(), "\"2\"");
+
+            // The second editor still holds the old version and must not overwrite the first
+            let response = service.oneshot(update("Outer Passage", "\"1\"")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+            let location = location_db.get(created_location.id).unwrap().unwrap();
+            assert_eq!(location.area, "Perrigen Falls");
+        }
+
+        #[tokio::test]
+        async fn list_locations_returns_only_rows_updated_since() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
+            let service = locations_route(connection_pool.clone());
+
+            let bearer_token = create_user_and_generate_token(connection_pool, "synkron@gjerrigknark.no", UserRole::READER).unwrap();
+
+            let created_location = location_db.create(UpsertLocation {
+                star_system: "Syndicate".to_string(),
+                area: "Poitot".to_string(),
+                ..UpsertLocation::default()
+            }).expect("Create location failed");
+
+            let list = |updated_since: chrono::DateTime<chrono::Utc>| Request::builder()
+                .uri(format!("/locations?limit=500&updated_since={}", updated_since.to_rfc3339_opts(chrono::SecondsFormat::Micros, true)))
+                .method("GET")
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .body(Body::empty())
+                .unwrap();
+
+            // The new location is part of a sync starting at its own modification time
+            let response = service.clone().oneshot(list(created_location.updated_at)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let ids: Vec<i64> = response_json["items"].as_array().unwrap().iter().map(|item| item["id"].as_i64().unwrap()).collect();
+            assert!(ids.contains(&(created_location.id as i64)));
+
+            // A sync starting after it no longer sees the location
+            let response = service.oneshot(list(created_location.updated_at + chrono::Duration::seconds(1))).await.unwrap();
+
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert!(response_json["items"].as_array().unwrap().iter().all(|item| item["id"] != created_location.id));
+        }
+
+        #[tokio::test]
+        async fn get_locations_returns_200_for_authorized_user_with_write_access() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
+            let service = locations_route(connection_pool.clone());
+
+            let bearer_token = create_user_and_generate_token(connection_pool, "kokefaktura@woodworm.org", UserRole::WRITER);
+
+            let request_body = UpsertLocation {
+                star_system: "Fountain".to_string(),
+                area: "Z-XMUC".to_string(),
+                ..UpsertLocation::default()
+            };
+
+            // Create a new location with the above data
+            let created_location = location_db.create(request_body.clone()).expect("Create location failed");
+
+            // Create a request with the ID associated with our newly inserted row
+            let request = Request::builder()
+                .uri(format!("/locations/{}", created_location.id))
+                .method("GET")
+                .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
+                .body(Body::empty())
+                .unwrap();
+
+            // Send the request through the service
+            let response = service
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            // Assert that the response status is 200
+            assert_eq!(response.status(), StatusCode::OK);
+
+            // Extract body from response
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+            // Construct JSON consisting of expected payload
+            let expected_response = json!({
+                "id": created_location.id,
+                "area": request_body.area,
+                "star_system_id": created_location.star_system_id,
+                "star_system": request_body.star_system,
+                "created_at": created_location.created_at,
+                "updated_at": created_location.updated_at,
+                "x": null,
+                "y": null,
+                "z": null,
+                "security_status": 0.0,
+                "sovereign_empire_id": null
+            });
+
+            // Assert equality
+            assert_eq!(response_json, expected_response);
+        }
+
+        #[tokio::test]
+        async fn get_locations_returns_401_for_unauthorized_user_without_read_access() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
+            let service = locations_route(connection_pool.clone());
+
+            let bearer_token = create_user_and_generate_token(connection_pool, "igor.invalidus@bogdanov.fr", UserRole::INVALID);
+
+            let request_body = UpsertLocation {
+                star_system: "Fountain".to_string(),
+                area: "T-M0FA".to_string(),
+                ..UpsertLocation::default()
+            };
+
+            // Create a new location with the above data
+            let created_location = location_db.create(request_body.clone()).expect("Create location failed");
+
+            // Create a request with the ID associated with our newly inserted row
+            let request = Request::builder()
+                .uri(format!("/locations/{}", created_location.id))
+                .method("GET")
+                .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
+                .body(Body::empty())
+                .unwrap();
+
+            // Send the request through the service
+            let response = service
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            // Assert that the response status is 401
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED)
+        }
+
+        #[tokio::test]
+        async fn get_locations_returns_404_on_non_existing_id() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let service = locations_route(connection_pool.clone());
+
+            let bearer_token = create_user_and_generate_token(connection_pool, "birdman@ifi.uio.no", UserRole::READER);
+
+            // Create a request with the aforementioned id
+            let request = Request::builder()
+                .uri(format!("/locations/{}", -666)) // Use a non-existent ID
+                .method("GET")
+                .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
+                .body(Body::empty())
+                .unwrap();
+
+            // Send the request through the service
+            let response = service
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            // Assert that the response status is 404 as there are no locations associated with the id
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+
+        #[tokio::test]
+        async fn delete_locations_returns_204_for_authorized_user_with_admin_role() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
+            let service = locations_route(connection_pool.clone());
+
+            let bearer_token = create_user_and_generate_token(connection_pool,"you.know.your.judo.well@succulentmail.gb", UserRole::ADMIN);
+
+            let request_body = UpsertLocation {
+                star_system: "Fountain".to_string(),
+                area: "8-TFDX".to_string(),
+                ..UpsertLocation::default()
+            };
+
+            // Create a new location with the above data
+            let created_location = location_db.create(request_body.clone()).expect("Create location failed");
+
+            // Create a request with the ID associated with our newly inserted row
+            let request = Request::builder()
+                .uri(format!("/locations/{}", created_location.id))
+                .method("DELETE")
+                .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
+                .body(Body::empty())
+                .unwrap();
+
+            // Send the request through the service
+            let response = service
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            // Assert that the response status is 204
+            assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+            // Attempt to retrieve the deleted location
+            let deleted_location_result = location_db.get(created_location.id);
+
+            // Assert that the Result is Ok (no error)
+            assert!(deleted_location_result.is_ok());
+
+            // Extract the Option<Location> from the Ok variant
+            let deleted_location = deleted_location_result.unwrap();
+
+            // Assert that the deleted location is None (i.e., it doesn't exist)
+            assert!(deleted_location.is_none());
+        }
+
+        #[tokio::test]
+        async fn delete_locations_records_audit_event_with_actor_and_request_id() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
+            let service = locations_route(connection_pool.clone());
+
+            let bearer_token = create_user_and_generate_token(connection_pool.clone(), "revisor@succulentmail.gb", UserRole::ADMIN);
+
+            let created_location = location_db.create(UpsertLocation {
+                star_system: "Catch".to_string(),
+                area: "Sendaya".to_string(),
+                ..UpsertLocation::default()
+            }).expect("Create location failed");
+
+            let request = Request::builder()
+                .uri(format!("/locations/{}", created_location.id))
+                .method("DELETE")
                 .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
-                .body(Body::from(serde_json::to_string(&request_body).unwrap()))
+                .header("x-request-id", "audit-delete-location")
+                .body(Body::empty())
                 .unwrap();
 
-            // Send the request through the service
             let response = service
                 .oneshot(request)
                 .await
                 .unwrap();
 
-            // Assert that the response status is 201
-            assert_eq!(response.status(), StatusCode::CREATED);
+            assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+            // The deletion is attributed to the caller and carries the deleted row as its 'before' snapshot
+            let filter = AuditQuery {
+                resource_type: Some("location".to_string()),
+                resource_id: Some(created_location.id),
+                ..AuditQuery::default()
+            };
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let events = AuditTable::new(connection).list(&filter, Page::new(None, None).unwrap()).expect("List audit events failed");
+
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].action, "delete");
+            assert_eq!(events[0].actor.as_deref(), Some("revisor@succulentmail.gb"));
+            assert_eq!(events[0].request_id.as_deref(), Some("audit-delete-location"));
+            assert_eq!(events[0].before.as_ref().unwrap()["area"], "Sendaya");
+            assert!(events[0].after.is_none());
         }
 
         #[tokio::test]
-        async fn post_locations_returns_401_for_unauthorized_user_without_write_access() {
+        async fn post_bulk_locations_returns_200_and_creates_every_location() {
             let database_url = load_environment_variable("TEST_DB");
-            let connection_pool = create_shared_connection_pool(database_url, 1);
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
             let service = locations_route(connection_pool.clone());
 
-            // Create user with role READER and generate associated bearer token
-            let bearer_token = create_user_and_generate_token(connection_pool, "myk.og.ekkel.russer@put.in", UserRole::READER);
+            let bearer_token = create_user_and_generate_token(connection_pool, "kartlegger@succulentmail.gb", UserRole::WRITER);
 
-            let request_body = UpsertLocation {
-                star_system: "Fountain".to_string(),
-                area: "The Serpent's Lair".to_string(),
-            };
+            let request_body = json!([
+                {"star_system": "Jita", "area": "The Forge"},
+                {"star_system": "Perimeter", "area": "The Forge"}
+            ]);
 
-            // Create a request with the above data as payload
             let request = Request::builder()
-                .uri("/locations")
+                .uri("/locations/bulk")
                 .method("POST")
                 .header("content-type", "application/json")
                 .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
-                .body(Body::from(serde_json::to_string(&request_body).unwrap()))
+                .body(Body::from(request_body.to_string()))
                 .unwrap();
 
-            // Send the request through the service
             let response = service
                 .oneshot(request)
                 .await
                 .unwrap();
 
-            // Assert that the response status is 401
-            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let response_body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let results = response_body["results"].as_array().unwrap();
+
+            // Every item reports 201 along with the id of its new row
+            assert_eq!(results.len(), 2);
+            for (index, result) in results.iter().enumerate() {
+                assert_eq!(result["index"], index);
+                assert_eq!(result["status"], 201);
+                let created_location = location_db.get(result["id"].as_i64().unwrap() as i32).unwrap().unwrap();
+                assert_eq!(created_location.star_system, request_body[index]["star_system"]);
+            }
         }
 
         #[tokio::test]
-        async fn put_locations_returns_200_for_authorized_user_with_edit_access() {
+        async fn put_bulk_locations_rolls_back_in_transactional_mode_and_reports_each_item_in_partial_mode() {
             let database_url = load_environment_variable("TEST_DB");
             let connection_pool = create_shared_connection_pool(database_url, 2);
             let connection = connection_pool.pool.get().expect("Failed to get connection");
             let mut location_db = LocationsTable::new(connection);
             let service = locations_route(connection_pool.clone());
 
-            // Create user with role WRITER and generate associated bearer token
-            let bearer_token = create_user_and_generate_token(connection_pool, "dagfinnkuk@blåfjelletsvenner.no", UserRole::EDITOR);
+            let bearer_token = create_user_and_generate_token(connection_pool, "omdoeper@succulentmail.gb", UserRole::EDITOR).unwrap();
 
-            let request_body = UpsertLocation {
-                star_system: "Fountain".to_string(),
-                area: "The Serpent's Lair".to_string(),
-            };
+            let created_location = location_db.create(UpsertLocation {
+                star_system: "Rens".to_string(),
+                area: "Heimatar".to_string(),
+                ..UpsertLocation::default()
+            }).expect("Create location failed");
 
-            // Create a new location with the above data
-            let created_location = location_db.create(request_body.clone()).expect("Create location failed");
+            // The second item refers to a location that does not exist
+            let request_body = json!([
+                {"id": created_location.id, "star_system": "Rens", "area": "Frarn"},
+                {"id": -666, "star_system": "Hek", "area": "Metropolis"}
+            ]);
 
-            // Assert equality
-            assert_eq!(request_body.star_system, created_location.star_system);
-            assert_eq!(request_body.area, created_location.area);
+            let request = Request::builder()
+                .uri("/locations/bulk")
+                .method("PUT")
+                .header("content-type", "application/json")
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .body(Body::from(request_body.to_string()))
+                .unwrap();
 
-            let updated_request_body = UpsertLocation {
-                star_system: "Kador".to_string(),
-                area: "The Crimson Expanse".to_string(),
-            };
+            let response = service.clone()
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            // The failing item is reported and the update of the first one is rolled back
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            assert_eq!(serde_json::from_slice::<serde_json::Value>(&body).unwrap(), json!({"error": "Location not found", "index": 1}));
+            assert_eq!(location_db.get(created_location.id).unwrap().unwrap().area, "Heimatar");
 
-            // Create a request with the above data as payload
             let request = Request::builder()
-                .uri(format!("/locations/{}", created_location.id))
+                .uri("/locations/bulk?mode=partial")
                 .method("PUT")
                 .header("content-type", "application/json")
-                .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
-                .body(Body::from(serde_json::to_string(&updated_request_body).unwrap()))
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .body(Body::from(request_body.to_string()))
                 .unwrap();
 
-            // Send the request through the service
             let response = service
                 .oneshot(request)
                 .await
                 .unwrap();
 
-            // Assert that the response status is 200
-            assert_eq!(response.status(), StatusCode::OK);
-
-            // Extract body from response
+            // In partial mode the first item is applied regardless of the second
+            assert_eq!(response.status(), StatusCode::MULTI_STATUS);
             let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-            let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
-
-            // Construct JSON consisting of expected payload
-            let expected_response = json!({
-                "id": created_location.id,
-                "area": updated_request_body.area,
-                "star_system": updated_request_body.star_system
-            });
-
-            // Assert equality
-            assert_eq!(response_json, expected_response);
+            assert_eq!(serde_json::from_slice::<serde_json::Value>(&body).unwrap(), json!({"results": [
+                {"index": 0, "status": 200, "id": created_location.id},
+                {"index": 1, "status": 404, "error": "Location not found"}
+            ]}));
+            assert_eq!(location_db.get(created_location.id).unwrap().unwrap().area, "Frarn");
         }
 
         #[tokio::test]
-        async fn put_locations_returns_401_for_unauthorized_user_without_edit_access() {
+        async fn post_bulk_locations_returns_422_for_invalid_items_before_writing_any() {
             let database_url = load_environment_variable("TEST_DB");
-            let connection_pool = create_shared_connection_pool(database_url, 2);
-            let connection = connection_pool.pool.get().expect("Failed to get connection");
-            let mut location_db = LocationsTable::new(connection);
+            let connection_pool = create_shared_connection_pool(database_url, 1);
             let service = locations_route(connection_pool.clone());
 
-            // Create user with role WRITER and generate associated bearer token
-            let bearer_token = create_user_and_generate_token(connection_pool, "necromancer@gpf.no", UserRole::WRITER);
-
-            let request_body = UpsertLocation {
-                star_system: "Fountain".to_string(),
-                area: "The Serpent's Lair".to_string(),
-            };
-
-            // Create a new location with the above data
-            let created_location = location_db.create(request_body.clone()).expect("Create location failed");
-
-            // Assert equality
-            assert_eq!(request_body.star_system, created_location.star_system);
-            assert_eq!(request_body.area, created_location.area);
+            let bearer_token = create_user_and_generate_token(connection_pool, "feltmaaler@succulentmail.gb", UserRole::WRITER).unwrap();
 
-            let updated_request_body = UpsertLocation {
-                star_system: "Kador".to_string(),
-                area: "The Crimson Expanse".to_string(),
-            };
+            // The second item has a security status out of range
+            let request_body = json!([
+                {"star_system": "Kor-Azor", "area": "Kor-Azor Prime"},
+                {"star_system": "Kor-Azor", "area": "Arzad", "security_status": 2.0}
+            ]);
 
-            // Create a request with the above data as payload
-            let request = Request::builder()
-                .uri(format!("/locations/{}", created_location.id))
-                .method("PUT")
+            let bulk_request = |uri: &str| Request::builder()
+                .uri(uri)
+                .method("POST")
                 .header("content-type", "application/json")
-                .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
-                .body(Body::from(serde_json::to_string(&updated_request_body).unwrap()))
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .body(Body::from(request_body.to_string()))
                 .unwrap();
 
-            // Send the request through the service
+            let response = service.clone()
+                .oneshot(bulk_request("/locations/bulk"))
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            assert_eq!(serde_json::from_slice::<serde_json::Value>(&body).unwrap(), json!({"error": "'security_status' must be between -1.0 and 1.0", "index": 1}));
+
             let response = service
-                .oneshot(request)
+                .oneshot(bulk_request("/locations/bulk?mode=partial"))
                 .await
                 .unwrap();
 
-            // Assert that the response status is 401
-            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            // The valid item was not written by the rejected request, so it is created now rather than reported as a duplicate
+            assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let results = serde_json::from_slice::<serde_json::Value>(&body).unwrap()["results"].clone();
+            assert_eq!(results[0]["status"], 201);
+            assert_eq!(results[1], json!({"index": 1, "status": 422, "error": "'security_status' must be between -1.0 and 1.0"}));
         }
 
         #[tokio::test]
-        async fn get_locations_returns_200_for_authorized_user_with_read_access() {
+        async fn get_export_locations_streams_csv_when_accepted() {
             let database_url = load_environment_variable("TEST_DB");
             let connection_pool = create_shared_connection_pool(database_url, 2);
             let connection = connection_pool.pool.get().expect("Failed to get connection");
             let mut location_db = LocationsTable::new(connection);
             let service = locations_route(connection_pool.clone());
 
-            let bearer_token = create_user_and_generate_token(connection_pool, "duvetdet@gjerrigknark.no", UserRole::READER);
-
-            let request_body = UpsertLocation {
-                star_system: "Fountain".to_string(),
-                area: "The Serpent's Lair".to_string(),
-            };
+            let bearer_token = create_user_and_generate_token(connection_pool, "eksportoer@succulentmail.gb", UserRole::READER);
 
-            // Create a new location with the above data
-            let created_location = location_db.create(request_body.clone()).expect("Create location failed");
+            let created_location = location_db.create(UpsertLocation {
+                star_system: "Tama".to_string(),
+                area: "The Citadel".to_string(),
+                ..UpsertLocation::default()
+            }).expect("Create location failed");
 
-            // Create a request with the ID associated with our newly inserted row
             let request = Request::builder()
-                .uri(format!("/locations/{}", created_location.id))
+                .uri("/locations/export")
                 .method("GET")
+                .header("Accept", "text/csv")
                 .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
                 .body(Body::empty())
                 .unwrap();
 
-            // Send the request through the service
             let response = service
                 .oneshot(request)
                 .await
                 .unwrap();
 
-            // Assert that the response status is 200
             assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers().get("content-type").unwrap(), "text/csv");
 
-            // Extract body from response
             let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-            let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
-
-            // Construct JSON consisting of expected payload
-            let expected_response = json!({
-                "id": created_location.id,
-                "area": request_body.area,
-                "star_system": request_body.star_system
-            });
+            let csv = String::from_utf8(body.to_vec()).unwrap();
 
-            // Assert equality
-            assert_eq!(response_json, expected_response);
+            // A single header followed by one record per location, the new one included
+            assert!(csv.starts_with("id,star_system_id,star_system,area,created_at,updated_at,x,y,z,security_status,sovereign_empire_id\n"));
+            assert_eq!(csv.matches("star_system,").count(), 1);
+            assert!(csv.contains(&format!("\n{},{},Tama,The Citadel,", created_location.id, created_location.star_system_id)));
         }
 
         #[tokio::test]
-        async fn get_locations_returns_200_for_authorized_user_with_write_access() {
+        async fn post_import_locations_validates_every_row_before_writing() {
             let database_url = load_environment_variable("TEST_DB");
             let connection_pool = create_shared_connection_pool(database_url, 2);
             let connection = connection_pool.pool.get().expect("Failed to get connection");
             let mut location_db = LocationsTable::new(connection);
             let service = locations_route(connection_pool.clone());
 
-            let bearer_token = create_user_and_generate_token(connection_pool, "kokefaktura@woodworm.org", UserRole::WRITER);
+            let bearer_token = create_user_and_generate_token(connection_pool, "importoer@succulentmail.gb", UserRole::WRITER).unwrap();
 
-            let request_body = UpsertLocation {
-                star_system: "Fountain".to_string(),
-                area: "The Serpent's Lair".to_string(),
-            };
+            let import = |uri: &str, body: String| Request::builder()
+                .uri(uri)
+                .method("POST")
+                .header("content-type", "application/x-ndjson")
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .body(Body::from(body))
+                .unwrap();
 
-            // Create a new location with the above data
-            let created_location = location_db.create(request_body.clone()).expect("Create location failed");
+            // The second row exceeds the length of the 'area' column
+            let invalid_rows = format!("{}\n{}\n", json!({"star_system": "Amamake", "area": "Heimatar"}), json!({"star_system": "Vard", "area": "x".repeat(101)}));
+            let response = service.clone().oneshot(import("/locations/import", invalid_rows)).await.unwrap();
 
-            // Create a request with the ID associated with our newly inserted row
-            let request = Request::builder()
-                .uri(format!("/locations/{}", created_location.id))
-                .method("GET")
-                .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
-                .body(Body::empty())
-                .unwrap();
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            assert_eq!(serde_json::from_slice::<serde_json::Value>(&body).unwrap()["rows"], json!([{"row": 2, "error": "'area' must be at most 100 characters"}]));
 
-            // Send the request through the service
-            let response = service
-                .oneshot(request)
-                .await
-                .unwrap();
+            // A dry run of valid rows reports them without writing
+            let valid_rows = format!("{}\n{}\n", json!({"star_system": "Amamake", "area": "Heimatar"}), json!({"star_system": "Vard", "area": "Heimatar"}));
+            let response = service.clone().oneshot(import("/locations/import?dry_run=true", valid_rows.clone())).await.unwrap();
 
-            // Assert that the response status is 200
             assert_eq!(response.status(), StatusCode::OK);
-
-            // Extract body from response
             let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-            let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(serde_json::from_slice::<serde_json::Value>(&body).unwrap(), json!({"rows": 2, "dry_run": true, "ids": []}));
 
-            // Construct JSON consisting of expected payload
-            let expected_response = json!({
-                "id": created_location.id,
-                "area": request_body.area,
-                "star_system": request_body.star_system
-            });
+            let response = service.oneshot(import("/locations/import", valid_rows)).await.unwrap();
 
-            // Assert equality
-            assert_eq!(response_json, expected_response);
+            assert_eq!(response.status(), StatusCode::CREATED);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let ids = report["ids"].as_array().unwrap();
+            assert_eq!(ids.len(), 2);
+            assert_eq!(location_db.get(ids[1].as_i64().unwrap() as i32).unwrap().unwrap().star_system, "Vard");
         }
 
         #[tokio::test]
-        async fn get_locations_returns_401_for_unauthorized_user_without_read_access() {
+        async fn post_restore_locations_returns_200_for_authorized_user_with_admin_role() {
             let database_url = load_environment_variable("TEST_DB");
             let connection_pool = create_shared_connection_pool(database_url, 2);
             let connection = connection_pool.pool.get().expect("Failed to get connection");
             let mut location_db = LocationsTable::new(connection);
             let service = locations_route(connection_pool.clone());
 
-            let bearer_token = create_user_and_generate_token(connection_pool, "igor.invalidus@bogdanov.fr", UserRole::INVALID);
+            let bearer_token = create_user_and_generate_token(connection_pool, "angrer@succulentmail.gb", UserRole::ADMIN);
 
-            let request_body = UpsertLocation {
+            // Create a location and delete it again
+            let created_location = location_db.create(UpsertLocation {
                 star_system: "Fountain".to_string(),
-                area: "The Serpent's Lair".to_string(),
-            };
+                area: "Mannar Gate".to_string(),
+                ..UpsertLocation::default()
+            }).expect("Create location failed");
+            location_db.delete(created_location.id, None).expect("Delete location failed");
 
-            // Create a new location with the above data
-            let created_location = location_db.create(request_body.clone()).expect("Create location failed");
-
-            // Create a request with the ID associated with our newly inserted row
+            // Create a request restoring the deleted location
             let request = Request::builder()
-                .uri(format!("/locations/{}", created_location.id))
-                .method("GET")
+                .uri(format!("/locations/{}/restore", created_location.id))
+                .method("POST")
                 .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
                 .body(Body::empty())
                 .unwrap();
@@ -526,82 +2351,187 @@ pub mod router {
                 .await
                 .unwrap();
 
-            // Assert that the response status is 401
-            assert_eq!(response.status(), StatusCode::UNAUTHORIZED)
+            // Assert that the response status is 200
+            assert_eq!(response.status(), StatusCode::OK);
+
+            // Assert that the location is readable again
+            assert!(location_db.get(created_location.id).unwrap().is_some());
         }
 
         #[tokio::test]
-        async fn get_locations_returns_404_on_non_existing_id() {
+        async fn get_nearby_locations_returns_locations_within_radius_nearest_first() {
             let database_url = load_environment_variable("TEST_DB");
             let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
             let service = locations_route(connection_pool.clone());
 
-            let bearer_token = create_user_and_generate_token(connection_pool, "birdman@ifi.uio.no", UserRole::READER);
+            let bearer_token = create_user_and_generate_token(connection_pool, "astrometrist@succulentmail.gb", UserRole::READER).unwrap();
 
-            // Create a request with the aforementioned id
-            let request = Request::builder()
-                .uri(format!("/locations/{}", -666)) // Use a non-existent ID
+            let get = |uri: String| Request::builder()
+                .uri(uri)
                 .method("GET")
-                .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
+                .header("Authorization", format!("Bearer {}", bearer_token))
                 .body(Body::empty())
                 .unwrap();
 
-            // Send the request through the service
-            let response = service
-                .oneshot(request)
-                .await
+            // A beacon far from every other location, with an outpost, a station 5 and a post 9 light years away and a relay out of reach
+            let mut create_at = |area: &str, x: f64, y: f64, z: f64| location_db.create(UpsertLocation {
+                star_system: "Jove Observatory".to_string(),
+                area: area.to_string(),
+                x: Some(x),
+                y: Some(y),
+                z: Some(z),
+                ..UpsertLocation::default()
+            }).expect("Create location failed");
+            let beacon = create_at("Astrometric Beacon", 5000.0, 5000.0, 5000.0);
+            let post = create_at("Listening Post", 5000.0, 5000.0, 5009.0);
+            let station = create_at("Survey Station", 5003.0, 5004.0, 5000.0);
+            let outpost = create_at("Astrometric Outpost", 5001.0, 5001.0, 5001.0);
+            create_at("Distant Relay", 5020.0, 5000.0, 5000.0);
+
+            let uncharted = location_db.create(UpsertLocation {
+                star_system: "New Eden".to_string(),
+                area: "Uncharted Pocket".to_string(),
+                ..UpsertLocation::default()
+            }).expect("Create location failed");
+
+            let response = service.clone().oneshot(get(format!("/locations/nearby?of={}&within=10", beacon.id))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let page: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let nearby: Vec<(i64, f64)> = page["items"].as_array().unwrap().iter()
+                .map(|location| (location["id"].as_i64().unwrap(), location["distance"].as_f64().unwrap()))
+                .collect();
+            assert_eq!(nearby.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![outpost.id as i64, station.id as i64, post.id as i64]);
+            assert!((nearby[0].1 - 3f64.sqrt()).abs() < 1e-9);
+
+            let response = service.clone().oneshot(get(format!("/locations/{}/distance/{}", beacon.id, outpost.id))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let distance: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert!((distance["light_years"].as_f64().unwrap() - 3f64.sqrt()).abs() < 1e-9);
+
+            // Locations without coordinates have no distance to anything
+            let response = service.clone().oneshot(get(format!("/locations/nearby?of={}&within=10", uncharted.id))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+            let response = service.clone().oneshot(get(format!("/locations/{}/distance/{}", beacon.id, uncharted.id))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+            let response = service.oneshot(get(format!("/locations/nearby?of={}&within=-1", beacon.id))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+
+        #[tokio::test]
+        async fn claims_are_recorded_in_sovereignty_history() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
+            let service = locations_route(connection_pool.clone());
+
+            let bearer_token = create_user_and_generate_token(connection_pool.clone(), "suverenitet@succulentmail.gb", UserRole::EDITOR).unwrap();
+
+            let request = |method: &str, uri: String, body: Body| Request::builder()
+                .uri(uri)
+                .method(method)
+                .header("content-type", "application/merge-patch+json")
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .body(body)
                 .unwrap();
 
-            // Assert that the response status is 404 as there are no locations associated with the id
-            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+            let created_location = location_db.create(UpsertLocation {
+                star_system: "Metropolis".to_string(),
+                area: "Hek".to_string(),
+                security_status: 0.5,
+                ..UpsertLocation::default()
+            }).expect("Create location failed");
+            let [low_sec, null_sec] = [("Hadaugago", 0.3), ("Auner", -0.4)].map(|(area, security_status)| location_db.create(UpsertLocation {
+                star_system: "Metropolis".to_string(),
+                area: area.to_string(),
+                security_status,
+                ..UpsertLocation::default()
+            }).expect("Create location failed").id as i64);
+
+            // One empire claims the location, then releases it to another
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut empire_db = EmpiresTable::new(connection);
+            let [first, second] = ["Hek Handelshus", "Hek Havnelag"].map(|name| empire_db.create(UpsertEmpire {
+                name: name.to_string(),
+                slogan: "Kjop billig, selg dyrt".to_string(),
+                location_id: created_location.id,
+                description: "Trades in Metropolis".to_string(),
+            }, None).expect("Create empire failed").id);
+            empire_db.claim(first, created_location.id).expect("Claim failed");
+            empire_db.release(first, created_location.id).expect("Release failed");
+            empire_db.claim(second, created_location.id).expect("Claim failed");
+            drop(empire_db);
+
+            let response = service.clone().oneshot(request("GET", format!("/locations/{}/sovereignty", created_location.id), Body::empty())).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let page: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let changes: Vec<(serde_json::Value, serde_json::Value)> = page["items"].as_array().unwrap().iter()
+                .map(|change| (change["previous_empire_id"].clone(), change["empire_id"].clone()))
+                .collect();
+            assert_eq!(changes, vec![(json!(null), json!(second)), (json!(first), json!(null)), (json!(null), json!(first))]);
+
+            // Each location is listed under the class of its security status only
+            let response = service.clone().oneshot(request("GET", "/locations?security=low&limit=500".to_string(), Body::empty())).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let page: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let ids: Vec<i64> = page["items"].as_array().unwrap().iter().map(|location| location["id"].as_i64().unwrap()).collect();
+            assert!(ids.contains(&low_sec) && !ids.contains(&null_sec) && !ids.contains(&created_location.id.into()));
+
+            let response = service.oneshot(request("GET", "/locations?security=null&limit=500".to_string(), Body::empty())).await.unwrap();
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let page: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let ids: Vec<i64> = page["items"].as_array().unwrap().iter().map(|location| location["id"].as_i64().unwrap()).collect();
+            assert!(ids.contains(&null_sec) && !ids.contains(&low_sec) && !ids.contains(&created_location.id.into()));
         }
 
         #[tokio::test]
-        async fn delete_locations_returns_204_for_authorized_user_with_admin_role() {
+        async fn post_and_restore_locations_return_409_on_duplicate_star_system_and_area() {
             let database_url = load_environment_variable("TEST_DB");
             let connection_pool = create_shared_connection_pool(database_url, 2);
             let connection = connection_pool.pool.get().expect("Failed to get connection");
             let mut location_db = LocationsTable::new(connection);
             let service = locations_route(connection_pool.clone());
 
-            let bearer_token = create_user_and_generate_token(connection_pool,"you.know.your.judo.well@succulentmail.gb", UserRole::ADMIN);
+            let bearer_token = create_user_and_generate_token(connection_pool, "dobbeltgjenger@succulentmail.gb", UserRole::ADMIN).unwrap();
 
-            let request_body = UpsertLocation {
+            let duplicate = UpsertLocation {
                 star_system: "Fountain".to_string(),
-                area: "The Serpent's Lair".to_string(),
+                area: "Serpentis Prime".to_string(),
+                ..UpsertLocation::default()
             };
+            let created_location = location_db.create(duplicate.clone()).expect("Create location failed");
 
-            // Create a new location with the above data
-            let created_location = location_db.create(request_body.clone()).expect("Create location failed");
-
-            // Create a request with the ID associated with our newly inserted row
-            let request = Request::builder()
-                .uri(format!("/locations/{}", created_location.id))
-                .method("DELETE")
-                .header("Authorization", format!("Bearer {}", bearer_token.unwrap())) // Add the bearer token
-                .body(Body::empty())
-                .unwrap();
-
-            // Send the request through the service
-            let response = service
-                .oneshot(request)
-                .await
+            let post = Request::builder()
+                .uri("/locations")
+                .method("POST")
+                .header("content-type", "application/json")
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .body(Body::from(serde_json::to_string(&duplicate).unwrap()))
                 .unwrap();
 
-            // Assert that the response status is 204
-            assert_eq!(response.status(), StatusCode::NO_CONTENT);
-
-            // Attempt to retrieve the deleted location
-            let deleted_location_result = location_db.get(created_location.id);
+            let response = service.clone().oneshot(post).await.unwrap();
+            assert_eq!(response.status(), StatusCode::CONFLICT);
 
-            // Assert that the Result is Ok (no error)
-            assert!(deleted_location_result.is_ok());
+            // Once deleted, the pair is free to be taken by another location, which blocks restoring the first one
+            location_db.delete(created_location.id, None).expect("Delete location failed");
+            location_db.create(duplicate).expect("Create location failed");
 
-            // Extract the Option<Location> from the Ok variant
-            let deleted_location = deleted_location_result.unwrap();
+            let restore = Request::builder()
+                .uri(format!("/locations/{}/restore", created_location.id))
+                .method("POST")
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .body(Body::empty())
+                .unwrap();
 
-            // Assert that the deleted location is None (i.e., it doesn't exist)
-            assert!(deleted_location.is_none());
+            let response = service.oneshot(restore).await.unwrap();
+            assert_eq!(response.status(), StatusCode::CONFLICT);
         }
 
         #[tokio::test]
@@ -616,7 +2546,8 @@ pub mod router {
 
             let request_body = UpsertLocation {
                 star_system: "Fountain".to_string(),
-                area: "The Serpent's Lair".to_string(),
+                area: "Cloud Ring".to_string(),
+                ..UpsertLocation::default()
             };
 
             // Create a new location with the above data
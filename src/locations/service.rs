@@ -4,91 +4,386 @@ pub mod service {
         PgConnection,
         r2d2::{ConnectionManager, PooledConnection},
     };
+    use chrono::{DateTime, Utc};
     use crate::{
-        locations::model::{Location, UpsertLocation},
+        common::{bulk::Transactional, db::ConnectionPool, fields::FieldSet, include::group_by, pagination::Page},
+        empires::{model::TerritoryClaim, service::service::EmpiresTable},
+        locations::model::{Location, LocationChangeset, LocationDocument, LocationQuery, NearbyLocation, SecurityClass, SovereigntyChange, UpsertLocation},
+        players::service::service::PlayersTable,
+        regions::model::UNASSIGNED,
         schema
     };
 
     type PooledPg = PooledConnection<ConnectionManager<PgConnection>>;
 
+    // Each step either inserts the row or, when it exists, finds it. Rows inserted by one step are not visible to the
+    // others, hence the union of the inserted and the existing row
+    const UNASSIGNED_STAR_SYSTEM: &str = "
+        WITH inserted_region AS (
+            INSERT INTO regions (name) VALUES ($2) ON CONFLICT (name) DO NOTHING RETURNING id
+        ), region AS (
+            SELECT id FROM inserted_region UNION ALL SELECT id FROM regions WHERE name = $2
+        ), inserted_constellation AS (
+            INSERT INTO constellations (region_id, name) SELECT id, $2 FROM region ON CONFLICT (name) DO NOTHING RETURNING id
+        ), constellation AS (
+            SELECT id FROM inserted_constellation UNION ALL SELECT id FROM constellations WHERE name = $2
+        )
+        INSERT INTO star_systems (constellation_id, name) SELECT id, $1 FROM constellation ON CONFLICT (name) DO NOTHING";
+
     pub struct LocationsTable {
         connection: PooledPg,
     }
 
+    impl Transactional for LocationsTable {
+        fn connection(&mut self) -> &mut PgConnection {
+            &mut self.connection
+        }
+    }
+
     impl LocationsTable {
         pub fn new(connection: PooledPg) -> LocationsTable {
             LocationsTable { connection }
         }
 
+        // Id of the star system with the name. Star systems a location is placed in are added to the 'Unassigned' constellation unless they exist,
+        // and the 'Unassigned' region and constellation are created along with them when they have been removed
+        fn star_system_id(connection: &mut PgConnection, name: &str) -> Result<i32, diesel::result::Error> {
+            use diesel::sql_types::Text;
+            use schema::star_systems;
+
+            diesel::sql_query(UNASSIGNED_STAR_SYSTEM)
+                .bind::<Text, _>(name)
+                .bind::<Text, _>(UNASSIGNED)
+                .execute(connection)?;
+
+            star_systems::table
+                .filter(star_systems::name.eq(name))
+                .select(star_systems::id)
+                .get_result(connection)
+        }
+
+        // The location as written, read back together with the name of its star system
+        fn written(&mut self, location_id: i32) -> Result<Location, diesel::result::Error> {
+            self.get(location_id)?.ok_or(diesel::result::Error::NotFound)
+        }
+
         pub fn create(&mut self, upsert_location: UpsertLocation) -> Result<Location, diesel::result::Error> {
+            let location_id = Self::insert(&mut self.connection, &upsert_location)?;
+            self.written(location_id)
+        }
+
+        // Inserts the location and returns its id. Takes the connection so that the insert can share a transaction with
+        // writes to other tables, as when fixtures are seeded
+        pub fn insert(connection: &mut PgConnection, upsert_location: &UpsertLocation) -> Result<i32, diesel::result::Error> {
             use schema::locations;
 
-            let new_location = diesel::insert_into(locations::table)
+            let star_system_id = Self::star_system_id(connection, &upsert_location.star_system)?;
+            diesel::insert_into(locations::table)
                 .values((
-                    locations::star_system.eq(&upsert_location.star_system),
+                    locations::star_system_id.eq(star_system_id),
                     locations::area.eq(&upsert_location.area),
+                    locations::x.eq(upsert_location.x),
+                    locations::y.eq(upsert_location.y),
+                    locations::z.eq(upsert_location.z),
+                    locations::security_status.eq(upsert_location.security_status),
                 ))
-                .get_result(&mut self.connection)
-                .expect("Create location failed");
+                .returning(locations::id)
+                .get_result(connection)
+        }
 
-            Ok(new_location)
+        // Id of the location with the area in the named star system, the natural key of a location
+        pub fn find_id(connection: &mut PgConnection, star_system: &str, area: &str) -> Result<Option<i32>, diesel::result::Error> {
+            use schema::{locations, star_systems};
+
+            locations::table.inner_join(star_systems::table)
+                .filter(star_systems::name.eq(star_system))
+                .filter(locations::area.eq(area))
+                .filter(locations::deleted_at.is_null())
+                .select(locations::id)
+                .get_result(connection)
+                .optional()
         }
 
         pub fn get(&mut self, location_id: i32) -> Result<Option<Location>, diesel::result::Error> {
-            use schema::locations;
+            self.read(location_id, &FieldSet::all())
+        }
 
-            let location = locations::table.find(location_id)
+        // The location with only the columns of the fieldset read
+        pub fn read(&mut self, location_id: i32, fields: &FieldSet) -> Result<Option<Location>, diesel::result::Error> {
+            use schema::{locations, star_systems};
+
+            let location = locations::table.inner_join(star_systems::table)
+                .filter(locations::id.eq(location_id)).filter(locations::deleted_at.is_null())
+                .select(Location::columns(fields))
                 .get_result(&mut self.connection)
                 .optional()?;
 
             Ok(location)
         }
 
-        pub fn update(&mut self, location_id: i32, upsert_location: UpsertLocation) -> Result<Location, diesel::result::Error> {
+        // Rows with an id greater than 'after_id' in id order, the keyset pagination used by exports
+        pub fn list_after(&mut self, after_id: i32, limit: i64) -> Result<Vec<Location>, diesel::result::Error> {
+            use schema::{locations, star_systems};
+
+            locations::table.inner_join(star_systems::table)
+                .filter(locations::id.gt(after_id)).filter(locations::deleted_at.is_null())
+                .select(Location::columns(&FieldSet::all()))
+                .order(locations::id.asc())
+                .limit(limit)
+                .load(&mut self.connection)
+        }
+
+        // Locations among 'ids' that have not been deleted, in no particular order
+        pub fn get_many(&mut self, ids: &[i32]) -> Result<Vec<Location>, diesel::result::Error> {
+            use schema::{locations, star_systems};
+
+            locations::table.inner_join(star_systems::table)
+                .filter(locations::id.eq_any(ids))
+                .filter(locations::deleted_at.is_null())
+                .select(Location::columns(&FieldSet::all()))
+                .load(&mut self.connection)
+        }
+
+        // The subset of 'ids' referring to locations that exist and are not deleted
+        pub fn existing_ids(&mut self, ids: &[i32]) -> Result<Vec<i32>, diesel::result::Error> {
             use schema::locations;
 
-            // Check if the location exists before attempting to update
-            let existing_location = locations::table.find(location_id)
-                .get_result::<Location>(&mut self.connection);
-
-            match existing_location {
-                Ok(_) => {
-                    let updated_location = diesel::update(locations::table.find(location_id))
-                        .set((
-                            locations::star_system.eq(&upsert_location.star_system),
-                            locations::area.eq(&upsert_location.area),
-                        ))
-                        .get_result(&mut self.connection)
-                        .expect("Update location failed");
-
-                    Ok(updated_location)
-                },
-                Err(_) => Err(diesel::result::Error::NotFound)
+            locations::table
+                .filter(locations::id.eq_any(ids)).filter(locations::deleted_at.is_null())
+                .select(locations::id)
+                .load(&mut self.connection)
+        }
+
+        // Locations other than 'origin' within 'radius' light years of it, nearest first. The bounding cube of the sphere
+        // is matched against the position index before exact distances are measured
+        pub fn nearby(&mut self, origin: &Location, radius: f64, page: Page) -> Result<Vec<NearbyLocation>, diesel::result::Error> {
+            use diesel::sql_types::{BigInt, Double, Integer, Nullable};
+
+            diesel::sql_query("SELECT locations.*, star_systems.name AS star_system, cube_distance(location_position(x, y, z), location_position($1, $2, $3)) AS distance \
+                               FROM locations JOIN star_systems ON star_systems.id = locations.star_system_id \
+                               WHERE locations.deleted_at IS NULL AND locations.id <> $4 \
+                               AND location_position(x, y, z) <@ cube_enlarge(location_position($1, $2, $3), $5, 3) \
+                               AND cube_distance(location_position(x, y, z), location_position($1, $2, $3)) <= $5 \
+                               ORDER BY distance, locations.id LIMIT $6 OFFSET $7")
+                .bind::<Nullable<Double>, _>(origin.x)
+                .bind::<Nullable<Double>, _>(origin.y)
+                .bind::<Nullable<Double>, _>(origin.z)
+                .bind::<Integer, _>(origin.id)
+                .bind::<Double, _>(radius)
+                .bind::<BigInt, _>(page.limit)
+                .bind::<BigInt, _>(page.offset)
+                .load(&mut self.connection)
+        }
+
+        // Rows ordered by modification time, optionally limited to those modified at or after 'updated_since' and to a security class
+        pub fn list(&mut self, filter: &LocationQuery, page: Page, fields: &FieldSet) -> Result<Vec<Location>, diesel::result::Error> {
+            use schema::{locations, star_systems};
+
+            let mut query = locations::table.inner_join(star_systems::table).filter(locations::deleted_at.is_null()).into_boxed();
+
+            if let Some(updated_since) = filter.updated_since {
+                query = query.filter(locations::updated_at.ge(updated_since));
             }
+            query = match filter.security {
+                Some(SecurityClass::High) => query.filter(locations::security_status.ge(SecurityClass::HIGH_SEC)),
+                Some(SecurityClass::Low) => query.filter(locations::security_status.gt(0.0)).filter(locations::security_status.lt(SecurityClass::HIGH_SEC)),
+                Some(SecurityClass::Null) => query.filter(locations::security_status.le(0.0)),
+                None => query,
+            };
+
+            query
+                .select(Location::columns(fields))
+                .order((locations::updated_at.asc(), locations::id.asc()))
+                .limit(page.limit)
+                .offset(page.offset)
+                .load(&mut self.connection)
+        }
+
+        // Changes of the sovereign empire of the location, newest first
+        pub fn sovereignty_history(&mut self, location_id: i32, page: Page) -> Result<Vec<SovereigntyChange>, diesel::result::Error> {
+            use schema::sovereignty_changes;
+
+            sovereignty_changes::table
+                .filter(sovereignty_changes::location_id.eq(location_id))
+                .order((sovereignty_changes::changed_at.desc(), sovereignty_changes::id.desc()))
+                .limit(page.limit)
+                .offset(page.offset)
+                .load(&mut self.connection)
+        }
+
+        // Claims of empires on the location, live and released, newest first
+        pub fn claims(&mut self, location_id: i32, page: Page) -> Result<Vec<TerritoryClaim>, diesel::result::Error> {
+            use schema::territory_claims;
+
+            territory_claims::table
+                .filter(territory_claims::location_id.eq(location_id))
+                .order((territory_claims::claimed_at.desc(), territory_claims::id.desc()))
+                .limit(page.limit)
+                .offset(page.offset)
+                .load(&mut self.connection)
         }
 
-        pub fn delete(&mut self, location_id: i32) -> Result<(), diesel::result::Error> {
+        // Security status of every location that has not been deleted, as weighed by the route planner
+        pub fn security_statuses(&mut self) -> Result<Vec<(i32, f64)>, diesel::result::Error> {
             use schema::locations;
 
-            // Check if the location exists before attempting to delete
-            let existing_location = locations::table.find(location_id)
-                .get_result::<Location>(&mut self.connection);
-
-            match existing_location {
-                Ok(_) => {
-                    diesel::delete(locations::table.find(location_id))
-                        .execute(&mut self.connection)?;
-                    Ok(())
-                },
-                Err(_) => {
-                    Err(diesel::result::Error::NotFound)
+            locations::table
+                .filter(locations::deleted_at.is_null())
+                .select((locations::id, locations::security_status))
+                .load(&mut self.connection)
+        }
+
+        pub fn update(&mut self, location_id: i32, upsert_location: UpsertLocation, expected_version: Option<i32>) -> Result<Location, diesel::result::Error> {
+            use schema::locations;
+
+            // The location is locked from the check until it is written, so a write without an expected version cannot lose a race
+            let location_id = self.connection.transaction(|connection| {
+                // Check if the location exists before attempting to update
+                let existing_version = locations::table.find(location_id).filter(locations::deleted_at.is_null())
+                    .select(locations::version)
+                    .for_update()
+                    .get_result::<i32>(connection);
+
+                match existing_version {
+                    Ok(existing_version) => {
+                        // Without an expected version the write is conditioned on the version just read
+                        let expected_version = expected_version.unwrap_or(existing_version);
+                        let star_system_id = Self::star_system_id(connection, &upsert_location.star_system)?;
+
+                        diesel::update(locations::table.find(location_id).filter(locations::version.eq(expected_version)))
+                            .set((
+                                locations::star_system_id.eq(star_system_id),
+                                locations::area.eq(&upsert_location.area),
+                                locations::x.eq(upsert_location.x),
+                                locations::y.eq(upsert_location.y),
+                                locations::z.eq(upsert_location.z),
+                                locations::security_status.eq(upsert_location.security_status),
+                                locations::version.eq(locations::version + 1),
+                            ))
+                            .returning(locations::id)
+                            .get_result(connection)
+                    },
+                    Err(_) => Err(diesel::result::Error::NotFound)
                 }
-            }
+            })?;
+
+            self.written(location_id)
+        }
+
+        // Only the columns present in the changeset are written
+        pub fn patch(&mut self, location_id: i32, mut changes: LocationChangeset, expected_version: Option<i32>) -> Result<Location, diesel::result::Error> {
+            use schema::locations;
+
+            // The location is locked from the read until it is written, so a write without an expected version cannot lose a race
+            let location_id = self.connection.transaction(|connection| {
+                let current_version = locations::table.find(location_id).filter(locations::deleted_at.is_null())
+                    .select(locations::version)
+                    .for_update()
+                    .get_result::<i32>(connection)?;
+                let expected_version = expected_version.unwrap_or(current_version);
+
+                // An empty changeset is not a valid UPDATE, so the current row is returned as is
+                if changes.is_empty() {
+                    return if current_version == expected_version { Ok(location_id) } else { Err(diesel::result::Error::NotFound) };
+                }
+                if let Some(star_system) = &changes.star_system {
+                    changes.star_system_id = Some(Self::star_system_id(connection, star_system)?);
+                }
+
+                diesel::update(locations::table.find(location_id).filter(locations::version.eq(expected_version)))
+                    .set((&changes, locations::version.eq(locations::version + 1)))
+                    .returning(locations::id)
+                    .get_result(connection)
+            })?;
+
+            self.written(location_id)
+        }
+
+        // Marks the row as deleted, hiding it from reads until it is restored or purged
+        pub fn delete(&mut self, location_id: i32, expected_version: Option<i32>) -> Result<(), diesel::result::Error> {
+            use schema::locations;
+
+            // The location is locked from the check until it is deleted, so a delete without an expected version cannot lose a race
+            self.connection.transaction(|connection| {
+                // Check if the location exists before attempting to delete
+                let existing_version = locations::table.find(location_id).filter(locations::deleted_at.is_null())
+                    .select(locations::version)
+                    .for_update()
+                    .get_result::<i32>(connection);
+
+                match existing_version {
+                    Ok(existing_version) => {
+                        let deleted_rows = diesel::update(locations::table.find(location_id).filter(locations::version.eq(expected_version.unwrap_or(existing_version))))
+                            .set((locations::deleted_at.eq(Some(Utc::now())), locations::version.eq(locations::version + 1)))
+                            .execute(connection)?;
+
+                        // No deleted row means the version differs from the expected version
+                        if deleted_rows == 0 {
+                            return Err(diesel::result::Error::NotFound);
+                        }
+                        Ok(())
+                    },
+                    Err(_) => {
+                        Err(diesel::result::Error::NotFound)
+                    }
+                }
+            })
+        }
+
+        // Undoes a soft delete. Fails with NotFound unless the row exists and is deleted
+        pub fn restore(&mut self, location_id: i32) -> Result<Location, diesel::result::Error> {
+            use schema::locations;
+
+            let location_id = diesel::update(locations::table.find(location_id).filter(locations::deleted_at.is_not_null()))
+                .set((locations::deleted_at.eq(None::<DateTime<Utc>>), locations::version.eq(locations::version + 1)))
+                .returning(locations::id)
+                .get_result(&mut self.connection)?;
+
+            self.written(location_id)
+        }
+
+        // Permanently removes rows deleted before the cutoff. Rows still referenced by other tables are kept until those references are gone
+        pub fn purge(&mut self, deleted_before: DateTime<Utc>) -> Result<usize, diesel::result::Error> {
+            use diesel::dsl::{exists, not};
+            use schema::locations;
+
+            diesel::delete(locations::table)
+                .filter(locations::deleted_at.lt(deleted_before))
+                .filter(not(exists(schema::empires::table.filter(schema::empires::location_id.eq(locations::id)))))
+                .filter(not(exists(schema::players::table.filter(schema::players::location_id.eq(locations::id)))))
+                .filter(not(exists(schema::stargates::table.filter(schema::stargates::from_location_id.eq(locations::id).or(schema::stargates::to_location_id.eq(locations::id))))))
+                .execute(&mut self.connection)
         }
     }
 
+    // Locations along with the relations named in 'included', each loaded in one query whatever the number of locations
+    pub fn location_documents(shared_state: &ConnectionPool, locations: Vec<Location>, included: &[&str]) -> Result<Vec<LocationDocument>, diesel::result::Error> {
+        let acquire = || shared_state.pool.get().expect("Failed to acquire connection from pool");
+        let location_ids: Vec<i32> = locations.iter().map(|location| location.id).collect();
+
+        let mut empires = if included.contains(&"empires") {
+            Some(group_by(EmpiresTable::new(acquire()).list_by_locations(&location_ids)?, |empire| empire.location_id))
+        } else {
+            None
+        };
+        let mut players = if included.contains(&"players") {
+            Some(group_by(PlayersTable::new(acquire()).list_by_locations(&location_ids)?, |player| player.location_id))
+        } else {
+            None
+        };
+
+        Ok(locations.into_iter()
+            .map(|location| LocationDocument {
+                empires: empires.as_mut().map(|empires| empires.remove(&location.id).unwrap_or_default()),
+                players: players.as_mut().map(|players| players.remove(&location.id).unwrap_or_default()),
+                location,
+            })
+            .collect())
+    }
+
     #[cfg(test)]
     mod tests {
+        use diesel::{Connection, RunQueryDsl};
         use crate::{
             common::{
                 db::create_shared_connection_pool,
@@ -109,7 +404,8 @@ pub mod service {
 
             let new_location = UpsertLocation {
                 star_system: "Test Star System".to_string(),
-                area: "Test Area".to_string(),
+                area: "Pegasus".to_string(),
+                ..UpsertLocation::default()
             };
 
             let created_location = location_db.create(new_location.clone()).expect("Create location failed");
@@ -118,6 +414,26 @@ pub mod service {
             assert_eq!(created_location.area, new_location.area);
         }
 
+        #[test]
+        fn create_restores_the_unassigned_constellation_when_it_was_removed() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 1);
+            let mut connection = connection_pool.pool.get().expect("Failed to get connection");
+
+            // The renames are rolled back with the test transaction, leaving the shared 'Unassigned' rows untouched
+            connection.begin_test_transaction().expect("Begin transaction failed");
+            diesel::sql_query("UPDATE constellations SET name = 'Formerly Unassigned' WHERE name = 'Unassigned'").execute(&mut connection).unwrap();
+            diesel::sql_query("UPDATE regions SET name = 'Formerly Unassigned' WHERE name = 'Unassigned'").execute(&mut connection).unwrap();
+
+            let mut location_db = LocationsTable::new(connection);
+            let created_location = location_db.create(UpsertLocation {
+                star_system: "Ny Stjerne".to_string(),
+                area: "Nybrott".to_string(),
+                ..UpsertLocation::default()
+            }).expect("Create location failed");
+
+            assert_eq!(created_location.star_system, "Ny Stjerne");
+        }
 
         #[test]
         fn read_succeeds_on_existing_id() {
@@ -128,7 +444,8 @@ pub mod service {
 
             let new_location = UpsertLocation {
                 star_system: "Test Star System".to_string(),
-                area: "Test Area".to_string(),
+                area: "Kalevala".to_string(),
+                ..UpsertLocation::default()
             };
             let created_location = location_db.create(new_location.clone()).expect("Create location failed");
 
@@ -160,15 +477,17 @@ pub mod service {
 
             let new_location = UpsertLocation {
                 star_system: "Test Star System".to_string(),
-                area: "Test Area".to_string(),
+                area: "YZ-LQL".to_string(),
+                ..UpsertLocation::default()
             };
             let created_location = location_db.create(new_location.clone()).expect("Create location failed");
 
             let updated_request = UpsertLocation {
                 star_system: "Updated Star System".to_string(),
                 area: "Updated Area".to_string(),
+                ..UpsertLocation::default()
             };
-            let updated_location = location_db.update(created_location.id, updated_request.clone()).expect("Update location failed");
+            let updated_location = location_db.update(created_location.id, updated_request.clone(), None).expect("Update location failed");
 
             assert_eq!(updated_location.star_system, updated_request.star_system);
             assert_eq!(updated_location.area, updated_request.area);
@@ -184,13 +503,101 @@ pub mod service {
             let request = UpsertLocation {
                 star_system: "This test will fail".to_string(),
                 area: "so write random skit here".to_string(),
+                ..UpsertLocation::default()
             };
 
-            let result = location_db.update(-1, request.clone());  // Use a non-existent ID
+            let result = location_db.update(-1, request.clone(), None);  // Use a non-existent ID
             assert!(result.is_err());  // Expecting an error as the ID is not present
         }
 
 
+        #[test]
+        fn update_increments_version_and_fails_on_stale_version() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 1);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
+
+            let request = UpsertLocation {
+                star_system: "Versioned Star System".to_string(),
+                area: "Versioned Area".to_string(),
+                ..UpsertLocation::default()
+            };
+            let created_location = location_db.create(request.clone()).expect("Create location failed");
+            let updated_location = location_db.update(created_location.id, request.clone(), Some(created_location.version)).expect("Update location failed");
+
+            assert_eq!(updated_location.version, created_location.version + 1);
+
+            // Writing against the version read before the update must not match any row
+            let result = location_db.update(created_location.id, request.clone(), Some(created_location.version));
+            assert!(matches!(result, Err(diesel::result::Error::NotFound)));
+        }
+
+        #[test]
+        fn update_without_expected_version_waits_for_a_concurrent_write_instead_of_failing() {
+            use diesel::sql_types::Integer;
+
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let request = UpsertLocation {
+                star_system: "Contested Star System".to_string(),
+                area: "Contested Area".to_string(),
+                ..UpsertLocation::default()
+            };
+            let created_location = LocationsTable::new(connection_pool.pool.get().expect("Failed to get connection"))
+                .create(request.clone()).expect("Create location failed");
+
+            // Another writer holds the row while it bumps the version
+            let mut writer = connection_pool.pool.get().expect("Failed to get connection");
+            diesel::sql_query("BEGIN").execute(&mut writer).expect("Begin failed");
+            diesel::sql_query("UPDATE locations SET version = version + 1 WHERE id = $1")
+                .bind::<Integer, _>(created_location.id)
+                .execute(&mut writer).expect("Concurrent update failed");
+
+            let update = std::thread::spawn({
+                let connection_pool = connection_pool.clone();
+                let request = request.clone();
+                move || LocationsTable::new(connection_pool.pool.get().expect("Failed to get connection")).update(created_location.id, request, None)
+            });
+            std::thread::sleep(std::time::Duration::from_millis(200));
+            diesel::sql_query("COMMIT").execute(&mut writer).expect("Commit failed");
+
+            let updated_location = update.join().unwrap().expect("Update location failed");
+            assert_eq!(updated_location.version, created_location.version + 2);
+        }
+
+        #[test]
+        fn restore_and_purge_follow_soft_delete() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 1);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut location_db = LocationsTable::new(connection);
+
+            let new_location = UpsertLocation {
+                star_system: "Soft Star System".to_string(),
+                area: "Soft Area".to_string(),
+                ..UpsertLocation::default()
+            };
+
+            // A live location can't be restored
+            let created_location = location_db.create(new_location.clone()).expect("Create location failed");
+            assert!(location_db.restore(created_location.id).is_err());
+
+            location_db.delete(created_location.id, None).expect("Delete location failed");
+            let restored_location = location_db.restore(created_location.id).expect("Restore location failed");
+            assert_eq!(restored_location.id, created_location.id);
+            assert!(location_db.get(created_location.id).expect("Read location failed").is_some());
+
+            // Only rows deleted before the cutoff are purged
+            location_db.delete(created_location.id, None).expect("Delete location failed");
+            location_db.purge(created_location.created_at).expect("Purge locations failed");
+            assert!(location_db.restore(created_location.id).is_ok());
+
+            location_db.delete(created_location.id, None).expect("Delete location failed");
+            location_db.purge(chrono::Utc::now() + chrono::Duration::seconds(1)).expect("Purge locations failed");
+            assert!(location_db.restore(created_location.id).is_err());
+        }
+
         #[test]
         fn delete_succeeds_on_existing_id() {
             let database_url = load_environment_variable("TEST_DB");
@@ -200,11 +607,12 @@ pub mod service {
 
             let new_location = UpsertLocation {
                 star_system: "Test Star System".to_string(),
-                area: "Test Area".to_string(),
+                area: "J-GAMP".to_string(),
+                ..UpsertLocation::default()
             };
 
             let created_location = location_db.create(new_location.clone()).expect("Create location failed");
-            location_db.delete(created_location.id.clone()).expect("Delete location failed");
+            location_db.delete(created_location.id.clone(), None).expect("Delete location failed");
             let deleted_location = location_db.get(created_location.id).expect("Read location failed");
             assert!(deleted_location.is_none()); // Expecting lack of value as location has been deleted
         }
@@ -216,9 +624,8 @@ pub mod service {
             let connection = connection_pool.pool.get().expect("Failed to get connection");
             let mut location_db = LocationsTable::new(connection);
 
-            let result = location_db.delete(-666);  // Use a non-existent ID
+            let result = location_db.delete(-666, None);  // Use a non-existent ID
             assert!(result.is_err());  // Expecting an error as the ID is not present
         }
     }
 }
-
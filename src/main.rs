@@ -1,29 +1,99 @@
-use crate:: {
+use axum_api_with_auth::{
     common::db::create_shared_connection_pool,
-    locations::router::router::locations_route,
-    empires::router::router::empires_route,
-    users::router::router::users_route,
-    common::util::load_environment_variable,
+    common::util::{load_environment_variable, load_optional_environment_variable},
+    common::telemetry::{init_tracing, trace_layer},
+    common::request_id::{include_request_id_in_errors, propagate_request_id_layer, set_request_id_layer},
+    common::metrics::{metrics_route, track_metrics},
+    common::health::health_route,
+    common::openapi::openapi_route,
+    common::versioning::versioned_route,
+    common::shutdown::{wait_for_signal, Shutdown, ShutdownConfig},
 };
-
-mod locations;mod users;mod schema;mod common;
-mod empires;
+use axum::middleware;
+use tower::ServiceBuilder;
 
 #[tokio::main]
 async fn main() {
+    init_tracing();
+
     let database_url = load_environment_variable("DEV_DB");
     let shared_connection_pool = create_shared_connection_pool(database_url, 1);
+    let shutdown = Shutdown::new();
+    let shutdown_config = ShutdownConfig::from_environment();
 
-    axum::Server::bind(&"0.0.0.0:3000".parse().unwrap())
-        .serve(users_route(shared_connection_pool.clone())
-            .nest("/", locations_route(shared_connection_pool.clone()))
-            .nest("/", empires_route(shared_connection_pool.clone()))
-                .into_make_service())
-        .await
-        .unwrap();
-}
+    let mut app = versioned_route(shared_connection_pool.clone())
+        .merge(health_route(shared_connection_pool.clone(), shutdown.clone()))
+        .merge(openapi_route());
+
+    // Metrics are served on the API port unless METRICS_PORT binds them to a separate listener
+    let metrics = metrics_route(shared_connection_pool.clone(), load_optional_environment_variable("METRICS_TOKEN"));
+
+    let metrics_server = match load_optional_environment_variable("METRICS_PORT") {
+        Some(metrics_port) => {
+            let metrics_address = format!("0.0.0.0:{}", metrics_port).parse().expect("METRICS_PORT must be a valid port");
+            tracing::info!("Serving metrics on {}", metrics_address);
 
+            let shutdown = shutdown.clone();
+            Some(tokio::spawn(async move {
+                axum::Server::bind(&metrics_address)
+                    .serve(metrics.into_make_service())
+                    .with_graceful_shutdown(async move { shutdown.draining().await })
+                    .await
+                    .unwrap();
+            }))
+        }
+        None => {
+            app = app.merge(metrics);
+            None
+        }
+    };
 
+    let app = app.layer(ServiceBuilder::new()
+        .layer(set_request_id_layer())
+        .layer(trace_layer())
+        .layer(middleware::from_fn(track_metrics))
+        .layer(propagate_request_id_layer())
+        .layer(middleware::from_fn(include_request_id_in_errors)));
 
+    // Flip readiness as soon as a termination signal arrives
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            wait_for_signal().await;
+            tracing::info!("Draining connections, readiness now reports unavailable");
+            shutdown.start_draining();
+        }
+    });
 
+    tracing::info!("Listening on 0.0.0.0:3000");
 
+    let server = axum::Server::bind(&"0.0.0.0:3000".parse().unwrap())
+        .serve(app.into_make_service())
+        .with_graceful_shutdown({
+            let shutdown = shutdown.clone();
+            async move {
+                shutdown.draining().await;
+                tokio::time::sleep(shutdown_config.readiness_delay).await;
+                tracing::info!("Stopped accepting connections, waiting up to {:?} for in-flight requests", shutdown_config.drain_timeout);
+            }
+        });
+
+    // In-flight requests get until the drain timeout to complete before they are aborted
+    let drain_deadline = async {
+        shutdown.draining().await;
+        tokio::time::sleep(shutdown_config.readiness_delay + shutdown_config.drain_timeout).await;
+    };
+
+    tokio::select! {
+        result = server => result.unwrap(),
+        _ = drain_deadline => tracing::warn!("Drain timeout elapsed, aborting remaining requests"),
+    }
+
+    if let Some(metrics_server) = metrics_server {
+        metrics_server.abort();
+    }
+
+    // The routers holding pool clones are dropped with the server, releasing the last connections here
+    drop(shared_connection_pool);
+    tracing::info!("Connection pool closed, shutdown complete");
+}
@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde_derive::{Serialize, Deserialize};
+use utoipa::ToSchema;
+use crate::{locations::model::Location, schema::players, ships::model::Ship};
+
+#[derive(Serialize, Debug, Clone, Queryable, ToSchema)]
+#[diesel(table_name = players)]
+pub struct Player {
+    pub id: i32,
+    pub user_id: i32,
+    /// Ship the player flies, which ties the player to the empire of the ship
+    pub active_ship_id: i32,
+    pub location_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Player {
+    pub const FIELDS: [&'static str; 6] = ["id", "user_id", "active_ship_id", "location_id", "created_at", "updated_at"];
+}
+
+#[derive(Debug, Clone, Insertable, Deserialize, Serialize, ToSchema)]
+#[diesel(table_name = players)]
+pub struct UpsertPlayer {
+    pub user_id: i32,
+    pub active_ship_id: i32,
+    pub location_id: i32,
+}
+
+// A player with the related resources named in '?include='. Relations that were not asked for are left out
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct PlayerDocument {
+    #[serde(flatten)]
+    pub player: Player,
+    /// Active ship of the player
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ship: Option<Ship>,
+    /// Location of the player, null when it has been deleted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Location>)]
+    pub location: Option<Option<Location>>,
+}
+
+impl PlayerDocument {
+    pub const INCLUDES: [&'static str; 2] = ["ship", "location"];
+}
@@ -0,0 +1,109 @@
+pub mod service {
+    use diesel::{
+        prelude::*,
+        PgConnection,
+        r2d2::{ConnectionManager, PooledConnection},
+    };
+    use std::collections::HashMap;
+    use crate::{
+        common::{db::ConnectionPool, pagination::Page},
+        locations::{model::Location, service::service::LocationsTable},
+        players::model::{Player, PlayerDocument, UpsertPlayer},
+        ships::{model::Ship, service::service::ShipsTable},
+        schema
+    };
+
+    type PooledPg = PooledConnection<ConnectionManager<PgConnection>>;
+
+    pub struct PlayersTable {
+        connection: PooledPg,
+    }
+
+    impl PlayersTable {
+        pub fn new(connection: PooledPg) -> PlayersTable {
+            PlayersTable { connection }
+        }
+
+        pub fn create(&mut self, upsert_player: UpsertPlayer) -> Result<Player, diesel::result::Error> {
+            use schema::players;
+
+            diesel::insert_into(players::table)
+                .values(&upsert_player)
+                .get_result(&mut self.connection)
+        }
+
+        // Players currently at the location, in id order
+        pub fn list_by_location(&mut self, location_id: i32, page: Page) -> Result<Vec<Player>, diesel::result::Error> {
+            use schema::players;
+
+            players::table
+                .filter(players::location_id.eq(location_id))
+                .order(players::id.asc())
+                .limit(page.limit)
+                .offset(page.offset)
+                .load(&mut self.connection)
+        }
+
+        // Players flying a ship of the empire, in id order
+        pub fn list_by_empire(&mut self, empire_id: i32, page: Page) -> Result<Vec<Player>, diesel::result::Error> {
+            use schema::{players, ships};
+
+            players::table
+                .inner_join(ships::table)
+                .filter(ships::empire_id.eq(empire_id))
+                .select(players::all_columns)
+                .order(players::id.asc())
+                .limit(page.limit)
+                .offset(page.offset)
+                .load(&mut self.connection)
+        }
+
+        // Players at any of the given locations in one query, in id order
+        pub fn list_by_locations(&mut self, location_ids: &[i32]) -> Result<Vec<Player>, diesel::result::Error> {
+            use schema::players;
+
+            players::table
+                .filter(players::location_id.eq_any(location_ids))
+                .order(players::id.asc())
+                .load(&mut self.connection)
+        }
+
+        // Players of any of the given empires in one query along with the id of their empire, in id order
+        pub fn list_by_empires(&mut self, empire_ids: &[i32]) -> Result<Vec<(i32, Player)>, diesel::result::Error> {
+            use schema::{players, ships};
+
+            players::table
+                .inner_join(ships::table)
+                .filter(ships::empire_id.eq_any(empire_ids))
+                .select((ships::empire_id, players::all_columns))
+                .order(players::id.asc())
+                .load(&mut self.connection)
+        }
+    }
+
+    // Players along with the relations named in 'included', each loaded in one query whatever the number of players
+    pub fn player_documents(shared_state: &ConnectionPool, players: Vec<Player>, included: &[&str]) -> Result<Vec<PlayerDocument>, diesel::result::Error> {
+        let acquire = || shared_state.pool.get().expect("Failed to acquire connection from pool");
+
+        let ships: Option<HashMap<i32, Ship>> = if included.contains(&"ship") {
+            let ship_ids: Vec<i32> = players.iter().map(|player| player.active_ship_id).collect();
+            Some(ShipsTable::new(acquire()).get_many(&ship_ids)?.into_iter().map(|ship| (ship.id, ship)).collect())
+        } else {
+            None
+        };
+        let locations: Option<HashMap<i32, Location>> = if included.contains(&"location") {
+            let location_ids: Vec<i32> = players.iter().map(|player| player.location_id).collect();
+            Some(LocationsTable::new(acquire()).get_many(&location_ids)?.into_iter().map(|location| (location.id, location)).collect())
+        } else {
+            None
+        };
+
+        Ok(players.into_iter()
+            .map(|player| PlayerDocument {
+                ship: ships.as_ref().and_then(|ships| ships.get(&player.active_ship_id).cloned()),
+                location: locations.as_ref().map(|locations| locations.get(&player.location_id).cloned()),
+                player,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde_derive::{Serialize, Deserialize};
+use utoipa::ToSchema;
+use crate::{common::util::check_length, schema::{constellations, regions, star_systems}};
+
+// Region and constellation holding star systems that have not been placed in the tree yet
+pub const UNASSIGNED: &str = "Unassigned";
+
+#[derive(Serialize, Debug, Clone, Queryable, ToSchema)]
+#[diesel(table_name = regions)]
+pub struct Region {
+    pub id: i32,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Debug, Clone, Queryable, ToSchema)]
+#[diesel(table_name = constellations)]
+pub struct Constellation {
+    pub id: i32,
+    pub region_id: i32,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Star system named by the 'star_system' of locations
+#[derive(Serialize, Debug, Clone, Queryable, ToSchema)]
+#[diesel(table_name = star_systems)]
+pub struct StarSystem {
+    pub id: i32,
+    pub constellation_id: i32,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Insertable, Deserialize, Serialize, ToSchema)]
+#[diesel(table_name = regions)]
+pub struct NewRegion {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Insertable, Deserialize, Serialize, ToSchema)]
+#[diesel(table_name = constellations)]
+pub struct NewConstellation {
+    pub region_id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Insertable, Deserialize, Serialize, ToSchema)]
+#[diesel(table_name = star_systems)]
+pub struct UpsertStarSystem {
+    pub constellation_id: i32,
+    pub name: String,
+}
+
+impl NewRegion {
+    pub fn validate(&self) -> Result<(), String> {
+        check_length("name", &self.name, 100)
+    }
+}
+
+impl NewConstellation {
+    pub fn validate(&self) -> Result<(), String> {
+        check_length("name", &self.name, 100)
+    }
+}
+
+impl UpsertStarSystem {
+    pub fn validate(&self) -> Result<(), String> {
+        check_length("name", &self.name, 100)
+    }
+}
+
+// Nodes of the tree as they are browsed, with the number of children below them
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct RegionNode {
+    #[serde(flatten)]
+    pub region: Region,
+    pub constellations: i64,
+    pub systems: i64,
+}
+
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct ConstellationNode {
+    #[serde(flatten)]
+    pub constellation: Constellation,
+    pub systems: i64,
+}
+
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct StarSystemNode {
+    #[serde(flatten)]
+    pub star_system: StarSystem,
+    /// Locations in the star system that have not been deleted
+    pub locations: i64,
+}
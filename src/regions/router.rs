@@ -0,0 +1,696 @@
+pub mod router {
+    use serde_json::{json, Value};
+    use tracing::error;
+    use axum::{
+        Router, http::StatusCode, Json, response::IntoResponse, extract::State, extract,
+    };
+    use http::HeaderMap;
+    use crate::{
+        audit::{model::AuditAction, service::service::record_event},
+        common::db::ConnectionPool,
+        regions::{
+            service::service::RegionsTable,
+            model::{NewConstellation, NewRegion, UpsertStarSystem}
+        },
+        users::model::UserRole,
+        common::security::{enforce_role_policy, decode_claims}
+    };
+
+    // - - - - - - - - - - - [ROUTES] - - - - - - - - - - -
+
+    pub fn regions_route(shared_connection_pool: ConnectionPool) -> Router {
+        Router::new()
+            .route("/regions", axum::routing::post(create_region_handler))
+            .route("/regions", axum::routing::get(list_regions_handler))
+            .route("/regions/:region_id", axum::routing::get(read_region_handler))
+            .route("/regions/:region_id/constellations", axum::routing::get(list_region_constellations_handler))
+            .route("/regions/:region_id/systems", axum::routing::get(list_region_star_systems_handler))
+            .route("/constellations", axum::routing::post(create_constellation_handler))
+            .route("/constellations/:constellation_id/systems", axum::routing::get(list_constellation_star_systems_handler))
+            .route("/systems", axum::routing::post(create_star_system_handler))
+            .route("/systems/:system_id", axum::routing::get(read_star_system_handler))
+            .route("/systems/:system_id", axum::routing::put(update_star_system_handler))
+            .with_state(shared_connection_pool)
+    }
+
+    // - - - - - - - - - - - [HANDLERS] - - - - - - - - - - -
+
+    /// Create a region
+    ///
+    /// Requires role WRITER or higher.
+    #[utoipa::path(
+        post,
+        path = "/regions",
+        tag = "regions",
+        request_body = NewRegion,
+        responses(
+            (status = 201, description = "Region created", body = Region),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 409, description = "Region name is already taken", body = ErrorBody),
+            (status = 422, description = "Name is too long", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while creating region", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn create_region_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        Json(new_region): Json<NewRegion>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'WRITER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::WRITER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                new_region.validate().map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": err}))))?;
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let result = RegionsTable::new(connection).create_region(new_region);
+
+                match result {
+                    Ok(new_region) => {
+                        record_event(&shared_state, &headers, AuditAction::Create, "region", new_region.id, None, Some(&new_region));
+                        Ok((StatusCode::CREATED, Json(new_region)))
+                    },
+                    Err(err) => Err(write_error(err, "Region", "create"))
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// List regions
+    ///
+    /// Requires role READER or higher. Ordered by name, each region with the number of constellations and star systems in it.
+    #[utoipa::path(
+        get,
+        path = "/regions",
+        tag = "regions",
+        responses(
+            (status = 200, description = "Every region", body = [RegionNode]),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing regions", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_regions_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                match RegionsTable::new(connection).list_regions() {
+                    Ok(regions) => Ok((StatusCode::OK, Json(regions))),
+                    Err(err) => {
+                        error!("Error listing regions: {:?}", err);
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list regions"}))))
+                    }
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Read a region by id
+    ///
+    /// Requires role READER or higher.
+    #[utoipa::path(
+        get,
+        path = "/regions/{region_id}",
+        tag = "regions",
+        params(("region_id" = i32, Path, description = "Id of the region")),
+        responses(
+            (status = 200, description = "Region found", body = RegionNode),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Region not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while reading region", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn read_region_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (region_id, ) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                match RegionsTable::new(connection).get_region(region_id) {
+                    Ok(Some(region)) => Ok((StatusCode::OK, Json(region))),
+                    Ok(None) => Err((StatusCode::NOT_FOUND, Json(json!({"error": "Region not found"})))),
+                    Err(err) => {
+                        error!("Error reading region: {:?}", err);
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to read region"}))))
+                    }
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// List the constellations of a region
+    ///
+    /// Requires role READER or higher. Ordered by name, each constellation with the number of star systems in it.
+    #[utoipa::path(
+        get,
+        path = "/regions/{region_id}/constellations",
+        tag = "regions",
+        params(("region_id" = i32, Path, description = "Id of the region")),
+        responses(
+            (status = 200, description = "Constellations of the region", body = [ConstellationNode]),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Region not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing constellations", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_region_constellations_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (region_id, ) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let listing_failed = |err: diesel::result::Error| {
+                    error!("Error listing constellations: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list constellations"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut regions = RegionsTable::new(connection);
+
+                if regions.get_region(region_id).map_err(listing_failed)?.is_none() {
+                    return Err((StatusCode::NOT_FOUND, Json(json!({"error": "Region not found"}))));
+                }
+
+                let constellations = regions.list_constellations(region_id).map_err(listing_failed)?;
+                Ok((StatusCode::OK, Json(constellations)))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// List the star systems of a region
+    ///
+    /// Requires role READER or higher. Star systems of every constellation in the region ordered by name, each with the number of locations in it.
+    #[utoipa::path(
+        get,
+        path = "/regions/{region_id}/systems",
+        tag = "regions",
+        params(("region_id" = i32, Path, description = "Id of the region")),
+        responses(
+            (status = 200, description = "Star systems of the region", body = [StarSystemNode]),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Region not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing star systems", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_region_star_systems_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (region_id, ) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let listing_failed = |err: diesel::result::Error| {
+                    error!("Error listing star systems: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list star systems"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut regions = RegionsTable::new(connection);
+
+                if regions.get_region(region_id).map_err(listing_failed)?.is_none() {
+                    return Err((StatusCode::NOT_FOUND, Json(json!({"error": "Region not found"}))));
+                }
+
+                let star_systems = regions.list_region_star_systems(region_id).map_err(listing_failed)?;
+                Ok((StatusCode::OK, Json(star_systems)))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Create a constellation
+    ///
+    /// Requires role WRITER or higher.
+    #[utoipa::path(
+        post,
+        path = "/constellations",
+        tag = "regions",
+        request_body = NewConstellation,
+        responses(
+            (status = 201, description = "Constellation created", body = Constellation),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 409, description = "Constellation name is already taken", body = ErrorBody),
+            (status = 422, description = "Region does not exist or name is too long", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while creating constellation", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn create_constellation_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        Json(new_constellation): Json<NewConstellation>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'WRITER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::WRITER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                new_constellation.validate().map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": err}))))?;
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let result = RegionsTable::new(connection).create_constellation(new_constellation);
+
+                match result {
+                    Ok(new_constellation) => {
+                        record_event(&shared_state, &headers, AuditAction::Create, "constellation", new_constellation.id, None, Some(&new_constellation));
+                        Ok((StatusCode::CREATED, Json(new_constellation)))
+                    },
+                    Err(err) => Err(write_error(err, "Constellation", "create"))
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// List the star systems of a constellation
+    ///
+    /// Requires role READER or higher. Ordered by name, each star system with the number of locations in it.
+    #[utoipa::path(
+        get,
+        path = "/constellations/{constellation_id}/systems",
+        tag = "regions",
+        params(("constellation_id" = i32, Path, description = "Id of the constellation")),
+        responses(
+            (status = 200, description = "Star systems of the constellation", body = [StarSystemNode]),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Constellation not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing star systems", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_constellation_star_systems_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (constellation_id, ) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let listing_failed = |err: diesel::result::Error| {
+                    error!("Error listing star systems: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list star systems"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut regions = RegionsTable::new(connection);
+
+                if regions.get_constellation(constellation_id).map_err(listing_failed)?.is_none() {
+                    return Err((StatusCode::NOT_FOUND, Json(json!({"error": "Constellation not found"}))));
+                }
+
+                let star_systems = regions.list_constellation_star_systems(constellation_id).map_err(listing_failed)?;
+                Ok((StatusCode::OK, Json(star_systems)))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Create a star system
+    ///
+    /// Requires role WRITER or higher. Star systems named by new locations are otherwise created in the 'Unassigned' constellation.
+    #[utoipa::path(
+        post,
+        path = "/systems",
+        tag = "regions",
+        request_body = UpsertStarSystem,
+        responses(
+            (status = 201, description = "Star system created", body = StarSystem),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 409, description = "Star system name is already taken", body = ErrorBody),
+            (status = 422, description = "Constellation does not exist or name is too long", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while creating star system", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn create_star_system_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        Json(upsert_star_system): Json<UpsertStarSystem>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'WRITER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::WRITER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                upsert_star_system.validate().map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": err}))))?;
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let result = RegionsTable::new(connection).create_star_system(upsert_star_system);
+
+                match result {
+                    Ok(new_star_system) => {
+                        record_event(&shared_state, &headers, AuditAction::Create, "star_system", new_star_system.id, None, Some(&new_star_system));
+                        Ok((StatusCode::CREATED, Json(new_star_system)))
+                    },
+                    Err(err) => Err(write_error(err, "Star system", "create"))
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Read a star system by id
+    ///
+    /// Requires role READER or higher.
+    #[utoipa::path(
+        get,
+        path = "/systems/{system_id}",
+        tag = "regions",
+        params(("system_id" = i32, Path, description = "Id of the star system")),
+        responses(
+            (status = 200, description = "Star system found", body = StarSystem),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Star system not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while reading star system", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn read_star_system_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (system_id, ) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                match RegionsTable::new(connection).get_star_system(system_id) {
+                    Ok(Some(star_system)) => Ok((StatusCode::OK, Json(star_system))),
+                    Ok(None) => Err((StatusCode::NOT_FOUND, Json(json!({"error": "Star system not found"})))),
+                    Err(err) => {
+                        error!("Error reading star system: {:?}", err);
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to read star system"}))))
+                    }
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Rename a star system or move it to another constellation
+    ///
+    /// Requires role EDITOR or higher. Locations in the star system take on the new name.
+    #[utoipa::path(
+        put,
+        path = "/systems/{system_id}",
+        tag = "regions",
+        params(("system_id" = i32, Path, description = "Id of the star system")),
+        request_body = UpsertStarSystem,
+        responses(
+            (status = 200, description = "Star system updated", body = StarSystem),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Star system not found", body = ErrorBody),
+            (status = 409, description = "Star system name is already taken", body = ErrorBody),
+            (status = 422, description = "Constellation does not exist or name is too long", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while updating star system", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn update_star_system_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+        Json(upsert_star_system): Json<UpsertStarSystem>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (system_id, ) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'EDITOR' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::EDITOR).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                upsert_star_system.validate().map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": err}))))?;
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut regions = RegionsTable::new(connection);
+
+                let before = regions.get_star_system(system_id).map_err(|err| write_error(err, "Star system", "update"))?;
+                let result = regions.update_star_system(system_id, upsert_star_system);
+                // Hand the connection back to the pool before the audit event is recorded
+                drop(regions);
+
+                match result {
+                    Ok(updated_star_system) => {
+                        record_event(&shared_state, &headers, AuditAction::Update, "star_system", system_id, before.as_ref(), Some(&updated_star_system));
+                        Ok((StatusCode::OK, Json(updated_star_system)))
+                    },
+                    Err(err) => Err(write_error(err, "Star system", "update"))
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    // Status and error body of a failed write to the region tree. A foreign key violation names a parent that does not exist
+    fn write_error(err: diesel::result::Error, resource: &str, action: &str) -> (StatusCode, Json<Value>) {
+        use diesel::result::{DatabaseErrorKind, Error};
+
+        match err {
+            Error::NotFound => (StatusCode::NOT_FOUND, Json(json!({"error": format!("{} not found", resource)}))),
+            Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                (StatusCode::CONFLICT, Json(json!({"error": format!("{} name is already taken", resource)})))
+            },
+            Error::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => {
+                let parent = if resource == "Constellation" { "Region" } else { "Constellation" };
+                (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": format!("{} does not exist", parent)})))
+            },
+            err => {
+                error!("Error writing {}: {:?}", resource.to_lowercase(), err);
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to {} {}", action, resource.to_lowercase())})))
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use axum::{
+            body::Body,
+            http::{Request, StatusCode}
+        };
+        use serde_json::{json, Value};
+        use tower::ServiceExt;
+        use crate::{
+            common::{
+                db::{create_shared_connection_pool, ConnectionPool},
+                security::{generate_token, hash_password},
+                util::load_environment_variable
+            },
+            locations::{
+                model::UpsertLocation,
+                service::service::LocationsTable
+            },
+            regions::router::router::regions_route,
+            users::{
+                model::{UpsertUser, UserRole},
+                service::service::UsersTable
+            }
+        };
+
+        fn create_user_and_generate_token(connection_pool: ConnectionPool, email: &str, user_role: UserRole) -> String {
+            let mut new_user = UpsertUser {
+                email: email.to_string(),
+                role: user_role.to_string(),
+                password: "KartleggerStjernehimmelen7".to_string(),
+                fullname: "Kartograf Karlsen".to_string()
+            };
+            hash_password(&mut new_user).expect("Hash failed");
+
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let created_user = UsersTable::new(connection).create(new_user).expect("Create user failed");
+            generate_token(&created_user).expect("Generate token failed")
+        }
+
+        async fn send(connection_pool: ConnectionPool, bearer_token: &str, method: &str, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+            let request = Request::builder()
+                .uri(uri)
+                .method(method)
+                .header("content-type", "application/json")
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .body(body.map(|body| Body::from(body.to_string())).unwrap_or_else(Body::empty))
+                .unwrap();
+
+            let response = regions_route(connection_pool).oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+        }
+
+        #[tokio::test]
+        async fn region_tree_is_browsed_with_child_counts() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let bearer_token = create_user_and_generate_token(connection_pool.clone(), "kartograf@stjernekart.gb", UserRole::EDITOR);
+
+            // Locations placed in an unknown star system add it to the 'Unassigned' constellation
+            let location = {
+                let connection = connection_pool.pool.get().expect("Failed to get connection");
+                LocationsTable::new(connection).create(UpsertLocation { star_system: "Aunenen".to_string(), area: "Citizen Astrometric Station".to_string(), ..UpsertLocation::default() }).unwrap()
+            };
+
+            let (status, region) = send(connection_pool.clone(), &bearer_token, "POST", "/regions", Some(json!({"name": "Black Rise"}))).await;
+            assert_eq!(status, StatusCode::CREATED);
+            let (status, _) = send(connection_pool.clone(), &bearer_token, "POST", "/regions", Some(json!({"name": "Black Rise"}))).await;
+            assert_eq!(status, StatusCode::CONFLICT);
+
+            let (status, constellation) = send(connection_pool.clone(), &bearer_token, "POST", "/constellations", Some(json!({"region_id": region["id"], "name": "Kurala"}))).await;
+            assert_eq!(status, StatusCode::CREATED);
+            let (status, _) = send(connection_pool.clone(), &bearer_token, "POST", "/constellations", Some(json!({"region_id": -666, "name": "Ihilakken"}))).await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+            let (status, _) = send(connection_pool.clone(), &bearer_token, "POST", "/systems", Some(json!({"constellation_id": constellation["id"], "name": "Nourvukaiken"}))).await;
+            assert_eq!(status, StatusCode::CREATED);
+
+            // Moving the star system of the location out of 'Unassigned' and renaming it renames it in the location
+            let unassigned = send(connection_pool.clone(), &bearer_token, "GET", "/regions", None).await.1.as_array().unwrap().iter()
+                .find(|region| region["name"] == "Unassigned").cloned().unwrap();
+            let aunenen = send(connection_pool.clone(), &bearer_token, "GET", &format!("/regions/{}/systems", unassigned["id"]), None).await.1.as_array().unwrap().iter()
+                .find(|star_system| star_system["name"] == "Aunenen").cloned().unwrap();
+            assert_eq!(aunenen["locations"], 1);
+
+            let (status, _) = send(connection_pool.clone(), &bearer_token, "PUT", &format!("/systems/{}", aunenen["id"]), Some(json!({"constellation_id": constellation["id"], "name": "Onne"}))).await;
+            assert_eq!(status, StatusCode::OK);
+
+            let (status, region) = send(connection_pool.clone(), &bearer_token, "GET", &format!("/regions/{}", region["id"]), None).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!((region["constellations"].clone(), region["systems"].clone()), (json!(1), json!(2)));
+
+            let (_, constellations) = send(connection_pool.clone(), &bearer_token, "GET", &format!("/regions/{}/constellations", region["id"]), None).await;
+            assert_eq!(constellations[0]["systems"], 2);
+
+            let (_, star_systems) = send(connection_pool.clone(), &bearer_token, "GET", &format!("/constellations/{}/systems", constellation["id"]), None).await;
+            assert_eq!(star_systems.as_array().unwrap().iter().map(|star_system| (star_system["name"].clone(), star_system["locations"].clone())).collect::<Vec<_>>(),
+                       vec![(json!("Nourvukaiken"), json!(0)), (json!("Onne"), json!(1))]);
+
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            assert_eq!(LocationsTable::new(connection).get(location.id).unwrap().unwrap().star_system, "Onne");
+
+            let (status, _) = send(connection_pool, &bearer_token, "GET", "/regions/-666/systems", None).await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+        }
+    }
+}
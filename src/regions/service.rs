@@ -0,0 +1,199 @@
+pub mod service {
+    use std::collections::HashMap;
+    use diesel::{
+        dsl::count_star,
+        prelude::*,
+        PgConnection,
+        r2d2::{ConnectionManager, PooledConnection},
+    };
+    use crate::{
+        regions::model::{
+            Constellation, ConstellationNode, NewConstellation, NewRegion, Region, RegionNode, StarSystem, StarSystemNode, UpsertStarSystem
+        },
+        schema
+    };
+
+    type PooledPg = PooledConnection<ConnectionManager<PgConnection>>;
+
+    pub struct RegionsTable {
+        connection: PooledPg,
+    }
+
+    impl RegionsTable {
+        pub fn new(connection: PooledPg) -> RegionsTable {
+            RegionsTable { connection }
+        }
+
+        pub fn create_region(&mut self, new_region: NewRegion) -> Result<Region, diesel::result::Error> {
+            use schema::regions;
+
+            diesel::insert_into(regions::table)
+                .values(&new_region)
+                .get_result(&mut self.connection)
+        }
+
+        pub fn create_constellation(&mut self, new_constellation: NewConstellation) -> Result<Constellation, diesel::result::Error> {
+            use schema::constellations;
+
+            diesel::insert_into(constellations::table)
+                .values(&new_constellation)
+                .get_result(&mut self.connection)
+        }
+
+        pub fn create_star_system(&mut self, upsert_star_system: UpsertStarSystem) -> Result<StarSystem, diesel::result::Error> {
+            use schema::star_systems;
+
+            diesel::insert_into(star_systems::table)
+                .values(&upsert_star_system)
+                .get_result(&mut self.connection)
+        }
+
+        pub fn get_star_system(&mut self, star_system_id: i32) -> Result<Option<StarSystem>, diesel::result::Error> {
+            use schema::star_systems;
+
+            star_systems::table.find(star_system_id)
+                .get_result(&mut self.connection)
+                .optional()
+        }
+
+        // Renames or moves a star system. Locations follow the new name through the foreign key
+        pub fn update_star_system(&mut self, star_system_id: i32, upsert_star_system: UpsertStarSystem) -> Result<StarSystem, diesel::result::Error> {
+            use schema::star_systems;
+
+            diesel::update(star_systems::table.find(star_system_id))
+                .set((
+                    star_systems::constellation_id.eq(upsert_star_system.constellation_id),
+                    star_systems::name.eq(&upsert_star_system.name),
+                ))
+                .get_result(&mut self.connection)
+        }
+
+        // Every region in name order
+        pub fn list_regions(&mut self) -> Result<Vec<RegionNode>, diesel::result::Error> {
+            use schema::regions;
+
+            let regions = regions::table.order(regions::name.asc()).load::<Region>(&mut self.connection)?;
+            self.region_nodes(regions)
+        }
+
+        pub fn get_region(&mut self, region_id: i32) -> Result<Option<RegionNode>, diesel::result::Error> {
+            use schema::regions;
+
+            let region = regions::table.find(region_id).get_result::<Region>(&mut self.connection).optional()?;
+            Ok(self.region_nodes(region.into_iter().collect())?.pop())
+        }
+
+        pub fn get_constellation(&mut self, constellation_id: i32) -> Result<Option<Constellation>, diesel::result::Error> {
+            use schema::constellations;
+
+            constellations::table.find(constellation_id)
+                .get_result(&mut self.connection)
+                .optional()
+        }
+
+        // Constellations of the region in name order
+        pub fn list_constellations(&mut self, region_id: i32) -> Result<Vec<ConstellationNode>, diesel::result::Error> {
+            use schema::{constellations, star_systems};
+
+            let constellations = constellations::table
+                .filter(constellations::region_id.eq(region_id))
+                .order(constellations::name.asc())
+                .load::<Constellation>(&mut self.connection)?;
+
+            let ids: Vec<i32> = constellations.iter().map(|constellation| constellation.id).collect();
+            let system_counts: HashMap<i32, i64> = star_systems::table
+                .filter(star_systems::constellation_id.eq_any(&ids))
+                .group_by(star_systems::constellation_id)
+                .select((star_systems::constellation_id, count_star()))
+                .load(&mut self.connection)?
+                .into_iter()
+                .collect();
+
+            Ok(constellations.into_iter()
+                .map(|constellation| ConstellationNode {
+                    systems: system_counts.get(&constellation.id).copied().unwrap_or_default(),
+                    constellation,
+                })
+                .collect())
+        }
+
+        // Star systems of every constellation in the region, in name order
+        pub fn list_region_star_systems(&mut self, region_id: i32) -> Result<Vec<StarSystemNode>, diesel::result::Error> {
+            use schema::{constellations, star_systems};
+
+            let star_systems = star_systems::table
+                .inner_join(constellations::table)
+                .filter(constellations::region_id.eq(region_id))
+                .select(star_systems::all_columns)
+                .order(star_systems::name.asc())
+                .load::<StarSystem>(&mut self.connection)?;
+
+            self.star_system_nodes(star_systems)
+        }
+
+        // Star systems of the constellation in name order
+        pub fn list_constellation_star_systems(&mut self, constellation_id: i32) -> Result<Vec<StarSystemNode>, diesel::result::Error> {
+            use schema::star_systems;
+
+            let star_systems = star_systems::table
+                .filter(star_systems::constellation_id.eq(constellation_id))
+                .order(star_systems::name.asc())
+                .load::<StarSystem>(&mut self.connection)?;
+
+            self.star_system_nodes(star_systems)
+        }
+
+        fn region_nodes(&mut self, regions: Vec<Region>) -> Result<Vec<RegionNode>, diesel::result::Error> {
+            use schema::{constellations, star_systems};
+
+            let ids: Vec<i32> = regions.iter().map(|region| region.id).collect();
+
+            let constellation_counts: HashMap<i32, i64> = constellations::table
+                .filter(constellations::region_id.eq_any(&ids))
+                .group_by(constellations::region_id)
+                .select((constellations::region_id, count_star()))
+                .load(&mut self.connection)?
+                .into_iter()
+                .collect();
+
+            let system_counts: HashMap<i32, i64> = star_systems::table
+                .inner_join(constellations::table)
+                .filter(constellations::region_id.eq_any(&ids))
+                .group_by(constellations::region_id)
+                .select((constellations::region_id, count_star()))
+                .load(&mut self.connection)?
+                .into_iter()
+                .collect();
+
+            Ok(regions.into_iter()
+                .map(|region| RegionNode {
+                    constellations: constellation_counts.get(&region.id).copied().unwrap_or_default(),
+                    systems: system_counts.get(&region.id).copied().unwrap_or_default(),
+                    region,
+                })
+                .collect())
+        }
+
+        // Deleted locations are not counted
+        fn star_system_nodes(&mut self, star_systems: Vec<StarSystem>) -> Result<Vec<StarSystemNode>, diesel::result::Error> {
+            use schema::locations;
+
+            let ids: Vec<i32> = star_systems.iter().map(|star_system| star_system.id).collect();
+            let location_counts: HashMap<i32, i64> = locations::table
+                .filter(locations::star_system_id.eq_any(&ids))
+                .filter(locations::deleted_at.is_null())
+                .group_by(locations::star_system_id)
+                .select((locations::star_system_id, count_star()))
+                .load(&mut self.connection)?
+                .into_iter()
+                .collect();
+
+            Ok(star_systems.into_iter()
+                .map(|star_system| StarSystemNode {
+                    locations: location_counts.get(&star_system.id).copied().unwrap_or_default(),
+                    star_system,
+                })
+                .collect())
+        }
+    }
+}
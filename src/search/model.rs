@@ -0,0 +1,79 @@
+use serde_derive::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+// Longest accepted search text, in characters
+pub const MAX_SEARCH_LENGTH: usize = 200;
+
+// Type of a search result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchKind {
+    Empire,
+    Ship,
+    Location,
+}
+
+impl SearchKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchKind::Empire => "empire",
+            SearchKind::Ship => "ship",
+            SearchKind::Location => "location",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<SearchKind> {
+        match kind {
+            "empire" => Some(SearchKind::Empire),
+            "ship" => Some(SearchKind::Ship),
+            "location" => Some(SearchKind::Location),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchQuery {
+    /// Words to search for. Quoted phrases, 'or' and a leading '-' to exclude a word are understood
+    pub q: Option<String>,
+    /// Only results of this type
+    #[serde(rename = "type")]
+    #[param(inline)]
+    pub kind: Option<SearchKind>,
+    /// Maximum number of items, between 1 and 500. Defaults to 50
+    pub limit: Option<i64>,
+    /// Number of items to skip. Defaults to 0
+    pub offset: Option<i64>,
+}
+
+impl SearchQuery {
+    // The trimmed search text, which must not be empty
+    pub fn text(&self) -> Result<&str, String> {
+        let text = self.q.as_deref().unwrap_or_default().trim();
+
+        if text.is_empty() {
+            return Err("Query parameter 'q' must not be empty".to_string());
+        }
+        if text.chars().count() > MAX_SEARCH_LENGTH {
+            return Err(format!("Query parameter 'q' must not be longer than {} characters", MAX_SEARCH_LENGTH));
+        }
+        Ok(text)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SearchHit {
+    #[serde(rename = "type")]
+    pub kind: SearchKind,
+    /// Id of the empire, ship or location
+    pub id: i32,
+    /// Name of the empire or ship, star system and area of the location
+    pub title: String,
+    /// Fragments of the matching text, HTML escaped, with the matched words wrapped in <mark> and </mark>
+    pub highlight: String,
+    /// Relevance of the result, higher is better. Results are ordered by it
+    pub rank: f32,
+    /// Whether the row, or the empire of a ship, has been deleted, which only ADMIN callers get results for
+    pub deleted: bool,
+}
@@ -0,0 +1,220 @@
+pub mod router {
+    use serde_json::{json, Value};
+    use tracing::error;
+    use axum::{
+        Router, http::StatusCode, Json, response::IntoResponse, extract::{rejection::QueryRejection, Query, State},
+    };
+    use http::HeaderMap;
+    use crate::{
+        search::{
+            model::SearchQuery,
+            service::service::SearchIndex
+        },
+        users::model::{string_to_user_role, UserRole},
+        common::{
+            db::ConnectionPool,
+            pagination::{invalid_query, Page, Paginated},
+            security::{enforce_role_policy, decode_claims, has_role}
+        }
+    };
+
+    // - - - - - - - - - - - [ROUTES] - - - - - - - - - - -
+
+    pub fn search_route(shared_connection_pool: ConnectionPool) -> Router {
+        Router::new()
+            .route("/search", axum::routing::get(search_handler))
+            .with_state(shared_connection_pool)
+    }
+
+    // - - - - - - - - - - - [HANDLERS] - - - - - - - - - - -
+
+    /// Search empires, ships and locations
+    ///
+    /// Requires role READER or higher. Full-text search over the names, slogans, categories and descriptions, most relevant first.
+    /// Deleted empires and locations are only found by ADMIN callers.
+    #[utoipa::path(
+        get,
+        path = "/search",
+        tag = "search",
+        params(SearchQuery),
+        responses(
+            (status = 200, description = "Page of search results", body = SearchHitPage),
+            (status = 400, description = "Missing search text or malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while searching", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn search_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<SearchQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        let page = Page::new(query.limit, query.offset)?;
+        let text = query.text().map_err(|err| (StatusCode::BAD_REQUEST, Json(json!({"error": err}))))?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(authorized_user) => {
+                let include_deleted = authorized_user
+                    .is_some_and(|user| has_role(&string_to_user_role(user.role), &UserRole::ADMIN));
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                match SearchIndex::new(connection).search(text, query.kind, include_deleted, page) {
+                    Ok(hits) => Ok((StatusCode::OK, Json(Paginated::new(hits, page)))),
+                    Err(err) => {
+                        error!("Error searching: {:?}", err);
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to search"}))))
+                    }
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use axum::{
+            body::Body,
+            http::{Request, StatusCode}
+        };
+        use serde_json::Value;
+        use tower::ServiceExt;
+        use crate::{
+            common::{
+                db::{create_shared_connection_pool, ConnectionPool},
+                security::{generate_token, hash_password},
+                util::load_environment_variable
+            },
+            empires::{
+                model::UpsertEmpire,
+                service::service::EmpiresTable
+            },
+            locations::{
+                model::UpsertLocation,
+                service::service::LocationsTable
+            },
+            search::router::router::search_route,
+            ships::{
+                model::UpsertShip,
+                service::service::ShipsTable
+            },
+            users::{
+                model::{UpsertUser, UserRole},
+                service::service::UsersTable
+            }
+        };
+
+        fn create_user_and_generate_token(connection_pool: ConnectionPool, email: &str, user_role: UserRole) -> String {
+            let mut new_user = UpsertUser {
+                email: email.to_string(),
+                role: user_role.to_string(),
+                password: "FinnerAltSomErVerdtAFinne4".to_string(),
+                fullname: "Søkbar Sørensen".to_string()
+            };
+            hash_password(&mut new_user).expect("Hash failed");
+
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let created_user = UsersTable::new(connection).create(new_user).expect("Create user failed");
+            generate_token(&created_user).expect("Generate token failed")
+        }
+
+        async fn get_json(connection_pool: ConnectionPool, bearer_token: &str, uri: &str) -> (StatusCode, Value) {
+            let request = Request::builder()
+                .uri(uri)
+                .method("GET")
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .body(Body::empty())
+                .unwrap();
+
+            let response = search_route(connection_pool).oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            (status, serde_json::from_slice(&body).unwrap())
+        }
+
+        #[tokio::test]
+        async fn search_ranks_typed_highlighted_results_and_hides_deleted_rows_below_admin() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let reader_token = create_user_and_generate_token(connection_pool.clone(), "leteren@succulentmail.gb", UserRole::READER);
+            let admin_token = create_user_and_generate_token(connection_pool.clone(), "finneren@succulentmail.gb", UserRole::ADMIN);
+
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let location = LocationsTable::new(connection).create(UpsertLocation {
+                star_system: "Placid".to_string(), area: "Intaki".to_string(), x: None, y: None, z: None, security_status: 0.6,
+            }).expect("Create location failed");
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut empires = EmpiresTable::new(connection);
+            let empire = empires.create(UpsertEmpire {
+                name: "Intaki Syndicate".to_string(),
+                slogan: "Freedom to trade".to_string(),
+                location_id: location.id,
+                description: "Exiles of the Intaki homeworld".to_string(),
+            }, None).expect("Create empire failed");
+            let mut ships = ShipsTable::new(connection_pool.pool.get().expect("Failed to get connection"));
+            let ship = ships.create(UpsertShip {
+                name: "Vexorion".to_string(),
+                category: Some("Battleship".to_string()),
+                description: Some("Launches <b>drone</b> swarms & repairs them".to_string()),
+                empire_id: empire.id,
+            }).expect("Create ship failed");
+            drop(ships);
+
+            // The ship is found by its class and description, and its markup is escaped around the highlighted words
+            let (status, page) = get_json(connection_pool.clone(), &reader_token, "/search?q=vexorion%20drone%20battleship").await;
+            assert_eq!(status, StatusCode::OK);
+            let hits = page["items"].as_array().unwrap();
+            assert_eq!((hits[0]["type"].as_str(), hits[0]["id"].as_i64()), (Some("ship"), Some(ship.id as i64)));
+            assert_eq!(hits[0]["highlight"], "<mark>Vexorion</mark> - <mark>Battleship</mark> - Launches &lt;b&gt;<mark>drone</mark>&lt;/b&gt; swarms &amp; repairs them");
+
+            let (_, page) = get_json(connection_pool.clone(), &reader_token, "/search?q=drone%20battleship").await;
+            let hits = page["items"].as_array().unwrap();
+            assert!(hits.windows(2).all(|pair| pair[0]["rank"].as_f64() >= pair[1]["rank"].as_f64()));
+
+            let (_, page) = get_json(connection_pool.clone(), &reader_token, "/search?q=intaki&type=location").await;
+            assert!(page["items"].as_array().unwrap().iter().all(|hit| hit["type"] == "location"));
+            assert_eq!(page["items"][0]["title"], "Placid - Intaki");
+
+            // Ships of a deleted empire are only found by admins, as the empire itself
+            empires.delete(empire.id, None).expect("Delete empire failed");
+            drop(empires);
+
+            let (_, page) = get_json(connection_pool.clone(), &reader_token, "/search?q=vexorion").await;
+            assert!(page["items"].as_array().unwrap().is_empty());
+
+            let (_, page) = get_json(connection_pool.clone(), &admin_token, "/search?q=vexorion").await;
+            assert_eq!((page["items"][0]["id"].as_i64(), &page["items"][0]["deleted"]), (Some(ship.id as i64), &Value::Bool(true)));
+
+            let (status, _) = get_json(connection_pool.clone(), &reader_token, "/search?q=%20").await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+
+            // A deleted location is only found by admins
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut locations = LocationsTable::new(connection);
+            let location = locations.create(UpsertLocation {
+                star_system: "Zarzakh".to_string(), area: "Gatefeld".to_string(), x: None, y: None, z: None, security_status: -1.0,
+            }).expect("Create location failed");
+            locations.delete(location.id, None).expect("Delete location failed");
+            drop(locations);
+
+            let (_, page) = get_json(connection_pool.clone(), &reader_token, "/search?q=gatefeld").await;
+            assert!(page["items"].as_array().unwrap().is_empty());
+
+            let (_, page) = get_json(connection_pool.clone(), &admin_token, "/search?q=gatefeld").await;
+            assert_eq!(page["items"][0]["id"], location.id);
+            assert_eq!(page["items"][0]["deleted"], true);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+pub mod service {
+    use diesel::{
+        prelude::*,
+        sql_types::{BigInt, Bool, Float4, Integer, Nullable, Text},
+        PgConnection,
+        r2d2::{ConnectionManager, PooledConnection},
+    };
+    use crate::{
+        common::pagination::Page,
+        search::model::{SearchHit, SearchKind},
+    };
+
+    type PooledPg = PooledConnection<ConnectionManager<PgConnection>>;
+
+    // Matches of every searched table are ranked together, and only the page returned is highlighted. A location is matched
+    // by its area together with the name of its star system, and a ship together with the name of its class, which live in
+    // the star system and the ship class. Ships of a deleted empire count as deleted. The document is HTML escaped before
+    // the matched words are wrapped in <mark>, so that the highlight is safe to render as HTML
+    const SEARCH: &str = "\
+        WITH hits AS ( \
+            SELECT 'empire' AS kind, id, name::text AS title, concat_ws(' - ', name, slogan, description) AS document, \
+                   ts_rank(search_vector, query) AS rank, deleted_at IS NOT NULL AS deleted \
+            FROM empires, websearch_to_tsquery('english', $1) query \
+            WHERE search_vector @@ query AND (deleted_at IS NULL OR $2) \
+            UNION ALL \
+            SELECT 'ship', ships.id, ships.name, concat_ws(' - ', ships.name, ship_classes.name, ships.description), \
+                   ts_rank(ships.search_vector || coalesce(ship_classes.search_vector, ''::tsvector), query), empires.deleted_at IS NOT NULL \
+            FROM ships JOIN empires ON empires.id = ships.empire_id LEFT JOIN ship_classes ON ship_classes.id = ships.ship_class_id, \
+                 websearch_to_tsquery('english', $1) query \
+            WHERE (ships.search_vector || coalesce(ship_classes.search_vector, ''::tsvector)) @@ query AND (empires.deleted_at IS NULL OR $2) \
+            UNION ALL \
+            SELECT 'location', locations.id, star_systems.name || ' - ' || area, star_systems.name || ' - ' || area, \
+                   ts_rank(locations.search_vector || star_systems.search_vector, query), locations.deleted_at IS NOT NULL \
+            FROM locations JOIN star_systems ON star_systems.id = locations.star_system_id, websearch_to_tsquery('english', $1) query \
+            WHERE (locations.search_vector || star_systems.search_vector) @@ query AND (locations.deleted_at IS NULL OR $2) \
+        ), page AS ( \
+            SELECT * FROM hits WHERE $3::text IS NULL OR kind = $3 \
+            ORDER BY rank DESC, kind, id LIMIT $4 OFFSET $5 \
+        ) \
+        SELECT kind, id, title, rank, deleted, \
+               ts_headline('english', replace(replace(replace(replace(document, '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), '\"', '&quot;'), \
+                           websearch_to_tsquery('english', $1), 'StartSel=<mark>, StopSel=</mark>, MaxFragments=2, MinWords=5, MaxWords=20') AS highlight \
+        FROM page ORDER BY rank DESC, kind, id";
+
+    #[derive(QueryableByName)]
+    struct SearchRow {
+        #[diesel(sql_type = Text)]
+        kind: String,
+        #[diesel(sql_type = Integer)]
+        id: i32,
+        #[diesel(sql_type = Text)]
+        title: String,
+        #[diesel(sql_type = Text)]
+        highlight: String,
+        #[diesel(sql_type = Float4)]
+        rank: f32,
+        #[diesel(sql_type = Bool)]
+        deleted: bool,
+    }
+
+    pub struct SearchIndex {
+        connection: PooledPg,
+    }
+
+    impl SearchIndex {
+        pub fn new(connection: PooledPg) -> SearchIndex {
+            SearchIndex { connection }
+        }
+
+        // Empires, ships and locations matching 'text', most relevant first. Deleted rows are only matched with 'include_deleted'
+        pub fn search(&mut self, text: &str, kind: Option<SearchKind>, include_deleted: bool, page: Page) -> Result<Vec<SearchHit>, diesel::result::Error> {
+            let rows: Vec<SearchRow> = diesel::sql_query(SEARCH)
+                .bind::<Text, _>(text)
+                .bind::<Bool, _>(include_deleted)
+                .bind::<Nullable<Text>, _>(kind.map(|kind| kind.as_str()))
+                .bind::<BigInt, _>(page.limit)
+                .bind::<BigInt, _>(page.offset)
+                .load(&mut self.connection)?;
+
+            Ok(rows.into_iter()
+                .filter_map(|row| Some(SearchHit {
+                    kind: SearchKind::parse(&row.kind)?,
+                    id: row.id,
+                    title: row.title,
+                    highlight: row.highlight,
+                    rank: row.rank,
+                    deleted: row.deleted,
+                }))
+                .collect())
+        }
+    }
+}
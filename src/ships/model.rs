@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde_derive::{Serialize, Deserialize};
+use utoipa::{IntoParams, ToSchema};
+use diesel::{dsl::sql, expression::SqlLiteral, sql_types::{Integer, Nullable, Text, Timestamptz}};
+use crate::{common::{fields::FieldSet, util::check_length}, schema::ship_classes};
+
+#[derive(Serialize, Debug, Clone, Queryable, ToSchema)]
+pub struct Ship {
+    pub id: i32,
+    pub name: String,
+    /// Id of the ship class
+    pub ship_class_id: Option<i32>,
+    /// Name of the ship class, read from the class the ship refers to
+    pub category: Option<String>,
+    pub description: Option<String>,
+    pub empire_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Selection loading a Ship, see Ship::columns
+pub type ShipColumns = (
+    SqlLiteral<Integer>, SqlLiteral<Text>, SqlLiteral<Nullable<Integer>>, SqlLiteral<Nullable<Text>>, SqlLiteral<Nullable<Text>>, SqlLiteral<Integer>, SqlLiteral<Timestamptz>, SqlLiteral<Timestamptz>,
+);
+
+impl Ship {
+    pub const FIELDS: [&'static str; 8] = ["id", "name", "ship_class_id", "category", "description", "empire_id", "created_at", "updated_at"];
+
+    // Columns of the row left joined with its class in model order, reading only those of the fieldset besides the id
+    pub fn columns(fields: &FieldSet) -> ShipColumns {
+        (
+            sql("ships.id"),
+            fields.column("name", "ships.name", "''"),
+            fields.column("ship_class_id", "ships.ship_class_id", "NULL"),
+            fields.column("category", "ship_classes.name", "NULL"),
+            fields.column("description", "ships.description", "NULL"),
+            fields.column("empire_id", "ships.empire_id", "0"),
+            fields.column("created_at", "ships.created_at", "'epoch'::timestamptz"),
+            fields.column("updated_at", "ships.updated_at", "'epoch'::timestamptz"),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct UpsertShip {
+    pub name: String,
+    /// Name of an existing ship class, resolved to its id when the ship is written
+    pub category: Option<String>,
+    pub description: Option<String>,
+    pub empire_id: i32,
+}
+
+impl UpsertShip {
+    // The description is unbounded TEXT, the other text columns are VARCHAR
+    pub fn validate(&self) -> Result<(), String> {
+        check_length("name", &self.name, 100)?;
+        check_length("category", self.category.as_deref().unwrap_or_default(), 50)
+    }
+}
+
+// Filters of 'GET /ships'
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ShipQuery {
+    /// Only ships of the class with this name
+    pub class: Option<String>,
+    /// Maximum number of items, between 1 and 500. Defaults to 50
+    pub limit: Option<i64>,
+    /// Number of items to skip. Defaults to 0
+    pub offset: Option<i64>,
+}
+
+// Class a ship is built to, its ships refer to it by id and carry its name as their category
+#[derive(Serialize, Debug, Clone, Queryable, ToSchema)]
+#[diesel(table_name = ship_classes)]
+pub struct ShipClass {
+    pub id: i32,
+    pub name: String,
+    /// Hit points of the hull
+    pub hull: i32,
+    /// Hit points of the armor
+    pub armor: i32,
+    /// Hit points of the shield
+    pub shield: i32,
+    /// Maximum velocity in m/s
+    pub speed: i32,
+    /// Capacity of the cargo hold in m3
+    pub cargo: i32,
+    /// Number of fitting slots
+    pub slots: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Insertable, AsChangeset, Deserialize, Serialize, ToSchema)]
+#[diesel(table_name = ship_classes)]
+pub struct UpsertShipClass {
+    pub name: String,
+    pub hull: i32,
+    pub armor: i32,
+    pub shield: i32,
+    pub speed: i32,
+    pub cargo: i32,
+    pub slots: i32,
+}
+
+impl UpsertShipClass {
+    pub fn validate(&self) -> Result<(), String> {
+        check_length("name", &self.name, 50)?;
+
+        let stats = [("hull", self.hull), ("armor", self.armor), ("shield", self.shield), ("speed", self.speed), ("cargo", self.cargo), ("slots", self.slots)];
+        match stats.iter().find(|(_, value)| *value < 0) {
+            Some((stat, _)) => Err(format!("'{}' must not be negative", stat)),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,946 @@
+pub mod router {
+    use std::collections::HashSet;
+    use serde_json::{json, Value};
+    use tracing::error;
+    use axum::{
+        Router, http::StatusCode, Json, body::Bytes, response::{IntoResponse, Response}, extract::{rejection::QueryRejection, Query, State}, extract,
+    };
+    use http::HeaderMap;
+    use crate::{
+        audit::{model::AuditAction, service::service::record_event},
+        common::db::ConnectionPool,
+        empires::service::service::EmpiresTable,
+        ships::{
+            service::service::ShipsTable,
+            model::{Ship, ShipQuery, UpsertShip, UpsertShipClass}
+        },
+        users::model::UserRole,
+        common::{
+            bulk::{apply_all, bulk_failure, bulk_response, check_bulk_size, validate_all, with_rejected, BulkMode, BulkQuery, BulkUpdate},
+            fields::FieldsQuery,
+            pagination::{invalid_query, Page, Paginated},
+            transfer::{export, export_format, import_format, invalid_rows, parse_rows, ExportQuery, ImportQuery, ImportReport, RowError},
+            security::{enforce_role_policy, decode_claims}
+        }
+    };
+
+    // - - - - - - - - - - - [ROUTES] - - - - - - - - - - -
+
+    pub fn ships_route(shared_connection_pool: ConnectionPool) -> Router {
+        Router::new()
+            .route("/ships", axum::routing::get(list_ships_handler))
+            .route("/ships/bulk", axum::routing::post(bulk_create_ships_handler))
+            .route("/ships/bulk", axum::routing::put(bulk_update_ships_handler))
+            .route("/ships/bulk", axum::routing::delete(bulk_delete_ships_handler))
+            .route("/ships/export", axum::routing::get(export_ships_handler))
+            .route("/ships/import", axum::routing::post(import_ships_handler))
+            .route("/ships/:ship_id", axum::routing::get(read_ship_handler))
+            .route("/ship-classes", axum::routing::post(create_ship_class_handler))
+            .route("/ship-classes", axum::routing::get(list_ship_classes_handler))
+            .route("/ship-classes/:class_id", axum::routing::get(read_ship_class_handler))
+            .route("/ship-classes/:class_id", axum::routing::put(update_ship_class_handler))
+            .route("/ship-classes/:class_id", axum::routing::delete(delete_ship_class_handler))
+            .with_state(shared_connection_pool)
+    }
+
+    // - - - - - - - - - - - [HANDLERS] - - - - - - - - - - -
+
+    /// List ships
+    ///
+    /// Requires role READER or higher. Ordered by id, optionally limited to one class with '?class='. '?fields=' limits the fields of each ship.
+    #[utoipa::path(
+        get,
+        path = "/ships",
+        tag = "ships",
+        params(ShipQuery, FieldsQuery),
+        responses(
+            (status = 200, description = "Page of ships", body = ShipPage),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing ships", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_ships_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<ShipQuery>, QueryRejection>,
+        fields: Result<Query<FieldsQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        let page = Page::new(query.limit, query.offset)?;
+        let fields = fields.map_err(invalid_query)?.parse(&Ship::FIELDS)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                match ShipsTable::new(connection).list(&query, page, &fields) {
+                    Ok(ships) => {
+                        let items = ships.iter().map(|ship| fields.project(ship, &[])).collect();
+                        Ok((StatusCode::OK, Json(Paginated::new(items, page))))
+                    }
+                    Err(err) => {
+                        error!("Error listing ships: {:?}", err);
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list ships"}))))
+                    }
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Read a ship by id
+    ///
+    /// Requires role READER or higher. '?fields=' limits the fields of the ship.
+    #[utoipa::path(
+        get,
+        path = "/ships/{ship_id}",
+        tag = "ships",
+        params(("ship_id" = i32, Path, description = "Id of the ship"), FieldsQuery),
+        responses(
+            (status = 200, description = "Ship found", body = Ship),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Ship not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while reading ship", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn read_ship_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+        fields: Result<Query<FieldsQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (ship_id, ) = path.0;
+        let fields = fields.map_err(invalid_query)?.parse(&Ship::FIELDS)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                match ShipsTable::new(connection).read(ship_id, &fields) {
+                    Ok(Some(ship)) => Ok((StatusCode::OK, Json(fields.project(&ship, &[])))),
+                    Ok(None) => Err((StatusCode::NOT_FOUND, Json(json!({"error": "Ship not found"})))),
+                    Err(err) => {
+                        error!("Error reading ship: {:?}", err);
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to read ship"}))))
+                    }
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Create ships in bulk
+    ///
+    /// Requires role WRITER or higher. In transactional mode the first failing item rolls back every item and is answered with its status and index.
+    #[utoipa::path(
+        post,
+        path = "/ships/bulk",
+        tag = "ships",
+        params(BulkQuery),
+        request_body = Vec<UpsertShip>,
+        responses(
+            (status = 200, description = "Every ship created", body = BulkResponse),
+            (status = 207, description = "Outcome of each ship in partial mode", body = BulkResponse),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 413, description = "Too many items", body = ErrorBody),
+            (status = 422, description = "A ship is invalid or its empire or ship class does not exist, all items rolled back", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while creating ships", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn bulk_create_ships_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<BulkQuery>, QueryRejection>,
+        Json(upsert_ships): Json<Vec<UpsertShip>>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        check_bulk_size(upsert_ships.len())?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'WRITER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::WRITER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let (upsert_ships, rejected) = validate_all(upsert_ships, query.mode(), UpsertShip::validate)?;
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut ships = ShipsTable::new(connection);
+                let result = apply_all(&mut ships, upsert_ships, query.mode(), |ships, upsert_ship| ships.create(upsert_ship));
+                drop(ships);
+
+                let outcomes = result.map_err(|err| bulk_failure(err, bulk_error))?;
+
+                for new_ship in outcomes.iter().flatten() {
+                    record_event(&shared_state, &headers, AuditAction::Create, "ship", new_ship.id, None, Some(new_ship));
+                }
+
+                let outcomes = outcomes.into_iter()
+                    .map(|outcome| outcome.map(|new_ship| new_ship.id).map_err(|err| bulk_error(&err)))
+                    .collect();
+
+                Ok(bulk_response(query.mode(), StatusCode::CREATED, with_rejected(outcomes, rejected)))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Replace ships in bulk
+    ///
+    /// Requires role EDITOR or higher. Each item holds the id of the ship next to its new representation.
+    #[utoipa::path(
+        put,
+        path = "/ships/bulk",
+        tag = "ships",
+        params(BulkQuery),
+        request_body = Vec<ShipUpdate>,
+        responses(
+            (status = 200, description = "Every ship updated", body = BulkResponse),
+            (status = 207, description = "Outcome of each ship in partial mode", body = BulkResponse),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Ship not found, all items rolled back", body = ErrorBody),
+            (status = 413, description = "Too many items", body = ErrorBody),
+            (status = 422, description = "A ship is invalid or its empire or ship class does not exist, all items rolled back", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while updating ships", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn bulk_update_ships_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<BulkQuery>, QueryRejection>,
+        Json(ship_updates): Json<Vec<BulkUpdate<UpsertShip>>>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        check_bulk_size(ship_updates.len())?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'EDITOR' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::EDITOR).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let (ship_updates, rejected) = validate_all(ship_updates, query.mode(), |update: &BulkUpdate<UpsertShip>| update.body.validate())?;
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut ships = ShipsTable::new(connection);
+                let result = apply_all(&mut ships, ship_updates, query.mode(), |ships, BulkUpdate { id, body }| {
+                    let before = ships.get(id)?.ok_or(diesel::result::Error::NotFound)?;
+                    ships.update(id, body).map(|after| (before, after))
+                });
+                drop(ships);
+
+                let outcomes = result.map_err(|err| bulk_failure(err, bulk_error))?;
+
+                for (before, after) in outcomes.iter().flatten() {
+                    record_event(&shared_state, &headers, AuditAction::Update, "ship", after.id, Some(before), Some(after));
+                }
+
+                let outcomes = outcomes.into_iter()
+                    .map(|outcome| outcome.map(|(_, updated_ship)| updated_ship.id).map_err(|err| bulk_error(&err)))
+                    .collect();
+
+                Ok(bulk_response(query.mode(), StatusCode::OK, with_rejected(outcomes, rejected)))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Delete ships in bulk
+    ///
+    /// Requires role ADMIN. The body is the array of ids to delete. Ships are deleted for good.
+    #[utoipa::path(
+        delete,
+        path = "/ships/bulk",
+        tag = "ships",
+        params(BulkQuery),
+        request_body = Vec<i32>,
+        responses(
+            (status = 200, description = "Every ship deleted", body = BulkResponse),
+            (status = 207, description = "Outcome of each ship in partial mode", body = BulkResponse),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Ship not found, all items rolled back", body = ErrorBody),
+            (status = 409, description = "Ship flown by a player, all items rolled back", body = ErrorBody),
+            (status = 413, description = "Too many items", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while deleting ships", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn bulk_delete_ships_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<BulkQuery>, QueryRejection>,
+        Json(ship_ids): Json<Vec<i32>>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        check_bulk_size(ship_ids.len())?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'ADMIN'
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::ADMIN).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut ships = ShipsTable::new(connection);
+                let result = apply_all(&mut ships, ship_ids, query.mode(), |ships, ship_id| {
+                    let before = ships.get(ship_id)?.ok_or(diesel::result::Error::NotFound)?;
+                    ships.delete(ship_id).map(|_| before)
+                });
+                drop(ships);
+
+                let outcomes = result.map_err(|err| bulk_failure(err, bulk_delete_error))?;
+
+                for deleted_ship in outcomes.iter().flatten() {
+                    record_event(&shared_state, &headers, AuditAction::Delete, "ship", deleted_ship.id, Some(deleted_ship), None);
+                }
+
+                let outcomes = outcomes.into_iter()
+                    .map(|outcome| outcome.map(|deleted_ship| deleted_ship.id).map_err(|err| bulk_delete_error(&err)))
+                    .collect();
+
+                Ok(bulk_response(query.mode(), StatusCode::NO_CONTENT, outcomes))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Export ships
+    ///
+    /// Requires role READER or higher. Streams every ship in id order as CSV or NDJSON, chosen with '?format=' or else the Accept header.
+    #[utoipa::path(
+        get,
+        path = "/ships/export",
+        tag = "ships",
+        params(ExportQuery),
+        responses(
+            (status = 200, description = "Every ship", content(("text/csv" = String), ("application/x-ndjson" = String))),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 406, description = "Neither CSV nor NDJSON is acceptable", body = ErrorBody),
+            (status = 500, description = "Missing header", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn export_ships_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<ExportQuery>, QueryRejection>,
+    ) -> Result<Response, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        let format = export_format(query.format, &headers)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut ships = ShipsTable::new(connection);
+                Ok(export(format, move |after_id, limit| ships.list_after(after_id, limit), |ship: &Ship| ship.id))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Import ships
+    ///
+    /// Requires role WRITER or higher. Creates a ship from every CSV record or NDJSON line, chosen with '?format=' or else the Content-Type header.
+    /// Every row is checked against the ship model and the empire and class it refers to before anything is written, and '?dry_run=true' stops after the check.
+    #[utoipa::path(
+        post,
+        path = "/ships/import",
+        tag = "ships",
+        params(ImportQuery),
+        request_body(content = String, description = "CSV with a header row naming the columns, or one JSON object per line", content_type = "text/csv"),
+        responses(
+            (status = 200, description = "Every row is valid, nothing written in dry-run mode", body = ImportReport),
+            (status = 201, description = "Every row imported", body = ImportReport),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 415, description = "Neither CSV nor NDJSON", body = ErrorBody),
+            (status = 422, description = "Invalid rows, nothing written", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while importing ships", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn import_ships_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<ImportQuery>, QueryRejection>,
+        body: Bytes,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        let format = import_format(query.format, &headers)?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'WRITER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::WRITER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let rows = parse_rows::<UpsertShip>(format, &body).map_err(invalid_rows)?;
+
+                let validation_failed = |err: diesel::result::Error| {
+                    error!("Error validating ships import: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to import ships"})))
+                };
+
+                // Ships may only refer to empires that exist and have not been deleted, and to existing classes
+                let empire_ids: Vec<i32> = rows.iter().map(|(_, ship)| ship.empire_id).collect();
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+                let known_empire_ids: HashSet<i32> = EmpiresTable::new(connection).existing_ids(&empire_ids).map_err(validation_failed)?
+                    .into_iter().collect();
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+                let known_classes: HashSet<String> = ShipsTable::new(connection).list_classes().map_err(validation_failed)?
+                    .into_iter().map(|ship_class| ship_class.name).collect();
+
+                let invalid: Vec<RowError> = rows.iter()
+                    .filter_map(|(row, ship)| {
+                        ship.validate().err()
+                            .or_else(|| (!known_empire_ids.contains(&ship.empire_id)).then(|| format!("Empire {} does not exist", ship.empire_id)))
+                            .or_else(|| ship.category.as_ref()
+                                .filter(|category| !known_classes.contains(*category))
+                                .map(|category| format!("Ship class '{}' does not exist", category)))
+                            .map(|error| RowError { row: *row, error })
+                    })
+                    .collect();
+
+                if !invalid.is_empty() {
+                    return Err(invalid_rows(invalid));
+                }
+                if query.dry_run {
+                    return Ok((StatusCode::OK, Json(ImportReport { rows: rows.len(), dry_run: true, ids: Vec::new() })));
+                }
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut ships = ShipsTable::new(connection);
+                let upsert_ships = rows.into_iter().map(|(_, ship)| ship).collect();
+                let result = apply_all(&mut ships, upsert_ships, BulkMode::Transactional, |ships, upsert_ship| ships.create(upsert_ship));
+                drop(ships);
+
+                let created_ships: Vec<Ship> = result.map_err(|err| bulk_failure(err, bulk_error))?.into_iter().flatten().collect();
+
+                for new_ship in &created_ships {
+                    record_event(&shared_state, &headers, AuditAction::Create, "ship", new_ship.id, None, Some(new_ship));
+                }
+
+                let ids = created_ships.iter().map(|new_ship| new_ship.id).collect();
+                Ok((StatusCode::CREATED, Json(ImportReport { rows: created_ships.len(), dry_run: false, ids })))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Create a ship class
+    ///
+    /// Requires role WRITER or higher.
+    #[utoipa::path(
+        post,
+        path = "/ship-classes",
+        tag = "ships",
+        request_body = UpsertShipClass,
+        responses(
+            (status = 201, description = "Ship class created", body = ShipClass),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 409, description = "Ship class name is already taken", body = ErrorBody),
+            (status = 422, description = "Name is too long or a stat is negative", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while creating ship class", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn create_ship_class_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        Json(upsert_ship_class): Json<UpsertShipClass>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'WRITER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::WRITER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                upsert_ship_class.validate().map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": err}))))?;
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let result = ShipsTable::new(connection).create_class(upsert_ship_class);
+
+                match result {
+                    Ok(new_ship_class) => {
+                        record_event(&shared_state, &headers, AuditAction::Create, "ship_class", new_ship_class.id, None, Some(&new_ship_class));
+                        Ok((StatusCode::CREATED, Json(new_ship_class)))
+                    },
+                    Err(err) => Err(class_write_error(err, "create"))
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// List ship classes
+    ///
+    /// Requires role READER or higher. Ordered by name, each class with its base stats.
+    #[utoipa::path(
+        get,
+        path = "/ship-classes",
+        tag = "ships",
+        responses(
+            (status = 200, description = "Every ship class", body = [ShipClass]),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing ship classes", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_ship_classes_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                match ShipsTable::new(connection).list_classes() {
+                    Ok(ship_classes) => Ok((StatusCode::OK, Json(ship_classes))),
+                    Err(err) => {
+                        error!("Error listing ship classes: {:?}", err);
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list ship classes"}))))
+                    }
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Read a ship class by id
+    ///
+    /// Requires role READER or higher.
+    #[utoipa::path(
+        get,
+        path = "/ship-classes/{class_id}",
+        tag = "ships",
+        params(("class_id" = i32, Path, description = "Id of the ship class")),
+        responses(
+            (status = 200, description = "Ship class found", body = ShipClass),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Ship class not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while reading ship class", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn read_ship_class_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (class_id, ) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                match ShipsTable::new(connection).get_class(class_id) {
+                    Ok(Some(ship_class)) => Ok((StatusCode::OK, Json(ship_class))),
+                    Ok(None) => Err((StatusCode::NOT_FOUND, Json(json!({"error": "Ship class not found"})))),
+                    Err(err) => {
+                        error!("Error reading ship class: {:?}", err);
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to read ship class"}))))
+                    }
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Rename a ship class or change its stats
+    ///
+    /// Requires role EDITOR or higher. Ships of the class take on the new name.
+    #[utoipa::path(
+        put,
+        path = "/ship-classes/{class_id}",
+        tag = "ships",
+        params(("class_id" = i32, Path, description = "Id of the ship class")),
+        request_body = UpsertShipClass,
+        responses(
+            (status = 200, description = "Ship class updated", body = ShipClass),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Ship class not found", body = ErrorBody),
+            (status = 409, description = "Ship class name is already taken", body = ErrorBody),
+            (status = 422, description = "Name is too long or a stat is negative", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while updating ship class", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn update_ship_class_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+        Json(upsert_ship_class): Json<UpsertShipClass>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (class_id, ) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'EDITOR' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::EDITOR).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                upsert_ship_class.validate().map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": err}))))?;
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut ships = ShipsTable::new(connection);
+
+                let before = ships.get_class(class_id).map_err(|err| class_write_error(err, "update"))?;
+                let result = ships.update_class(class_id, upsert_ship_class);
+                drop(ships);
+
+                match result {
+                    Ok(updated_ship_class) => {
+                        record_event(&shared_state, &headers, AuditAction::Update, "ship_class", class_id, before.as_ref(), Some(&updated_ship_class));
+                        Ok((StatusCode::OK, Json(updated_ship_class)))
+                    },
+                    Err(err) => Err(class_write_error(err, "update"))
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Delete a ship class
+    ///
+    /// Requires role ADMIN. Only classes without ships can be deleted.
+    #[utoipa::path(
+        delete,
+        path = "/ship-classes/{class_id}",
+        tag = "ships",
+        params(("class_id" = i32, Path, description = "Id of the ship class")),
+        responses(
+            (status = 204, description = "Ship class deleted"),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Ship class not found", body = ErrorBody),
+            (status = 409, description = "Ship class is still used by ships", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while deleting ship class", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn delete_ship_class_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (class_id, ) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'ADMIN'
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::ADMIN).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut ships = ShipsTable::new(connection);
+
+                let before = ships.get_class(class_id).map_err(|err| class_write_error(err, "delete"))?;
+                let result = ships.delete_class(class_id);
+                drop(ships);
+
+                match result {
+                    Ok(()) => {
+                        record_event(&shared_state, &headers, AuditAction::Delete, "ship_class", class_id, before.as_ref(), None);
+                        Ok(StatusCode::NO_CONTENT)
+                    },
+                    Err(err) => Err(class_write_error(err, "delete"))
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    // Status and error body of a failed write to a ship class. The foreign key only stops a class with ships from being deleted
+    fn class_write_error(err: diesel::result::Error, action: &str) -> (StatusCode, Json<Value>) {
+        use diesel::result::{DatabaseErrorKind, Error};
+
+        match err {
+            Error::NotFound => (StatusCode::NOT_FOUND, Json(json!({"error": "Ship class not found"}))),
+            Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                (StatusCode::CONFLICT, Json(json!({"error": "Ship class name is already taken"})))
+            },
+            Error::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => {
+                (StatusCode::CONFLICT, Json(json!({"error": "Ship class is still used by ships"})))
+            },
+            err => {
+                error!("Error writing ship class: {:?}", err);
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to {} ship class", action)})))
+            }
+        }
+    }
+
+    // Status and message of an item that failed in a bulk request. Ships refer to their empire and to their class
+    fn bulk_error(err: &diesel::result::Error) -> (StatusCode, String) {
+        match err {
+            diesel::result::Error::NotFound => (StatusCode::NOT_FOUND, "Ship not found".to_string()),
+            diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::ForeignKeyViolation, info) => {
+                if info.constraint_name() == Some("ships_ship_class_id_fkey") {
+                    (StatusCode::UNPROCESSABLE_ENTITY, "Ship class does not exist".to_string())
+                } else {
+                    (StatusCode::UNPROCESSABLE_ENTITY, "Empire does not exist".to_string())
+                }
+            },
+            err => {
+                error!("Error applying bulk ship item: {:?}", err);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Failed to apply ship".to_string())
+            }
+        }
+    }
+
+    // The only foreign key violated by deleting a ship is the reference of a player flying it
+    fn bulk_delete_error(err: &diesel::result::Error) -> (StatusCode, String) {
+        match err {
+            diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::ForeignKeyViolation, _) => {
+                (StatusCode::CONFLICT, "Ship is flown by a player".to_string())
+            },
+            err => bulk_error(err),
+        }
+    }
+    #[cfg(test)]
+    mod tests {
+        use axum::{
+            body::Body,
+            http::{Request, StatusCode}
+        };
+        use serde_json::{json, Value};
+        use tower::ServiceExt;
+        use crate::{
+            common::{
+                db::{create_shared_connection_pool, ConnectionPool},
+                security::{generate_token, hash_password},
+                util::load_environment_variable
+            },
+            empires::{model::UpsertEmpire, service::service::EmpiresTable},
+            locations::{model::UpsertLocation, service::service::LocationsTable},
+            ships::router::router::ships_route,
+            users::{
+                model::{UpsertUser, UserRole},
+                service::service::UsersTable
+            }
+        };
+
+        fn create_user_and_generate_token(connection_pool: ConnectionPool, email: &str, user_role: UserRole) -> String {
+            let mut new_user = UpsertUser {
+                email: email.to_string(),
+                role: user_role.to_string(),
+                password: "SkrogOgSkjoldOgLast3".to_string(),
+                fullname: "Verftsmester Vik".to_string()
+            };
+            hash_password(&mut new_user).expect("Hash failed");
+
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let created_user = UsersTable::new(connection).create(new_user).expect("Create user failed");
+            generate_token(&created_user).expect("Generate token failed")
+        }
+
+        async fn send(connection_pool: ConnectionPool, bearer_token: &str, method: &str, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+            let request = Request::builder()
+                .uri(uri)
+                .method(method)
+                .header("content-type", "application/json")
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .body(body.map(|body| Body::from(body.to_string())).unwrap_or_else(Body::empty))
+                .unwrap();
+
+            let response = ships_route(connection_pool).oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+        }
+
+        #[tokio::test]
+        async fn ships_are_linked_to_classes_and_filtered_by_them() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let bearer_token = create_user_and_generate_token(connection_pool.clone(), "verftet@succulentmail.gb", UserRole::ADMIN);
+
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let location = LocationsTable::new(connection).create(UpsertLocation {
+                star_system: "Heimatar".to_string(),
+                area: "Sebiestor".to_string(),
+                ..UpsertLocation::default()
+            }).expect("Create location failed");
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let empire_id = EmpiresTable::new(connection).create(UpsertEmpire {
+                name: "Sebiestor Tribe".to_string(),
+                slogan: "Engineers of the republic".to_string(),
+                location_id: location.id,
+                description: "A tribe of the Minmatar Republic".to_string(),
+            }, None).expect("Create empire failed").id;
+
+            // Seeded categories became classes with base stats
+            let (status, ship_classes) = send(connection_pool.clone(), &bearer_token, "GET", "/ship-classes", None).await;
+            assert_eq!(status, StatusCode::OK);
+            let battleship = ship_classes.as_array().unwrap().iter().find(|ship_class| ship_class["name"] == "Battleship").unwrap();
+            assert_eq!((battleship["hull"].as_i64(), battleship["slots"].as_i64()), (Some(7000), Some(19)));
+
+            let frigate_class_id = ship_classes.as_array().unwrap().iter().find(|ship_class| ship_class["name"] == "Frigate").unwrap()["id"].clone();
+
+            // Ships name their class and refer to it by id
+            let (status, results) = send(connection_pool.clone(), &bearer_token, "POST", "/ships/bulk",
+                Some(json!([{"name": "Rifter", "category": "Frigate", "empire_id": empire_id}]))).await;
+            assert_eq!(status, StatusCode::OK);
+            let (_, rifter) = send(connection_pool.clone(), &bearer_token, "GET", &format!("/ships/{}", results["results"][0]["id"]), None).await;
+            assert_eq!(rifter["ship_class_id"], frigate_class_id);
+
+            let (status, page) = send(connection_pool.clone(), &bearer_token, "GET", "/ships?class=Frigate&limit=500", None).await;
+            assert_eq!(status, StatusCode::OK);
+            let frigates = page["items"].as_array().unwrap();
+            assert!(frigates.iter().all(|ship| ship["category"] == "Frigate"));
+            assert!(frigates.iter().any(|ship| ship["id"] == rifter["id"]));
+
+            let carrier = json!({"name": "Carrier", "hull": 60000, "armor": 70000, "shield": 65000, "speed": 80, "cargo": 2000, "slots": 18});
+            let (status, created) = send(connection_pool.clone(), &bearer_token, "POST", "/ship-classes", Some(carrier.clone())).await;
+            assert_eq!(status, StatusCode::CREATED);
+            let (status, _) = send(connection_pool.clone(), &bearer_token, "POST", "/ship-classes", Some(carrier)).await;
+            assert_eq!(status, StatusCode::CONFLICT);
+
+            // Ships can only be of an existing class
+            let (status, error) = send(connection_pool.clone(), &bearer_token, "POST", "/ships/bulk",
+                Some(json!([{"name": "Nidhoggur", "category": "Dreadnought", "empire_id": empire_id}]))).await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            assert_eq!(error["error"], "Ship class does not exist");
+
+            let (status, results) = send(connection_pool.clone(), &bearer_token, "POST", "/ships/bulk",
+                Some(json!([{"name": "Nidhoggur", "category": "Carrier", "empire_id": empire_id}]))).await;
+            assert_eq!(status, StatusCode::OK);
+            let ship_id = results["results"][0]["id"].as_i64().unwrap();
+
+            // Renaming the class renames it in its ships, and a class with ships is not deleted
+            let class_uri = format!("/ship-classes/{}", created["id"]);
+            let renamed = json!({"name": "Force Auxiliary", "hull": 60000, "armor": 70000, "shield": 65000, "speed": 80, "cargo": 2000, "slots": 18});
+            let (status, _) = send(connection_pool.clone(), &bearer_token, "PUT", &class_uri, Some(renamed)).await;
+            assert_eq!(status, StatusCode::OK);
+            let (_, ship) = send(connection_pool.clone(), &bearer_token, "GET", &format!("/ships/{}", ship_id), None).await;
+            assert_eq!((ship["category"].as_str(), &ship["ship_class_id"]), (Some("Force Auxiliary"), &created["id"]));
+
+            let (status, error) = send(connection_pool.clone(), &bearer_token, "DELETE", &class_uri, None).await;
+            assert_eq!(status, StatusCode::CONFLICT);
+            assert_eq!(error["error"], "Ship class is still used by ships");
+
+            // Without its ships the class can go, so the test leaves the classes as it found them
+            let (status, _) = send(connection_pool.clone(), &bearer_token, "DELETE", "/ships/bulk", Some(json!([ship_id]))).await;
+            assert_eq!(status, StatusCode::OK);
+            let (status, _) = send(connection_pool.clone(), &bearer_token, "DELETE", &class_uri, None).await;
+            assert_eq!(status, StatusCode::NO_CONTENT);
+
+            let negative = json!({"name": "Titan", "hull": -1, "armor": 0, "shield": 0, "speed": 0, "cargo": 0, "slots": 0});
+            let (status, _) = send(connection_pool.clone(), &bearer_token, "POST", "/ship-classes", Some(negative)).await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+pub mod service {
+    use diesel::{
+        prelude::*,
+        sql_types::{Nullable, Text},
+        PgConnection,
+        r2d2::{ConnectionManager, PooledConnection},
+    };
+    use crate::{
+        common::{bulk::Transactional, fields::FieldSet, pagination::Page},
+        ships::model::{Ship, ShipClass, ShipQuery, UpsertShip, UpsertShipClass},
+        schema
+    };
+
+    type PooledPg = PooledConnection<ConnectionManager<PgConnection>>;
+
+    // Id of the ship class with the name, see the reference_ship_classes_by_id migration. An unknown name fails the write
+    // with a violation of 'ships_ship_class_id_fkey'
+    define_sql_function!(fn ship_class_id_of(class_name: Nullable<Text>) -> Nullable<Integer>);
+
+    pub struct ShipsTable {
+        connection: PooledPg,
+    }
+
+    impl Transactional for ShipsTable {
+        fn connection(&mut self) -> &mut PgConnection {
+            &mut self.connection
+        }
+    }
+
+    impl ShipsTable {
+        pub fn new(connection: PooledPg) -> ShipsTable {
+            ShipsTable { connection }
+        }
+
+        pub fn create(&mut self, upsert_ship: UpsertShip) -> Result<Ship, diesel::result::Error> {
+            use schema::ships;
+
+            let ship_id = diesel::insert_into(ships::table)
+                .values((
+                    ships::name.eq(&upsert_ship.name),
+                    ships::ship_class_id.eq(ship_class_id_of(&upsert_ship.category)),
+                    ships::description.eq(&upsert_ship.description),
+                    ships::empire_id.eq(upsert_ship.empire_id),
+                ))
+                .returning(ships::id)
+                .get_result(&mut self.connection)?;
+
+            self.written(ship_id)
+        }
+
+        // The ship as written, read back together with the name of its class
+        fn written(&mut self, ship_id: i32) -> Result<Ship, diesel::result::Error> {
+            self.get(ship_id)?.ok_or(diesel::result::Error::NotFound)
+        }
+
+        pub fn get(&mut self, ship_id: i32) -> Result<Option<Ship>, diesel::result::Error> {
+            self.read(ship_id, &FieldSet::all())
+        }
+
+        // The ship with only the columns of the fieldset read
+        pub fn read(&mut self, ship_id: i32, fields: &FieldSet) -> Result<Option<Ship>, diesel::result::Error> {
+            use schema::{ship_classes, ships};
+
+            ships::table.left_join(ship_classes::table)
+                .filter(ships::id.eq(ship_id))
+                .select(Ship::columns(fields))
+                .get_result(&mut self.connection)
+                .optional()
+        }
+
+        // Rows with an id greater than 'after_id' in id order, the keyset pagination used by exports
+        pub fn list_after(&mut self, after_id: i32, limit: i64) -> Result<Vec<Ship>, diesel::result::Error> {
+            use schema::{ship_classes, ships};
+
+            ships::table.left_join(ship_classes::table)
+                .filter(ships::id.gt(after_id))
+                .select(Ship::columns(&FieldSet::all()))
+                .order(ships::id.asc())
+                .limit(limit)
+                .load(&mut self.connection)
+        }
+
+        pub fn get_many(&mut self, ids: &[i32]) -> Result<Vec<Ship>, diesel::result::Error> {
+            use schema::{ship_classes, ships};
+
+            ships::table.left_join(ship_classes::table)
+                .filter(ships::id.eq_any(ids))
+                .select(Ship::columns(&FieldSet::all()))
+                .load(&mut self.connection)
+        }
+
+        // Ships of all the given empires in one query, in id order
+        pub fn list_by_empires(&mut self, empire_ids: &[i32]) -> Result<Vec<Ship>, diesel::result::Error> {
+            use schema::{ship_classes, ships};
+
+            ships::table.left_join(ship_classes::table)
+                .filter(ships::empire_id.eq_any(empire_ids))
+                .select(Ship::columns(&FieldSet::all()))
+                .order(ships::id.asc())
+                .load(&mut self.connection)
+        }
+
+        // Ships of the empire in id order
+        pub fn list_by_empire(&mut self, empire_id: i32, page: Page, fields: &FieldSet) -> Result<Vec<Ship>, diesel::result::Error> {
+            use schema::{ship_classes, ships};
+
+            ships::table.left_join(ship_classes::table)
+                .filter(ships::empire_id.eq(empire_id))
+                .select(Ship::columns(fields))
+                .order(ships::id.asc())
+                .limit(page.limit)
+                .offset(page.offset)
+                .load(&mut self.connection)
+        }
+
+        // Ships in id order, optionally only those of one class
+        pub fn list(&mut self, filter: &ShipQuery, page: Page, fields: &FieldSet) -> Result<Vec<Ship>, diesel::result::Error> {
+            use schema::{ship_classes, ships};
+
+            let mut query = ships::table.left_join(ship_classes::table).into_boxed();
+
+            if let Some(class) = &filter.class {
+                query = query.filter(ship_classes::name.eq(class));
+            }
+
+            query
+                .select(Ship::columns(fields))
+                .order(ships::id.asc())
+                .limit(page.limit)
+                .offset(page.offset)
+                .load(&mut self.connection)
+        }
+
+        pub fn update(&mut self, ship_id: i32, upsert_ship: UpsertShip) -> Result<Ship, diesel::result::Error> {
+            use schema::ships;
+
+            let ship_id = diesel::update(ships::table.find(ship_id))
+                .set((
+                    ships::name.eq(&upsert_ship.name),
+                    ships::ship_class_id.eq(ship_class_id_of(&upsert_ship.category)),
+                    ships::description.eq(&upsert_ship.description),
+                    ships::empire_id.eq(upsert_ship.empire_id),
+                ))
+                .returning(ships::id)
+                .get_result(&mut self.connection)?;
+
+            self.written(ship_id)
+        }
+
+        // Ships are deleted for good. Fails with a foreign key violation while a player flies the ship
+        pub fn delete(&mut self, ship_id: i32) -> Result<(), diesel::result::Error> {
+            use schema::ships;
+
+            let deleted_rows = diesel::delete(ships::table.find(ship_id))
+                .execute(&mut self.connection)?;
+
+            if deleted_rows == 0 {
+                return Err(diesel::result::Error::NotFound);
+            }
+            Ok(())
+        }
+
+        pub fn create_class(&mut self, upsert_ship_class: UpsertShipClass) -> Result<ShipClass, diesel::result::Error> {
+            use schema::ship_classes;
+
+            diesel::insert_into(ship_classes::table)
+                .values(&upsert_ship_class)
+                .get_result(&mut self.connection)
+        }
+
+        pub fn get_class(&mut self, class_id: i32) -> Result<Option<ShipClass>, diesel::result::Error> {
+            use schema::ship_classes;
+
+            ship_classes::table.find(class_id)
+                .get_result(&mut self.connection)
+                .optional()
+        }
+
+        // Every ship class in name order
+        pub fn list_classes(&mut self) -> Result<Vec<ShipClass>, diesel::result::Error> {
+            use schema::ship_classes;
+
+            ship_classes::table
+                .order(ship_classes::name.asc())
+                .load(&mut self.connection)
+        }
+
+        // Renames a class or changes its stats. Ships refer to the class by id and read the new name
+        pub fn update_class(&mut self, class_id: i32, upsert_ship_class: UpsertShipClass) -> Result<ShipClass, diesel::result::Error> {
+            use schema::ship_classes;
+
+            diesel::update(ship_classes::table.find(class_id))
+                .set(&upsert_ship_class)
+                .get_result(&mut self.connection)
+        }
+
+        // Fails with a foreign key violation while ships still use the class
+        pub fn delete_class(&mut self, class_id: i32) -> Result<(), diesel::result::Error> {
+            use schema::ship_classes;
+
+            let deleted_rows = diesel::delete(ship_classes::table.find(class_id))
+                .execute(&mut self.connection)?;
+
+            if deleted_rows == 0 {
+                return Err(diesel::result::Error::NotFound);
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod router;
+pub mod service;
+pub mod model;
+pub mod route;
@@ -0,0 +1,78 @@
+use std::collections::HashSet;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde_derive::{Serialize, Deserialize};
+use utoipa::{IntoParams, ToSchema};
+use crate::{locations::model::{Location, SecurityClass}, schema::stargates};
+
+// A stargate connects two locations and is jumped in both directions
+#[derive(Serialize, Debug, Clone, Queryable, ToSchema)]
+#[diesel(table_name = stargates)]
+pub struct Stargate {
+    pub id: i32,
+    pub from_location_id: i32,
+    pub to_location_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Insertable, Deserialize, Serialize, ToSchema)]
+#[diesel(table_name = stargates)]
+pub struct UpsertStargate {
+    pub from_location_id: i32,
+    pub to_location_id: i32,
+}
+
+// Locations passed through from origin to destination, both included
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct Route {
+    pub jumps: usize,
+    pub locations: Vec<Location>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutePreference {
+    // Fewest jumps
+    #[default]
+    Shortest,
+    // Fewest jumps into low-sec and null-sec
+    Safer,
+    // Fewest jumps into high-sec
+    LessSecure,
+}
+
+impl RoutePreference {
+    // Jumps into a location of the unwanted security class cost as much as this many jumps elsewhere
+    const PENALTY: u64 = 50;
+
+    pub fn jump_cost(&self, security_status: f64) -> u64 {
+        let high_sec = SecurityClass::of(security_status) == SecurityClass::High;
+
+        match self {
+            RoutePreference::Safer if !high_sec => RoutePreference::PENALTY,
+            RoutePreference::LessSecure if high_sec => RoutePreference::PENALTY,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RouteQuery {
+    /// Comma separated ids of locations the route must not pass through
+    pub avoid: Option<String>,
+    /// 'shortest' (default) takes the fewest jumps, 'safer' keeps to high-sec and 'less_secure' keeps out of it where possible
+    #[param(inline)]
+    pub prefer: Option<RoutePreference>,
+}
+
+impl RouteQuery {
+    pub fn avoided(&self) -> Result<HashSet<i32>, String> {
+        self.avoid.iter()
+            .flat_map(|avoid| avoid.split(','))
+            .filter(|id| !id.trim().is_empty())
+            .map(|id| id.trim().parse::<i32>().map_err(|_| format!("Query parameter 'avoid' holds an invalid location id '{}'", id)))
+            .collect()
+    }
+}
@@ -0,0 +1,72 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+// Cheapest path from 'origin' to 'destination' as the ids of the locations passed through, both ends included.
+// Gates are pairs of location ids jumped in both directions, 'cost' is the cost of a jump into a location and 'avoid' lists locations
+// that are never entered. Dijkstra's algorithm over the gates, None when the destination cannot be reached
+pub fn shortest_path(gates: &[(i32, i32)], origin: i32, destination: i32, avoid: &HashSet<i32>, cost: impl Fn(i32) -> u64) -> Option<Vec<i32>> {
+    let mut neighbours: HashMap<i32, Vec<i32>> = HashMap::new();
+    for &(from, to) in gates {
+        neighbours.entry(from).or_default().push(to);
+        neighbours.entry(to).or_default().push(from);
+    }
+
+    let mut costs: HashMap<i32, u64> = HashMap::from([(origin, 0)]);
+    let mut previous: HashMap<i32, i32> = HashMap::new();
+    let mut queue = BinaryHeap::from([Reverse((0, origin))]);
+
+    while let Some(Reverse((location_cost, location))) = queue.pop() {
+        if location == destination {
+            let mut path = vec![destination];
+            while let Some(&before) = previous.get(path.last().unwrap()) {
+                path.push(before);
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        // A shorter way to this location has already been expanded
+        if location_cost > costs[&location] {
+            continue;
+        }
+
+        for &next in neighbours.get(&location).into_iter().flatten() {
+            if avoid.contains(&next) {
+                continue;
+            }
+
+            let next_cost = location_cost + cost(next);
+            if costs.get(&next).is_none_or(|&known| next_cost < known) {
+                costs.insert(next, next_cost);
+                previous.insert(next, location);
+                queue.push(Reverse((next_cost, next)));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::stargates::route::shortest_path;
+
+    #[test]
+    fn shortest_path_takes_fewest_jumps_around_avoided_locations() {
+        let gates = [(1, 2), (2, 3), (3, 4), (1, 5), (5, 4), (6, 7)];
+
+        let jump = |_| 1;
+
+        assert_eq!(shortest_path(&gates, 1, 4, &HashSet::new(), jump), Some(vec![1, 5, 4]));
+        assert_eq!(shortest_path(&gates, 4, 1, &HashSet::from([5]), jump), Some(vec![4, 3, 2, 1]));
+        assert_eq!(shortest_path(&gates, 2, 2, &HashSet::new(), jump), Some(vec![2]));
+        assert_eq!(shortest_path(&gates, 1, 4, &HashSet::from([3, 5]), jump), None);
+        assert_eq!(shortest_path(&gates, 1, 7, &HashSet::new(), jump), None);
+
+        // An expensive location is detoured around even if that takes more jumps
+        assert_eq!(shortest_path(&gates, 1, 4, &HashSet::new(), |location| if location == 5 { 10 } else { 1 }), Some(vec![1, 2, 3, 4]));
+    }
+}
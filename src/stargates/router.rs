@@ -0,0 +1,565 @@
+pub mod router {
+    use std::collections::HashMap;
+    use serde_json::{json, Value};
+    use tracing::error;
+    use axum::{
+        Router, http::StatusCode, Json, response::IntoResponse, extract::{rejection::QueryRejection, Query, State}, extract,
+    };
+    use http::HeaderMap;
+    use crate::{
+        audit::{model::AuditAction, service::service::record_event},
+        common::db::ConnectionPool,
+        locations::service::service::LocationsTable,
+        stargates::{
+            service::service::StargatesTable,
+            model::{Route, RouteQuery, UpsertStargate},
+            route::shortest_path
+        },
+        users::model::UserRole,
+        common::{
+            pagination::{invalid_query, ListQuery, Paginated},
+            security::{enforce_role_policy, decode_claims}
+        }
+    };
+
+    // - - - - - - - - - - - [ROUTES] - - - - - - - - - - -
+
+    pub fn stargates_route(shared_connection_pool: ConnectionPool) -> Router {
+        Router::new()
+            .route("/stargates", axum::routing::post(create_stargate_handler))
+            .route("/stargates", axum::routing::get(list_stargates_handler))
+            .route("/stargates/:stargate_id", axum::routing::get(read_stargate_handler))
+            .route("/stargates/:stargate_id", axum::routing::put(update_stargate_handler))
+            .route("/stargates/:stargate_id", axum::routing::delete(delete_stargate_handler))
+            // The first segment is named like in the locations router, as both are merged into one router
+            .route("/locations/:location_id/route/:destination_id", axum::routing::get(plan_route_handler))
+            .with_state(shared_connection_pool)
+    }
+
+    // - - - - - - - - - - - [HANDLERS] - - - - - - - - - - -
+
+    /// Create a stargate
+    ///
+    /// Requires role WRITER or higher. A stargate is jumped in both directions, so two locations are connected at most once.
+    #[utoipa::path(
+        post,
+        path = "/stargates",
+        tag = "stargates",
+        request_body = UpsertStargate,
+        responses(
+            (status = 201, description = "Stargate created", body = Stargate),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 409, description = "The locations are already connected", body = ErrorBody),
+            (status = 422, description = "Location does not exist or both ends are the same location", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while creating stargate", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn create_stargate_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        Json(upsert_stargate): Json<UpsertStargate>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'WRITER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::WRITER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let result = StargatesTable::new(connection).create(upsert_stargate);
+
+                match result {
+                    Ok(new_stargate) => {
+                        record_event(&shared_state, &headers, AuditAction::Create, "stargate", new_stargate.id, None, Some(&new_stargate));
+                        Ok((StatusCode::CREATED, Json(new_stargate)))
+                    },
+                    Err(err) => Err(write_error(err, "create"))
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// List stargates
+    ///
+    /// Requires role READER or higher. Ordered by modification time, oldest first.
+    #[utoipa::path(
+        get,
+        path = "/stargates",
+        tag = "stargates",
+        params(ListQuery),
+        responses(
+            (status = 200, description = "Page of stargates", body = StargatePage),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing stargates", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_stargates_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<ListQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        let page = query.page()?;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                match StargatesTable::new(connection).list(query.updated_since, page) {
+                    Ok(stargates) => Ok((StatusCode::OK, Json(Paginated::new(stargates, page)))),
+                    Err(err) => {
+                        error!("Error listing stargates: {:?}", err);
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list stargates"}))))
+                    }
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Read a stargate by id
+    ///
+    /// Requires role READER or higher.
+    #[utoipa::path(
+        get,
+        path = "/stargates/{stargate_id}",
+        tag = "stargates",
+        params(("stargate_id" = i32, Path, description = "Id of the stargate")),
+        responses(
+            (status = 200, description = "Stargate found", body = Stargate),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Stargate not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while reading stargate", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn read_stargate_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (stargate_id, ) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                match StargatesTable::new(connection).get(stargate_id) {
+                    Ok(Some(stargate)) => Ok((StatusCode::OK, Json(stargate))),
+                    Ok(None) => Err((StatusCode::NOT_FOUND, Json(json!({"error": "Stargate not found"})))),
+                    Err(err) => {
+                        error!("Error reading stargate: {:?}", err);
+                        Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to read stargate"}))))
+                    }
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Reconnect a stargate
+    ///
+    /// Requires role EDITOR or higher.
+    #[utoipa::path(
+        put,
+        path = "/stargates/{stargate_id}",
+        tag = "stargates",
+        params(("stargate_id" = i32, Path, description = "Id of the stargate")),
+        request_body = UpsertStargate,
+        responses(
+            (status = 200, description = "Stargate updated", body = Stargate),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Stargate not found", body = ErrorBody),
+            (status = 409, description = "The locations are already connected", body = ErrorBody),
+            (status = 422, description = "Location does not exist or both ends are the same location", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while updating stargate", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn update_stargate_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+        Json(upsert_stargate): Json<UpsertStargate>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (stargate_id, ) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'EDITOR' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::EDITOR).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut stargates = StargatesTable::new(connection);
+
+                let before = stargates.get(stargate_id).map_err(|err| write_error(err, "update"))?;
+                let result = stargates.update(stargate_id, upsert_stargate);
+                drop(stargates);
+
+                match result {
+                    Ok(updated_stargate) => {
+                        record_event(&shared_state, &headers, AuditAction::Update, "stargate", stargate_id, before.as_ref(), Some(&updated_stargate));
+                        Ok((StatusCode::OK, Json(updated_stargate)))
+                    },
+                    Err(err) => Err(write_error(err, "update"))
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Delete a stargate
+    ///
+    /// Requires role ADMIN. Stargates are deleted for good.
+    #[utoipa::path(
+        delete,
+        path = "/stargates/{stargate_id}",
+        tag = "stargates",
+        params(("stargate_id" = i32, Path, description = "Id of the stargate")),
+        responses(
+            (status = 204, description = "Stargate deleted"),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Stargate not found", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while deleting stargate", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn delete_stargate_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, )>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (stargate_id, ) = path.0;
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'ADMIN'
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::ADMIN).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+
+                let mut stargates = StargatesTable::new(connection);
+
+                let before = stargates.get(stargate_id).map_err(|err| write_error(err, "delete"))?;
+                let result = stargates.delete(stargate_id);
+                drop(stargates);
+
+                match result {
+                    Ok(_) => {
+                        record_event(&shared_state, &headers, AuditAction::Delete, "stargate", stargate_id, before.as_ref(), None);
+                        Ok((StatusCode::NO_CONTENT, ()))
+                    },
+                    Err(err) => Err(write_error(err, "delete"))
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Plan a route between two locations
+    ///
+    /// Requires role READER or higher. Finds the route with the fewest jumps through stargates between locations that have not been deleted,
+    /// or with '?prefer=' the route with the fewest jumps into unwanted security classes.
+    #[utoipa::path(
+        get,
+        path = "/locations/{location_id}/route/{destination_id}",
+        tag = "stargates",
+        params(
+            ("location_id" = i32, Path, description = "Id of the location the route starts at"),
+            ("destination_id" = i32, Path, description = "Id of the location the route ends at"),
+            RouteQuery
+        ),
+        responses(
+            (status = 200, description = "Shortest route", body = Route),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "Location not found or no route between the locations", body = ErrorBody),
+            (status = 422, description = "The origin or destination is avoided", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while planning route", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn plan_route_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32, i32)>,
+        query: Result<Query<RouteQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (origin_id, destination_id) = path.0;
+        let Query(query) = query.map_err(invalid_query)?;
+
+        let avoid = query.avoided().map_err(|err| (StatusCode::BAD_REQUEST, Json(json!({"error": err}))))?;
+        if avoid.contains(&origin_id) || avoid.contains(&destination_id) {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": "The origin and destination cannot be avoided"}))));
+        }
+
+        // Decode claims from bearer token header
+        let claims = match decode_claims(&headers) {
+            Ok(claims) => claims,
+            Err((status_code, json_value)) => return Err((status_code, json_value)),
+        };
+
+        // Ensure that the user derived from claims exists and has the role 'READER' or higher
+        let authorization = enforce_role_policy(&shared_state, &claims, UserRole::READER).await;
+
+        match authorization {
+            Ok(_authorized_user) => {
+                let planning_failed = |err: diesel::result::Error| {
+                    error!("Error planning route: {:?}", err);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to plan route"})))
+                };
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+                let gates = StargatesTable::new(connection).connections().map_err(planning_failed)?;
+
+                let connection = shared_state.pool.get()
+                    .expect("Failed to acquire connection from pool");
+                let mut locations = LocationsTable::new(connection);
+
+                for location_id in [origin_id, destination_id] {
+                    if locations.get(location_id).map_err(planning_failed)?.is_none() {
+                        return Err((StatusCode::NOT_FOUND, Json(json!({"error": "Location not found"}))));
+                    }
+                }
+
+                let preference = query.prefer.unwrap_or_default();
+                let security_statuses: HashMap<i32, f64> = locations.security_statuses().map_err(planning_failed)?.into_iter().collect();
+                let jump_cost = |location_id: i32| security_statuses.get(&location_id).map_or(1, |&security_status| preference.jump_cost(security_status));
+
+                let path = shortest_path(&gates, origin_id, destination_id, &avoid, jump_cost)
+                    .ok_or((StatusCode::NOT_FOUND, Json(json!({"error": "No route between the locations"}))))?;
+
+                let mut locations_by_id: HashMap<i32, _> = locations.get_many(&path).map_err(planning_failed)?
+                    .into_iter()
+                    .map(|location| (location.id, location))
+                    .collect();
+
+                let route = Route {
+                    jumps: path.len() - 1,
+                    locations: path.iter().filter_map(|location_id| locations_by_id.remove(location_id)).collect(),
+                };
+
+                Ok((StatusCode::OK, Json(route)))
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    // Status and error body of a failed stargate write
+    fn write_error(err: diesel::result::Error, action: &str) -> (StatusCode, Json<Value>) {
+        use diesel::result::{DatabaseErrorKind, Error};
+
+        match err {
+            Error::NotFound => (StatusCode::NOT_FOUND, Json(json!({"error": "Stargate not found"}))),
+            Error::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": "Location does not exist"})))
+            },
+            Error::DatabaseError(DatabaseErrorKind::CheckViolation, _) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": "A stargate must connect two different locations"})))
+            },
+            Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                (StatusCode::CONFLICT, Json(json!({"error": "A stargate already connects these locations"})))
+            },
+            err => {
+                error!("Error writing stargate: {:?}", err);
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to {} stargate", action)})))
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use axum::{
+            body::Body,
+            http::{Request, StatusCode}
+        };
+        use serde_json::{json, Value};
+        use tower::ServiceExt;
+        use crate::{
+            common::{
+                db::{create_shared_connection_pool, ConnectionPool},
+                security::{generate_token, hash_password},
+                util::load_environment_variable
+            },
+            locations::{
+                model::UpsertLocation,
+                service::service::LocationsTable
+            },
+            stargates::{model::UpsertStargate, router::router::stargates_route, service::service::StargatesTable},
+            users::{
+                model::{UpsertUser, UserRole},
+                service::service::UsersTable
+            }
+        };
+
+        fn create_user_and_generate_token(connection_pool: ConnectionPool, email: &str, user_role: UserRole) -> String {
+            let mut new_user = UpsertUser {
+                email: email.to_string(),
+                role: user_role.to_string(),
+                password: "PorterIkkeStjerneporter9".to_string(),
+                fullname: "Navigatør Nilsen".to_string()
+            };
+            hash_password(&mut new_user).expect("Hash failed");
+
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let created_user = UsersTable::new(connection).create(new_user).expect("Create user failed");
+            generate_token(&created_user).expect("Generate token failed")
+        }
+
+        async fn get_json(connection_pool: ConnectionPool, bearer_token: &str, uri: &str) -> (StatusCode, Value) {
+            let request = Request::builder()
+                .uri(uri)
+                .method("GET")
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .body(Body::empty())
+                .unwrap();
+
+            let response = stargates_route(connection_pool).oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            (status, serde_json::from_slice(&body).unwrap())
+        }
+
+        fn route_of(route: &Value) -> Vec<i64> {
+            route["locations"].as_array().unwrap().iter().map(|location| location["id"].as_i64().unwrap()).collect()
+        }
+
+        #[tokio::test]
+        async fn get_route_takes_fewest_jumps_between_connected_systems() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let bearer_token = create_user_and_generate_token(connection_pool.clone(), "navigator@stargate.gb", UserRole::READER);
+
+            // New Eden - Genesis - Amarr - Delve, with Jita bridging New Eden and Amarr and a back door from Genesis into Delve
+            let [new_eden, genesis, jita, amarr, delve] = {
+                let connection = connection_pool.pool.get().expect("Failed to get connection");
+                let mut location_db = LocationsTable::new(connection);
+                [("New Eden", 1.0), ("Genesis", 0.4), ("Jita", 0.9), ("Amarr", 1.0), ("Delve", -0.4)].map(|(area, security_status)| {
+                    let location = UpsertLocation { star_system: "Lonetrek".to_string(), area: area.to_string(), security_status, ..UpsertLocation::default() };
+                    location_db.create(location).expect("Create location failed").id as i64
+                })
+            };
+
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut stargate_db = StargatesTable::new(connection);
+            for (from, to) in [(new_eden, genesis), (new_eden, jita), (genesis, amarr), (jita, amarr), (amarr, delve), (genesis, delve)] {
+                stargate_db.create(UpsertStargate { from_location_id: from as i32, to_location_id: to as i32 }).expect("Create stargate failed");
+            }
+            drop(stargate_db);
+
+            // Jita to Delve runs through Amarr
+            let (status, route) = get_json(connection_pool.clone(), &bearer_token, &format!("/locations/{}/route/{}", jita, delve)).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(route["jumps"], 2);
+            assert_eq!(route_of(&route), vec![jita, amarr, delve]);
+            assert_eq!(route["locations"][1]["area"], "Amarr");
+
+            // Avoiding Amarr takes the long way through New Eden and Genesis
+            let (status, route) = get_json(connection_pool.clone(), &bearer_token, &format!("/locations/{}/route/{}?avoid={}", jita, delve, amarr)).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(route_of(&route), vec![jita, new_eden, genesis, delve]);
+
+            // Avoiding New Eden as well leaves Jita without a way out
+            let (status, _) = get_json(connection_pool.clone(), &bearer_token, &format!("/locations/{}/route/{}?avoid={},{}", jita, delve, amarr, new_eden)).await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+
+            // From Amarr to New Eden the safer route passes high-sec Jita and the less secure one low-sec Genesis
+            let (status, route) = get_json(connection_pool.clone(), &bearer_token, &format!("/locations/{}/route/{}?prefer=safer", amarr, new_eden)).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(route_of(&route), vec![amarr, jita, new_eden]);
+
+            let (_, route) = get_json(connection_pool.clone(), &bearer_token, &format!("/locations/{}/route/{}?prefer=less_secure", amarr, new_eden)).await;
+            assert_eq!(route_of(&route), vec![amarr, genesis, new_eden]);
+
+            let (status, _) = get_json(connection_pool.clone(), &bearer_token, &format!("/locations/{}/route/{}?avoid={}", jita, delve, jita)).await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+            let (status, _) = get_json(connection_pool, &bearer_token, &format!("/locations/{}/route/{}?avoid=amarr", jita, delve)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+        }
+
+        #[tokio::test]
+        async fn post_stargates_rejects_loops_and_duplicate_connections() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let bearer_token = create_user_and_generate_token(connection_pool.clone(), "portbygger@stargate.gb", UserRole::WRITER);
+
+            let (first, second) = {
+                let connection = connection_pool.pool.get().expect("Failed to get connection");
+                let mut location_db = LocationsTable::new(connection);
+                let first = location_db.create(UpsertLocation { star_system: "Molden Heath".to_string(), area: "Egbinger".to_string(), ..UpsertLocation::default() }).unwrap();
+                let second = location_db.create(UpsertLocation { star_system: "Molden Heath".to_string(), area: "Tvink".to_string(), ..UpsertLocation::default() }).unwrap();
+                (first.id, second.id)
+            };
+
+            let post = |body: Value| Request::builder()
+                .uri("/stargates")
+                .method("POST")
+                .header("content-type", "application/json")
+                .header("Authorization", format!("Bearer {}", bearer_token))
+                .body(Body::from(body.to_string()))
+                .unwrap();
+
+            let response = stargates_route(connection_pool.clone()).oneshot(post(json!({"from_location_id": first, "to_location_id": second}))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+
+            // The same connection in the opposite direction already exists
+            let response = stargates_route(connection_pool.clone()).oneshot(post(json!({"from_location_id": second, "to_location_id": first}))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::CONFLICT);
+
+            let response = stargates_route(connection_pool.clone()).oneshot(post(json!({"from_location_id": first, "to_location_id": first}))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+            let response = stargates_route(connection_pool).oneshot(post(json!({"from_location_id": first, "to_location_id": -666}))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
+}
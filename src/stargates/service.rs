@@ -0,0 +1,94 @@
+pub mod service {
+    use diesel::{
+        prelude::*,
+        PgConnection,
+        r2d2::{ConnectionManager, PooledConnection},
+    };
+    use chrono::{DateTime, Utc};
+    use crate::{
+        common::pagination::Page,
+        stargates::model::{Stargate, UpsertStargate},
+        schema
+    };
+
+    type PooledPg = PooledConnection<ConnectionManager<PgConnection>>;
+
+    pub struct StargatesTable {
+        connection: PooledPg,
+    }
+
+    impl StargatesTable {
+        pub fn new(connection: PooledPg) -> StargatesTable {
+            StargatesTable { connection }
+        }
+
+        pub fn create(&mut self, upsert_stargate: UpsertStargate) -> Result<Stargate, diesel::result::Error> {
+            use schema::stargates;
+
+            diesel::insert_into(stargates::table)
+                .values(&upsert_stargate)
+                .get_result(&mut self.connection)
+        }
+
+        pub fn get(&mut self, stargate_id: i32) -> Result<Option<Stargate>, diesel::result::Error> {
+            use schema::stargates;
+
+            stargates::table.find(stargate_id)
+                .get_result(&mut self.connection)
+                .optional()
+        }
+
+        // Rows ordered by modification time, optionally limited to those modified at or after 'updated_since'
+        pub fn list(&mut self, updated_since: Option<DateTime<Utc>>, page: Page) -> Result<Vec<Stargate>, diesel::result::Error> {
+            use schema::stargates;
+
+            let mut query = stargates::table.into_boxed();
+
+            if let Some(updated_since) = updated_since {
+                query = query.filter(stargates::updated_at.ge(updated_since));
+            }
+
+            query
+                .order((stargates::updated_at.asc(), stargates::id.asc()))
+                .limit(page.limit)
+                .offset(page.offset)
+                .load(&mut self.connection)
+        }
+
+        pub fn update(&mut self, stargate_id: i32, upsert_stargate: UpsertStargate) -> Result<Stargate, diesel::result::Error> {
+            use schema::stargates;
+
+            diesel::update(stargates::table.find(stargate_id))
+                .set((
+                    stargates::from_location_id.eq(upsert_stargate.from_location_id),
+                    stargates::to_location_id.eq(upsert_stargate.to_location_id),
+                ))
+                .get_result(&mut self.connection)
+        }
+
+        pub fn delete(&mut self, stargate_id: i32) -> Result<(), diesel::result::Error> {
+            use schema::stargates;
+
+            let deleted_rows = diesel::delete(stargates::table.find(stargate_id))
+                .execute(&mut self.connection)?;
+
+            if deleted_rows == 0 {
+                return Err(diesel::result::Error::NotFound);
+            }
+            Ok(())
+        }
+
+        // Location pairs of every stargate between two locations that have not been deleted, the graph routes are planned on
+        pub fn connections(&mut self) -> Result<Vec<(i32, i32)>, diesel::result::Error> {
+            use schema::{locations, stargates};
+
+            let visible_locations = locations::table.filter(locations::deleted_at.is_null()).select(locations::id);
+
+            stargates::table
+                .filter(stargates::from_location_id.eq_any(visible_locations))
+                .filter(stargates::to_location_id.eq_any(visible_locations))
+                .select((stargates::from_location_id, stargates::to_location_id))
+                .load(&mut self.connection)
+        }
+    }
+}
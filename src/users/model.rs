@@ -1,19 +1,54 @@
 use std::fmt;
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use regex::Regex;
 use serde_derive::{Serialize, Deserialize};
-use crate::schema::users;
+use utoipa::ToSchema;
+use diesel::{dsl::sql, expression::SqlLiteral, sql_types::{Integer, Nullable, Text, Timestamptz}};
+use crate::{common::{fields::FieldSet, patch::changed}, schema::users};
 
-#[derive(Debug, Clone, Serialize, Queryable)]
+#[derive(Debug, Clone, Serialize, Queryable, ToSchema)]
 #[diesel(table_name = users)]
 pub struct User {
     pub id: i32,
     pub email: String,
     pub password: String,
     pub fullname: String,
-    pub role: String
+    pub role: String,
+    #[serde(skip)]
+    pub version: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+// Selection loading a User, see User::columns
+pub type UserColumns = (
+    SqlLiteral<Integer>, SqlLiteral<Text>, SqlLiteral<Text>, SqlLiteral<Text>, SqlLiteral<Text>, SqlLiteral<Integer>,
+    SqlLiteral<Timestamptz>, SqlLiteral<Timestamptz>, SqlLiteral<Nullable<Timestamptz>>,
+);
+
+impl User {
+    pub const FIELDS: [&'static str; 7] = ["id", "email", "password", "fullname", "role", "created_at", "updated_at"];
+
+    // Columns of the row in model order, reading only those of the fieldset besides the id and the version served as ETag
+    pub fn columns(fields: &FieldSet) -> UserColumns {
+        (
+            sql("users.id"),
+            fields.column("email", "users.email", "''"),
+            fields.column("password", "users.password", "''"),
+            fields.column("fullname", "users.fullname", "''"),
+            fields.column("role", "users.role", "''"),
+            sql("users.version"),
+            fields.column("created_at", "users.created_at", "'epoch'::timestamptz"),
+            fields.column("updated_at", "users.updated_at", "'epoch'::timestamptz"),
+            sql("NULL"),
+        )
+    }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum UserRole {
     READER,
@@ -46,7 +81,7 @@ pub fn string_to_user_role(role: String) -> UserRole {
 }
 
 
-#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable, ToSchema)]
 #[diesel(table_name = users)]
 pub struct UpsertUser {
     pub email: String,
@@ -62,7 +97,39 @@ impl UpsertUser {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl From<User> for UpsertUser {
+    fn from(user: User) -> Self {
+        UpsertUser { email: user.email, password: user.password, fullname: user.fullname, role: user.role }
+    }
+}
+
+// Columns changed by a merge patch, unchanged columns are left as None and skipped in the UPDATE
+#[derive(Debug, Clone, Default, AsChangeset)]
+#[diesel(table_name = users)]
+pub struct UserChangeset {
+    pub email: Option<String>,
+    pub password: Option<String>,
+    pub fullname: Option<String>,
+    pub role: Option<String>,
+}
+
+impl UserChangeset {
+    // The password is compared as stored, so callers set it explicitly whenever the patch contains one
+    pub fn between(current: &UpsertUser, merged: &UpsertUser) -> UserChangeset {
+        UserChangeset {
+            email: changed(&current.email, &merged.email),
+            password: changed(&current.password, &merged.password),
+            fullname: changed(&current.fullname, &merged.fullname),
+            role: changed(&current.role, &merged.role),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.email.is_none() && self.password.is_none() && self.fullname.is_none() && self.role.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct LoginUser {
     pub email: String,
     pub password: String
@@ -1,15 +1,26 @@
 pub mod router {
     use serde_json::{json, Value};
+    use tracing::error;
     use bcrypt::verify;
-    use axum::{extract, extract::State, http::StatusCode, Json, response::IntoResponse, Router};
+    use axum::{extract, extract::{rejection::QueryRejection, Query, State}, http::{header, HeaderMap, StatusCode}, Json, response::IntoResponse, Router};
     use crate::{
+        audit::{model::AuditAction, service::service::record_event},
         common::{
             db::ConnectionPool,
-            security::{hash_password, generate_token}},
+            etag::{check_if_match, etag, is_not_modified, not_modified, precondition_failed},
+            fields::FieldsQuery,
+            metrics::record_login_attempt,
+            pagination::{invalid_query, ListQuery, Paginated},
+            patch::apply_merge_patch,
+            security::{decode_claims, enforce_role_policy, hash_password, generate_token}},
         users::{
             service::service::UsersTable,
             model::{
+                string_to_user_role,
                 UpsertUser,
+                User,
+                UserChangeset,
+                UserRole,
                 LoginUser,
             },
         },
@@ -20,9 +31,12 @@ pub mod router {
     pub fn users_route(shared_connection_pool: ConnectionPool) -> Router {
         Router::new()
             .route("/users", axum::routing::post(create_user_handler))
+            .route("/users", axum::routing::get(list_users_handler))
             .route("/users/:user_id", axum::routing::get(get_user_handler))
             .route("/users/:user_id", axum::routing::put(update_user_handler))
+            .route("/users/:user_id", axum::routing::patch(patch_user_handler))
             .route("/users/:user_id", axum::routing::delete(delete_user_handler))
+            .route("/users/:user_id/restore", axum::routing::post(restore_user_handler))
             .route("/users/login", axum::routing::post(login_user_handler))
             .with_state(shared_connection_pool)
     }
@@ -30,7 +44,20 @@ pub mod router {
 
     // - - - - - - - - - - - [HANDLERS] - - - - - - - - - - -
 
+    /// Register a user
+    #[utoipa::path(
+        post,
+        path = "/users",
+        tag = "users",
+        request_body = UpsertUser,
+        responses(
+            (status = 201, description = "User created", body = User),
+            (status = 422, description = "Invalid email or user could not be created", body = ErrorBody),
+            (status = 500, description = "Failure while hashing password", body = ErrorBody)
+        )
+    )]
     pub async fn create_user_handler(
+        headers: HeaderMap,
         State(shared_state): State<ConnectionPool>,
         Json(mut body): Json<UpsertUser>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
@@ -43,10 +70,15 @@ pub mod router {
         let connection = shared_state.pool.get()
             .expect("Failed to acquire connection from pool");
 
-        match UsersTable::new(connection).create(body) {
-            Ok(created_user) => Ok((StatusCode::CREATED, Json(created_user))),
+        let result = UsersTable::new(connection).create(body);
+
+        match result {
+            Ok(created_user) => {
+                record_event(&shared_state, &headers, AuditAction::Create, "user", created_user.id, None, Some(&created_user));
+                Ok((StatusCode::CREATED, Json(created_user)))
+            },
             Err(err) => {
-                eprintln!("Create user failed: {:?}", err);
+                error!("Create user failed: {:?}", err);
                 Err((StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": "Failed to create user"}))))
             }
         }
@@ -56,36 +88,180 @@ pub mod router {
         body.is_valid_email()
     }
 
+    /// List users
+    ///
+    /// Requires role ADMIN. Ordered by modification time, oldest first. '?fields=' limits the fields of each user.
+    #[utoipa::path(
+        get,
+        path = "/users",
+        tag = "users",
+        params(ListQuery, FieldsQuery),
+        responses(
+            (status = 200, description = "Page of users", body = UserPage),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while listing users", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn list_users_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        query: Result<Query<ListQuery>, QueryRejection>,
+        fields: Result<Query<FieldsQuery>, QueryRejection>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let Query(query) = query.map_err(invalid_query)?;
+        let page = query.page()?;
+        let fields = fields.map_err(invalid_query)?.parse(&User::FIELDS)?;
+
+        // Unlike the other user routes, listing exposes every account and is therefore limited to ADMIN
+        let claims = decode_claims(&headers)?;
+        enforce_role_policy(&shared_state, &claims, UserRole::ADMIN).await?;
+
+        let connection = shared_state.pool.get()
+            .expect("Failed to acquire connection from pool");
+
+        match UsersTable::new(connection).list(query.updated_since, page, &fields) {
+            Ok(users) => {
+                let items = users.iter().map(|user| fields.project(user, &[])).collect();
+                Ok((StatusCode::OK, Json(Paginated::new(items, page))))
+            }
+            Err(err) => {
+                error!("Error listing users: {:?}", err);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to list users"}))))
+            }
+        }
+    }
+
+    /// Read a user by id
+    ///
+    /// '?fields=' limits the fields of the user.
+    #[utoipa::path(
+        get,
+        path = "/users/{user_id}",
+        tag = "users",
+        params(("user_id" = i32, Path, description = "Id of the user"), FieldsQuery),
+        responses(
+            (status = 200, description = "User found", body = User, headers(("ETag" = String, description = "Version of the user"))),
+            (status = 304, description = "User matches If-None-Match"),
+            (status = 400, description = "Malformed query parameters", body = ErrorBody),
+            (status = 404, description = "User not found", body = ErrorBody),
+            (status = 500, description = "Failure while reading user", body = ErrorBody)
+        )
+    )]
     pub async fn get_user_handler(
+        headers: HeaderMap,
         State(shared_state): State<ConnectionPool>,
         path: extract::Path<(i32,)>,
+        fields: Result<Query<FieldsQuery>, QueryRejection>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
         let (user_id,) = path.0;
+        let fields = fields.map_err(invalid_query)?.parse(&User::FIELDS)?;
 
         let connection = shared_state.pool.get()
             .expect("Failed to acquire connection from pool");
 
         let mut users = UsersTable::new(connection);
 
-        match users.get(user_id) {
+        match users.read(user_id, &fields) {
             Ok(user) => {
                 if let Some(user) = user {
-                    Ok((StatusCode::OK, Json(user)))
+                    if is_not_modified(&headers, user.version) {
+                        return Ok(not_modified(user.version));
+                    }
+                    Ok((StatusCode::OK, [(header::ETAG, etag(user.version))], Json(fields.project(&user, &[]))).into_response())
                 } else {
                     Err((StatusCode::NOT_FOUND, Json(json!({"error": "User not found"}))))
                 }
             },
             Err(err) => {
-                eprintln!("Error reading user: {:?}", err);
+                error!("Error reading user: {:?}", err);
                 Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to read user"}))))
             }
         }
     }
 
+    /// Replace a user
+    ///
+    /// The password is hashed before it is stored, as on create.
+    #[utoipa::path(
+        put,
+        path = "/users/{user_id}",
+        tag = "users",
+        params(("user_id" = i32, Path, description = "Id of the user")),
+        request_body = UpsertUser,
+        responses(
+            (status = 200, description = "User updated", body = User, headers(("ETag" = String, description = "Version of the user"))),
+            (status = 404, description = "User not found", body = ErrorBody),
+            (status = 412, description = "If-Match does not match the current version", body = ErrorBody),
+            (status = 500, description = "Failure while hashing password or updating user", body = ErrorBody)
+        )
+    )]
     pub async fn update_user_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32,)>,
+        Json(mut update_user): Json<UpsertUser>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (user_id,) = path.0;
+
+        let connection = shared_state.pool.get()
+            .expect("Failed to acquire connection from pool");
+
+        let mut users = UsersTable::new(connection);
+
+        let before = users.get(user_id).map_err(|err| {
+            error!("Error reading user: {:?}", err);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to update user"})))
+        })?;
+
+        let expected_version = check_if_match(&headers, || Ok(before.as_ref().map(|user| user.version)))?;
+
+        hash_password(&mut update_user)?;
+
+        let result = users.update(user_id, update_user, expected_version);
+        // Hand the connection back to the pool before the audit event is recorded
+        drop(users);
+
+        match result {
+            Ok(updated_user) => {
+                record_event(&shared_state, &headers, AuditAction::Update, "user", user_id, before.as_ref(), Some(&updated_user));
+                Ok((StatusCode::OK, [(header::ETAG, etag(updated_user.version))], Json(updated_user)))
+            },
+            // A conditional write matching no row lost the race against a concurrent write
+            Err(diesel::result::Error::NotFound) if expected_version.is_some() => Err(precondition_failed()),
+            Err(diesel::result::Error::NotFound) => {
+                Err((StatusCode::NOT_FOUND, Json(json!({"error": "User not found"}))))
+            },
+            Err(err) => {
+                error!("Error updating user: {:?}", err);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to update user"}))))
+            }
+        }
+    }
+
+    /// Partially update a user with a JSON merge patch (RFC 7396)
+    ///
+    /// Only the columns changed by the patch are written. A password in the patch is always hashed before it is stored.
+    #[utoipa::path(
+        patch,
+        path = "/users/{user_id}",
+        tag = "users",
+        params(("user_id" = i32, Path, description = "Id of the user")),
+        request_body(content = Object, content_type = "application/merge-patch+json", description = "Merge patch applied to the UpsertUser representation"),
+        responses(
+            (status = 200, description = "User updated", body = User, headers(("ETag" = String, description = "Version of the user"))),
+            (status = 404, description = "User not found", body = ErrorBody),
+            (status = 412, description = "If-Match does not match the current version", body = ErrorBody),
+            (status = 422, description = "Patch is not an object, names unknown fields or yields an invalid user", body = ErrorBody),
+            (status = 500, description = "Failure while hashing password or updating user", body = ErrorBody)
+        )
+    )]
+    pub async fn patch_user_handler(
+        headers: HeaderMap,
         State(shared_state): State<ConnectionPool>,
         path: extract::Path<(i32,)>,
-        Json(update_user): Json<UpsertUser>,
+        Json(patch): Json<Value>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
         let (user_id,) = path.0;
 
@@ -94,19 +270,76 @@ pub mod router {
 
         let mut users = UsersTable::new(connection);
 
-        match users.update(user_id, update_user) {
-            Ok(updated_user) => Ok((StatusCode::OK, Json(updated_user))),
+        let existing = match users.get(user_id) {
+            Ok(Some(user)) => user,
+            Ok(None) => return Err((StatusCode::NOT_FOUND, Json(json!({"error": "User not found"})))),
+            Err(err) => {
+                error!("Error reading user: {:?}", err);
+                return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to update user"}))));
+            }
+        };
+
+        let expected_version = check_if_match(&headers, || Ok(Some(existing.version)))?;
+        let current = UpsertUser::from(existing.clone());
+
+        // Merge the patch into the current representation, rejecting documents that no longer form a valid user
+        let mut merged = apply_merge_patch(&current, &patch)
+            .map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": err}))))?;
+
+        if !validate_email(&merged) {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": "Invalid input for field 'email'"}))));
+        }
+
+        if string_to_user_role(merged.role.clone()) == UserRole::INVALID {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": "Invalid input for field 'role'"}))));
+        }
+
+        let mut changes = UserChangeset::between(&current, &merged);
+
+        // The stored password is a hash, so a password in the patch is hashed and written even if it equals the stored value
+        if patch.get("password").is_some() {
+            hash_password(&mut merged)?;
+            changes.password = Some(merged.password);
+        }
+
+        let result = users.patch(user_id, changes, expected_version);
+        drop(users);
+
+        match result {
+            Ok(updated_user) => {
+                record_event(&shared_state, &headers, AuditAction::Patch, "user", user_id, Some(&existing), Some(&updated_user));
+                Ok((StatusCode::OK, [(header::ETAG, etag(updated_user.version))], Json(updated_user)))
+            },
+            Err(diesel::result::Error::NotFound) if expected_version.is_some() => Err(precondition_failed()),
             Err(diesel::result::Error::NotFound) => {
                 Err((StatusCode::NOT_FOUND, Json(json!({"error": "User not found"}))))
             },
+            Err(diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::UniqueViolation, _)) => {
+                Err((StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": "Email is already in use"}))))
+            },
             Err(err) => {
-                eprintln!("Error updating user: {:?}", err);
+                error!("Error updating user: {:?}", err);
                 Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to update user"}))))
             }
         }
     }
 
+    /// Delete a user
+    ///
+    /// The account is hidden and its email freed until an ADMIN restores it or it is purged.
+    #[utoipa::path(
+        delete,
+        path = "/users/{user_id}",
+        tag = "users",
+        params(("user_id" = i32, Path, description = "Id of the user")),
+        responses(
+            (status = 204, description = "User deleted"),
+            (status = 412, description = "If-Match does not match the current version", body = ErrorBody),
+            (status = 500, description = "Failure while deleting user", body = ErrorBody)
+        )
+    )]
     pub async fn delete_user_handler(
+        headers: HeaderMap,
         State(shared_state): State<ConnectionPool>,
         path: extract::Path<(i32,)>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
@@ -117,15 +350,94 @@ pub mod router {
 
         let mut users = UsersTable::new(connection);
 
-        match users.delete(user_id) {
-            Ok(_) => Ok((StatusCode::NO_CONTENT, ())),
+        let before = users.get(user_id).map_err(|err| {
+            error!("Error reading user: {:?}", err);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to delete user"})))
+        })?;
+
+        let expected_version = check_if_match(&headers, || Ok(before.as_ref().map(|user| user.version)))?;
+
+        let result = users.delete(user_id, expected_version);
+        drop(users);
+
+        match result {
+            Ok(_) => {
+                record_event(&shared_state, &headers, AuditAction::Delete, "user", user_id, before.as_ref(), None);
+                Ok((StatusCode::NO_CONTENT, ()))
+            },
+            Err(diesel::result::Error::NotFound) if expected_version.is_some() => Err(precondition_failed()),
             Err(err) => {
-                eprintln!("Error deleting user: {:?}", err);
+                error!("Error deleting user: {:?}", err);
                 Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to delete user"}))))
             }
         }
     }
 
+    /// Restore a deleted user
+    ///
+    /// Requires role ADMIN.
+    #[utoipa::path(
+        post,
+        path = "/users/{user_id}/restore",
+        tag = "users",
+        params(("user_id" = i32, Path, description = "Id of the deleted user")),
+        responses(
+            (status = 200, description = "User restored", body = User, headers(("ETag" = String, description = "Version of the user"))),
+            (status = 401, description = "Missing or insufficient role", body = ErrorBody),
+            (status = 404, description = "No deleted user with this id", body = ErrorBody),
+            (status = 409, description = "The email has been registered again since the user was deleted", body = ErrorBody),
+            (status = 500, description = "Missing header or failure while restoring user", body = ErrorBody)
+        ),
+        security(("bearer_auth" = []))
+    )]
+    pub async fn restore_user_handler(
+        headers: HeaderMap,
+        State(shared_state): State<ConnectionPool>,
+        path: extract::Path<(i32,)>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+        let (user_id,) = path.0;
+
+        let claims = decode_claims(&headers)?;
+        enforce_role_policy(&shared_state, &claims, UserRole::ADMIN).await?;
+
+        let connection = shared_state.pool.get()
+            .expect("Failed to acquire connection from pool");
+
+        let result = UsersTable::new(connection).restore(user_id);
+
+        match result {
+            Ok(restored_user) => {
+                record_event(&shared_state, &headers, AuditAction::Restore, "user", user_id, None, Some(&restored_user));
+                Ok((StatusCode::OK, [(header::ETAG, etag(restored_user.version))], Json(restored_user)))
+            },
+            Err(diesel::result::Error::NotFound) => {
+                Err((StatusCode::NOT_FOUND, Json(json!({"error": "No deleted user with this id"}))))
+            },
+            Err(diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::UniqueViolation, _)) => {
+                Err((StatusCode::CONFLICT, Json(json!({"error": "Email is already in use by another user"}))))
+            },
+            Err(err) => {
+                error!("Error restoring user: {:?}", err);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to restore user"}))))
+            }
+        }
+    }
+
+    /// Exchange credentials for a bearer token
+    ///
+    /// The token is returned as a bare JSON string and expires after one hour.
+    #[utoipa::path(
+        post,
+        path = "/users/login",
+        tag = "users",
+        request_body = LoginUser,
+        responses(
+            (status = 200, description = "Signed JWT", body = String),
+            (status = 401, description = "Wrong password", body = ErrorBody),
+            (status = 404, description = "User not found", body = ErrorBody),
+            (status = 500, description = "Failure while reading user or generating token", body = ErrorBody)
+        )
+    )]
     pub async fn login_user_handler(
         State(shared_state): State<ConnectionPool>,
         Json(body): Json<LoginUser>,
@@ -135,20 +447,26 @@ pub mod router {
 
         match UsersTable::new(connection).get_by_email(body.email.clone()) {
             Ok(Some(user)) if body.email == user.email => {
-                return if verify(&body.password, &user.password).unwrap_or(false) {
-                    if let Some(token) = generate_token(&user).ok() {
+                if verify(&body.password, &user.password).unwrap_or(false) {
+                    if let Ok(token) = generate_token(&user) {
+                        record_login_attempt("success");
                         Ok((StatusCode::OK, Json(token)))
                     } else {
+                        record_login_attempt("error");
                         Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to generate token"}))))
                     }
                 } else {
+                    record_login_attempt("wrong_password");
                     Err((StatusCode::UNAUTHORIZED, Json(json!({"error": "Wrong password"}))))
                 }
             }
-            Ok(Some(_)) => Err((StatusCode::NOT_FOUND, Json(json!({"error": "User not found"})))),
-            Ok(None) => Err((StatusCode::NOT_FOUND, Json(json!({"error": "User not found"})))),
+            Ok(Some(_)) | Ok(None) => {
+                record_login_attempt("unknown_user");
+                Err((StatusCode::NOT_FOUND, Json(json!({"error": "User not found"}))))
+            },
             Err(err) => {
-                eprintln!("Error reading user: {:?}", err);
+                error!("Error reading user: {:?}", err);
+                record_login_attempt("error");
                 Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to read user"}))))
             }
         }
@@ -160,7 +478,9 @@ pub mod router {
         use axum::http::{Request, StatusCode};
         use serde_json::json;
         use tower::ServiceExt;
-        use crate::{create_shared_connection_pool, load_environment_variable, users_route};
+        use crate::audit::{model::AuditQuery, service::service::AuditTable};
+        use crate::common::{db::create_shared_connection_pool, pagination::Page, util::load_environment_variable};
+        use crate::users::router::router::users_route;
         use crate::users::model::UpsertUser;
         use crate::users::service::service::UsersTable;
 
@@ -195,6 +515,52 @@ pub mod router {
             assert_eq!(response.status(), StatusCode::CREATED);
         }
 
+        #[tokio::test]
+        async fn post_users_records_audit_event_without_password() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 1);
+            let service = users_route(connection_pool.clone());
+
+            let request_body = UpsertUser {
+                email: "nyansatt@revisjon.no".to_string(),
+                password: "Hemmelig42".to_string(),
+                fullname: "Nina Nyansatt".to_string(),
+                role: "READER".to_string()
+            };
+
+            let request = Request::builder()
+                .uri("/users")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_string(&request_body).unwrap()))
+                .unwrap();
+
+            let response = service
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::CREATED);
+
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let created_user: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+            let filter = AuditQuery {
+                resource_type: Some("user".to_string()),
+                resource_id: created_user["id"].as_i64().map(|id| id as i32),
+                ..AuditQuery::default()
+            };
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let events = AuditTable::new(connection).list(&filter, Page::new(None, None).unwrap()).expect("List audit events failed");
+
+            // Self registration has no actor, and the password hash never reaches the audit log
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].action, "create");
+            assert!(events[0].actor.is_none());
+            assert_eq!(events[0].after.as_ref().unwrap()["email"], "nyansatt@revisjon.no");
+            assert!(events[0].after.as_ref().unwrap().get("password").is_none());
+        }
+
         #[tokio::test]
         async fn post_users_returns_422_on_invalid_email() {
             let database_url = load_environment_variable("TEST_DB");
@@ -280,17 +646,103 @@ pub mod router {
             let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
             let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
 
-            // Construct JSON consisting of expected payload
+            // Construct JSON consisting of expected payload, the password being stored as a hash
             let expected_response = json!({
                 "id": created_user.id,
                 "email": updated_request_body.email,
-                "password": updated_request_body.password,
+                "password": response_json["password"],
                 "fullname": updated_request_body.fullname,
-                "role": updated_request_body.role
+                "role": updated_request_body.role,
+                "created_at": created_user.created_at,
+                "updated_at": response_json["updated_at"]
             });
 
             // Assert equality
             assert_eq!(response_json, expected_response);
+            assert!(bcrypt::verify(&updated_request_body.password, response_json["password"].as_str().unwrap()).unwrap());
+
+            // Assert that the trigger advanced the modification time
+            let updated_at: chrono::DateTime<chrono::Utc> = serde_json::from_value(response_json["updated_at"].clone()).unwrap();
+            assert!(updated_at > created_user.updated_at);
+        }
+
+        #[tokio::test]
+        async fn patch_users_returns_200_and_hashes_patched_password() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut user_db = UsersTable::new(connection);
+            let service = users_route(connection_pool);
+
+            let request_body = UpsertUser {
+                email: "lappen@snowmail.com".to_string(),
+                password: "gammeltpassord".to_string(),
+                fullname: "Lars Lappen".to_string(),
+                role: "READER".to_string()
+            };
+
+            // Create a new user with the above data
+            let created_user = user_db.create(request_body.clone()).expect("Create user failed");
+
+            // Create a request with a merge patch changing only the password
+            let request = Request::builder()
+                .uri(format!("/users/{}", created_user.id))
+                .method("PATCH")
+                .header("content-type", "application/merge-patch+json")
+                .body(Body::from(json!({"password": "nyttpassord"}).to_string()))
+                .unwrap();
+
+            // Send the request through the service
+            let response = service
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            // Assert that the response status is 200
+            assert_eq!(response.status(), StatusCode::OK);
+
+            // Extract body from response
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+            // Assert that untouched fields are kept and the password is stored as a hash of the patched value
+            assert_eq!(response_json["email"], request_body.email);
+            assert_eq!(response_json["fullname"], request_body.fullname);
+            assert_ne!(response_json["password"], "nyttpassord");
+            assert!(bcrypt::verify("nyttpassord", response_json["password"].as_str().unwrap()).unwrap());
+        }
+
+        #[tokio::test]
+        async fn patch_users_returns_422_on_invalid_email() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 2);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut user_db = UsersTable::new(connection);
+            let service = users_route(connection_pool);
+
+            let created_user = user_db.create(UpsertUser {
+                email: "snorre@snowmail.com".to_string(),
+                password: "Big100".to_string(),
+                fullname: "Snorre Snø".to_string(),
+                role: "READER".to_string()
+            }).expect("Create user failed");
+
+            // Create a request with a merge patch yielding an invalid email
+            let request = Request::builder()
+                .uri(format!("/users/{}", created_user.id))
+                .method("PATCH")
+                .header("content-type", "application/merge-patch+json")
+                .body(Body::from(json!({"email": "eg-klare-meg"}).to_string()))
+                .unwrap();
+
+            // Send the request through the service
+            let response = service
+                .oneshot(request)
+                .await
+                .unwrap();
+
+            // Assert that the response status is 422
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
         }
 
         #[tokio::test]
@@ -337,7 +789,9 @@ pub mod router {
                 "email": request_body.email,
                 "password": request_body.password,
                 "fullname": request_body.fullname,
-                "role": request_body.role
+                "role": request_body.role,
+                "created_at": created_user.created_at,
+                "updated_at": created_user.updated_at
             });
 
             // Assert equality
@@ -7,10 +7,12 @@ pub mod service {
         r2d2::{ConnectionManager, PooledConnection},
     };
 
+    use chrono::{DateTime, Utc};
+
     use crate::{
-        users::model::{User, UpsertUser},
+        users::model::{User, UserChangeset, UpsertUser},
         schema,
-        common::error::CustomError
+        common::{error::CustomError, fields::FieldSet, pagination::Page}
     };
 
     type PooledPg = PooledConnection<ConnectionManager<PgConnection>>;
@@ -25,6 +27,11 @@ pub mod service {
         }
 
         pub fn create(&mut self, create_user: UpsertUser) -> Result<User, CustomError> {
+            Self::insert(&mut self.connection, &create_user)
+        }
+
+        // Takes the connection so that the insert can share a transaction with writes to other tables, as when fixtures are seeded
+        pub fn insert(connection: &mut PgConnection, create_user: &UpsertUser) -> Result<User, CustomError> {
             use schema::users;
 
             diesel::insert_into(users::table)
@@ -34,16 +41,22 @@ pub mod service {
                     users::fullname.eq(&create_user.fullname),
                     users::role.eq(&create_user.role),
                 ))
-                .get_result::<User>(&mut self.connection)
+                .get_result::<User>(connection)
                 .map_err(|err| {
                     CustomError::from_diesel_err(err, "while creating user")
                 })
         }
 
         pub fn get(&mut self, user_id: i32) -> Result<Option<User>, diesel::result::Error> {
+            self.read(user_id, &FieldSet::all())
+        }
+
+        // The user with only the columns of the fieldset read
+        pub fn read(&mut self, user_id: i32, fields: &FieldSet) -> Result<Option<User>, diesel::result::Error> {
             use schema::users;
 
-            let user = users::table.find(user_id)
+            let user = users::table.find(user_id).filter(users::deleted_at.is_null())
+                .select(User::columns(fields))
                 .get_result(&mut self.connection)
                 .optional()?;
 
@@ -55,55 +68,132 @@ pub mod service {
 
             let user = users::table
                 .filter(users::email.eq(email))
+                .filter(users::deleted_at.is_null())
                 .get_result(&mut self.connection)
                 .optional()?;
 
             Ok(user)
         }
 
-        pub fn update(&mut self, user_id: i32, update_user: UpsertUser) -> Result<User, Error> {
+        // Rows ordered by modification time, optionally limited to those modified at or after 'updated_since'
+        pub fn list(&mut self, updated_since: Option<DateTime<Utc>>, page: Page, fields: &FieldSet) -> Result<Vec<User>, diesel::result::Error> {
             use schema::users;
 
-            // Check if the user exists before attempting to update
-            let existing_user = users::table.find(user_id)
-                .get_result::<User>(&mut self.connection);
-
-            match existing_user {
-                Ok(_) => {
-                    let updated_user = diesel::update(users::table.find(user_id))
-                        .set((
-                            users::email.eq(&update_user.email),
-                            users::password.eq(&update_user.password),
-                            users::fullname.eq(&update_user.fullname),
-                            users::role.eq(&update_user.role),
-                        ))
-                        .get_result(&mut self.connection)
-                        .expect("Update user failed");
-
-                    Ok(updated_user)
-                },
-                Err(_) => Err(Error::NotFound)
+            let mut query = users::table.filter(users::deleted_at.is_null()).into_boxed();
+
+            if let Some(updated_since) = updated_since {
+                query = query.filter(users::updated_at.ge(updated_since));
             }
+
+            query
+                .select(User::columns(fields))
+                .order((users::updated_at.asc(), users::id.asc()))
+                .limit(page.limit)
+                .offset(page.offset)
+                .load(&mut self.connection)
         }
 
+        pub fn update(&mut self, user_id: i32, update_user: UpsertUser, expected_version: Option<i32>) -> Result<User, Error> {
+            use schema::users;
+
+            // The user is locked from the check until it is written, so a write without an expected version cannot lose a race
+            self.connection.transaction(|connection| {
+                // Check if the user exists before attempting to update
+                let existing_user = users::table.find(user_id).filter(users::deleted_at.is_null())
+                    .for_update()
+                    .get_result::<User>(connection);
+
+                match existing_user {
+                    Ok(existing) => {
+                        // Without an expected version the write is conditioned on the version just read
+                        let expected_version = expected_version.unwrap_or(existing.version);
+
+                        let updated_user = diesel::update(users::table.find(user_id).filter(users::version.eq(expected_version)))
+                            .set((
+                                users::email.eq(&update_user.email),
+                                users::password.eq(&update_user.password),
+                                users::fullname.eq(&update_user.fullname),
+                                users::role.eq(&update_user.role),
+                                users::version.eq(users::version + 1),
+                            ))
+                            .get_result(connection)?;
+
+                        Ok(updated_user)
+                    },
+                    Err(_) => Err(Error::NotFound)
+                }
+            })
+        }
 
-        pub fn delete(&mut self, user_id: i32) -> Result<(), diesel::result::Error> {
+
+        // Only the columns present in the changeset are written
+        pub fn patch(&mut self, user_id: i32, changes: UserChangeset, expected_version: Option<i32>) -> Result<User, diesel::result::Error> {
             use schema::users;
 
-            // Check if the location exists before attempting to delete
-            let existing_location = users::table.find(user_id)
-                .get_result::<User>(&mut self.connection);
-
-            match existing_location {
-                Ok(_) => {
-                    diesel::delete(users::table.find(user_id))
-                        .execute(&mut self.connection)?;
-                    Ok(())
-                },
-                Err(_) => {
-                    Err(Error::NotFound)
+            // The user is locked from the read until it is written, so a write without an expected version cannot lose a race
+            self.connection.transaction(|connection| {
+                let current = users::table.find(user_id).filter(users::deleted_at.is_null()).for_update().get_result::<User>(connection)?;
+                let expected_version = expected_version.unwrap_or(current.version);
+
+                // An empty changeset is not a valid UPDATE, so the current row is returned as is
+                if changes.is_empty() {
+                    return if current.version == expected_version { Ok(current) } else { Err(diesel::result::Error::NotFound) };
                 }
-            }
+
+                diesel::update(users::table.find(user_id).filter(users::version.eq(expected_version)))
+                    .set((&changes, users::version.eq(users::version + 1)))
+                    .get_result(connection)
+            })
+        }
+
+        // Marks the row as deleted, hiding it from reads until it is restored or purged
+        pub fn delete(&mut self, user_id: i32, expected_version: Option<i32>) -> Result<(), diesel::result::Error> {
+            use schema::users;
+
+            // The user is locked from the check until it is deleted, so a delete without an expected version cannot lose a race
+            self.connection.transaction(|connection| {
+                // Check if the location exists before attempting to delete
+                let existing_location = users::table.find(user_id).filter(users::deleted_at.is_null())
+                    .for_update()
+                    .get_result::<User>(connection);
+
+                match existing_location {
+                    Ok(existing) => {
+                        let deleted_rows = diesel::update(users::table.find(user_id).filter(users::version.eq(expected_version.unwrap_or(existing.version))))
+                            .set((users::deleted_at.eq(Some(Utc::now())), users::version.eq(users::version + 1)))
+                            .execute(connection)?;
+
+                        // No deleted row means the version differs from the expected version
+                        if deleted_rows == 0 {
+                            return Err(Error::NotFound);
+                        }
+                        Ok(())
+                    },
+                    Err(_) => {
+                        Err(Error::NotFound)
+                    }
+                }
+            })
+        }
+
+        // Undoes a soft delete. Fails with NotFound unless the row exists and is deleted
+        pub fn restore(&mut self, user_id: i32) -> Result<User, diesel::result::Error> {
+            use schema::users;
+
+            diesel::update(users::table.find(user_id).filter(users::deleted_at.is_not_null()))
+                .set((users::deleted_at.eq(None::<DateTime<Utc>>), users::version.eq(users::version + 1)))
+                .get_result(&mut self.connection)
+        }
+
+        // Permanently removes rows deleted before the cutoff. Rows still referenced by other tables are kept until those references are gone
+        pub fn purge(&mut self, deleted_before: DateTime<Utc>) -> Result<usize, diesel::result::Error> {
+            use diesel::dsl::{exists, not};
+            use schema::users;
+
+            diesel::delete(users::table)
+                .filter(users::deleted_at.lt(deleted_before))
+                .filter(not(exists(schema::players::table.filter(schema::players::user_id.eq(users::id)))))
+                .execute(&mut self.connection)
         }
     }
 
@@ -246,7 +336,7 @@ pub mod service {
                 role: "READER".to_string()
             };
 
-            let updated_user = user_db.update(original_user.id, updated_request.clone()).expect("Update user failed");
+            let updated_user = user_db.update(original_user.id, updated_request.clone(), None).expect("Update user failed");
 
             assert_eq!(updated_user.email, updated_request.email);
             assert_eq!(updated_user.password, updated_request.password);
@@ -268,11 +358,37 @@ pub mod service {
                 role: "READER".to_string()
             };
 
-            let result = user_db.update(-666, request.clone());  // Use a non-existent ID
+            let result = user_db.update(-666, request.clone(), None);  // Use a non-existent ID
 
             assert!(result.is_err());  // Expecting an error as the ID is not present
         }
 
+        #[test]
+        fn create_succeeds_on_email_of_deleted_user() {
+            let database_url = load_environment_variable("TEST_DB");
+            let connection_pool = create_shared_connection_pool(database_url, 1);
+            let connection = connection_pool.pool.get().expect("Failed to get connection");
+            let mut user_db = UsersTable::new(connection);
+
+            let request = UpsertUser {
+                email: "second.chance@mongols.com".to_string(),
+                password: "IkkeGiOpp".to_string(),
+                fullname: "Anne Gang".to_string(),
+                role: "READER".to_string()
+            };
+
+            let deleted_user = user_db.create(request.clone()).expect("Create user failed");
+            user_db.delete(deleted_user.id, None).expect("Delete user failed");
+
+            // The email is free again, but the deleted account can't be restored while it is taken
+            let new_user = user_db.create(request.clone()).expect("Create user failed");
+            assert_ne!(new_user.id, deleted_user.id);
+            assert!(matches!(
+                user_db.restore(deleted_user.id),
+                Err(diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::UniqueViolation, _))
+            ));
+        }
+
         #[test]
         fn delete_succeeds_on_existing_id() {
             let database_url = load_environment_variable("TEST_DB");
@@ -288,7 +404,7 @@ pub mod service {
             };
 
             let user = user_db.create(request.clone()).expect("Create user failed");
-            user_db.delete(user.id.clone()).expect("Delete user failed");
+            user_db.delete(user.id.clone(), None).expect("Delete user failed");
             let deleted_user = user_db.get(user.id).expect("Read user failed");
 
             assert!(deleted_user.is_none()); // Expecting lack of value as user has been deleted
@@ -300,7 +416,7 @@ pub mod service {
             let connection_pool = create_shared_connection_pool(database_url, 1);
             let connection = connection_pool.pool.get().expect("Failed to get connection");
             let mut user_db = UsersTable::new(connection);
-            let result = user_db.delete(-666);  // Use a non-existent ID
+            let result = user_db.delete(-666, None);  // Use a non-existent ID
 
             assert!(result.is_err());  // Expecting an error as the ID is not present
         }